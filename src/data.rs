@@ -1,4 +1,7 @@
 pub mod color64;
 pub mod point64;
+pub mod polynomial;
 pub mod ray;
+pub mod ray_packet;
+pub mod storage;
 pub mod vector3;
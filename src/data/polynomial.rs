@@ -0,0 +1,229 @@
+//! Real-root solvers for low-degree polynomials, used by analytic ray/surface intersections.
+
+use std::f64::consts::TAU;
+
+/// Up to four real roots, in ascending order.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Roots {
+    values: [f64; 4],
+    len: usize,
+}
+
+impl Roots {
+    fn push(&mut self, root: f64) {
+        if self.len < self.values.len() && root.is_finite() {
+            self.values[self.len] = root;
+            self.len += 1;
+        }
+    }
+
+    fn sort(&mut self) {
+        self.values[..self.len].sort_by(|a, b| a.total_cmp(b));
+    }
+
+    pub fn as_slice(&self) -> &[f64] {
+        &self.values[..self.len]
+    }
+}
+
+/// Real roots of `a·x² + b·x + c = 0`.
+///
+/// Uses the cancellation-free form of the quadratic formula. A discriminant within
+/// `tolerance` below zero is treated as a double root so tangent rays are not lost to rounding.
+fn quadratic_roots(a: f64, b: f64, c: f64, tolerance: f64, roots: &mut Roots) {
+    if a == 0. {
+        if b != 0. {
+            roots.push(-c / b);
+        }
+        return;
+    }
+
+    let mut discriminant = b * b - 4. * a * c;
+    if discriminant < 0. {
+        if discriminant < -tolerance {
+            return;
+        }
+        discriminant = 0.;
+    }
+
+    if discriminant == 0. {
+        roots.push(-0.5 * b / a);
+        return;
+    }
+
+    let q = -0.5 * (b + b.signum() * discriminant.sqrt());
+    roots.push(q / a);
+    if q != 0. {
+        roots.push(c / q);
+    }
+}
+
+/// Real roots of `a·x² + b·x + c = 0`, in ascending order.
+#[allow(dead_code)]
+pub fn solve_quadratic(a: f64, b: f64, c: f64) -> Roots {
+    let mut roots = Roots::default();
+    quadratic_roots(a, b, c, 0., &mut roots);
+    roots.sort();
+    roots
+}
+
+/// Real roots of the monic cubic `x³ + a·x² + b·x + c = 0`, in ascending order.
+pub fn solve_cubic(a: f64, b: f64, c: f64) -> Roots {
+    let mut roots = Roots::default();
+    let q = (a * a - 3. * b) / 9.;
+    let r = (2. * a * a * a - 9. * a * b + 27. * c) / 54.;
+    let q3 = q * q * q;
+    let shift = a / 3.;
+
+    if r * r < q3 {
+        // Three distinct real roots: trigonometric form.
+        let theta = (r / q3.sqrt()).clamp(-1., 1.).acos();
+        let scale = -2. * q.sqrt();
+        for k in 0..3 {
+            roots.push(scale * ((theta + TAU * k as f64) / 3.).cos() - shift);
+        }
+    } else {
+        let big_a = -r.signum() * (r.abs() + (r * r - q3).sqrt()).cbrt();
+        let big_b = if big_a != 0. { q / big_a } else { 0. };
+        roots.push(big_a + big_b - shift);
+    }
+
+    for root in roots.values[..roots.len].iter_mut() {
+        *root = polish(*root, &[1., a, b, c]);
+    }
+    roots.sort();
+    roots
+}
+
+/// Real roots of the monic quartic `x⁴ + a·x³ + b·x² + c·x + d = 0`, in ascending order.
+///
+/// Ferrari's method on the depressed quartic, with every root refined by Newton iterations
+/// against the original polynomial to recover precision lost in the resolvent cubic.
+pub fn solve_quartic(a: f64, b: f64, c: f64, d: f64) -> Roots {
+    let a2 = a * a;
+    // Substitute x = y - a/4 to obtain y⁴ + p·y² + q·y + r = 0.
+    let p = b - 3. / 8. * a2;
+    let q = c - 0.5 * a * b + 0.125 * a2 * a;
+    let r = d - 0.25 * a * c + a2 * b / 16. - 3. * a2 * a2 / 256.;
+    let shift = 0.25 * a;
+
+    let scale = 1. + p.abs() + r.abs().sqrt();
+    let tolerance = 1e-12 * scale * scale;
+
+    let mut depressed = Roots::default();
+
+    if q.abs() <= 1e-14 * scale * scale * scale {
+        // Biquadratic: solve for y² and take both square roots.
+        let mut squares = Roots::default();
+        quadratic_roots(1., p, r, tolerance, &mut squares);
+        for &y2 in squares.as_slice() {
+            if y2 > 0. {
+                let y = y2.sqrt();
+                depressed.push(-y);
+                depressed.push(y);
+            } else if y2 > -tolerance {
+                depressed.push(0.);
+            }
+        }
+    } else {
+        // Resolvent cubic m³ + p·m² + (p²/4 - r)·m - q²/8 = 0; its largest root is positive
+        // and splits the quartic into two real quadratics.
+        let resolvent = solve_cubic(p, 0.25 * p * p - r, -0.125 * q * q);
+        let m = match resolvent.as_slice().last() {
+            Some(&m) if m > 0. => m,
+            _ => return Roots::default(),
+        };
+
+        let s = (2. * m).sqrt();
+        let half_p_plus_m = 0.5 * p + m;
+        let q_over_2s = q / (2. * s);
+        quadratic_roots(1., s, half_p_plus_m - q_over_2s, tolerance, &mut depressed);
+        quadratic_roots(1., -s, half_p_plus_m + q_over_2s, tolerance, &mut depressed);
+    }
+
+    let mut roots = Roots::default();
+    for &y in depressed.as_slice() {
+        roots.push(polish(y - shift, &[1., a, b, c, d]));
+    }
+    roots.sort();
+    roots
+}
+
+/// Evaluates a polynomial (coefficients in descending degree) and its derivative at `x`.
+fn evaluate(coefficients: &[f64], x: f64) -> (f64, f64) {
+    coefficients
+        .iter()
+        .fold((0., 0.), |(value, derivative), &coeff| {
+            (value * x + coeff, derivative * x + value)
+        })
+}
+
+/// A few Newton steps, keeping the original guess if they fail to improve it.
+fn polish(x: f64, coefficients: &[f64]) -> f64 {
+    let mut best = x;
+    let (mut best_value, _) = evaluate(coefficients, x);
+
+    let mut current = x;
+    for _ in 0..4 {
+        let (value, derivative) = evaluate(coefficients, current);
+        if derivative == 0. {
+            break;
+        }
+        current -= value / derivative;
+        let (new_value, _) = evaluate(coefficients, current);
+        if new_value.abs() < best_value.abs() {
+            best = current;
+            best_value = new_value;
+        } else {
+            break;
+        }
+    }
+
+    best
+}
+
+#[cfg(test)]
+mod test {
+    use super::{solve_cubic, solve_quadratic, solve_quartic};
+    use approx::assert_abs_diff_eq;
+
+    #[test]
+    fn quadratic_roots() {
+        let roots = solve_quadratic(1., -3., 2.);
+        assert_eq!(roots.as_slice(), &[1., 2.]);
+        assert!(solve_quadratic(1., 0., 1.).as_slice().is_empty());
+    }
+
+    #[test]
+    fn cubic_roots() {
+        // (x - 1)(x - 2)(x - 3)
+        let roots = solve_cubic(-6., 11., -6.);
+        assert_eq!(roots.as_slice().len(), 3);
+        for (root, expected) in roots.as_slice().iter().zip([1., 2., 3.]) {
+            assert_abs_diff_eq!(*root, expected, epsilon = 1e-10);
+        }
+    }
+
+    #[test]
+    fn quartic_roots() {
+        // (x + 2)(x - 1)(x - 3)(x - 4) = x⁴ - 6x³ + 3x² + 26x - 24
+        let roots = solve_quartic(-6., 3., 26., -24.);
+        assert_eq!(roots.as_slice().len(), 4);
+        for (root, expected) in roots.as_slice().iter().zip([-2., 1., 3., 4.]) {
+            assert_abs_diff_eq!(*root, expected, epsilon = 1e-10);
+        }
+
+        // (x² + 1)(x² + 4) has no real roots.
+        assert!(solve_quartic(0., 5., 0., 4.).as_slice().is_empty());
+    }
+
+    #[test]
+    fn quartic_double_roots() {
+        // (x - 1)²(x - 5)² = x⁴ - 12x³ + 46x² - 60x + 25
+        let roots = solve_quartic(-12., 46., -60., 25.);
+        assert!(!roots.as_slice().is_empty());
+        for root in roots.as_slice() {
+            assert!((root - 1.).abs() < 1e-6 || (root - 5.).abs() < 1e-6);
+        }
+    }
+}
@@ -1,6 +1,6 @@
 use crate::data::color64::{BLACK, Color64};
 use crate::data::point64::Point64;
-use crate::hittables::Hittable;
+use crate::hittables::{HitRecord, Hittable};
 use rand::Rng;
 use std::ops::Add;
 
@@ -11,7 +11,7 @@ pub struct Ray {
 }
 
 /// After this many bounces, use Russian roulette to terminate diffuse paths.
-const RR_MIN_BOUNCES: i32 = 3;
+pub(crate) const RR_MIN_BOUNCES: i32 = 3;
 
 impl Add for Color64 {
     type Output = Self;
@@ -26,30 +26,48 @@ impl Ray {
         self.origin + self.direction * t
     }
 
-    pub fn color_in_world(
+    fn color_in_world_recurse(
         &self,
         world: &Hittable,
         background: &Color64,
         max_depth: i32,
+        depth: i32,
         rng: &mut impl Rng,
     ) -> Color64 {
-        self.color_in_world_recurse(world, background, max_depth, max_depth, rng)
+        if depth < 1 {
+            return BLACK;
+        }
+
+        let hit_record = world.is_hit_by(self, 0.001, f64::INFINITY);
+        self.color_from_hit(hit_record, world, background, max_depth, depth, rng)
     }
 
-    fn color_in_world_recurse(
+    /// Color seen along this ray, given its closest hit in `world`, which callers trace
+    /// themselves so primary rays can be found in packets. Bounces are traced one ray at a
+    /// time.
+    pub fn color_in_world_from_hit(
         &self,
+        hit_record: Option<HitRecord>,
         world: &Hittable,
         background: &Color64,
         max_depth: i32,
-        depth: i32,
         rng: &mut impl Rng,
     ) -> Color64 {
-        if depth < 1 {
+        if max_depth < 1 {
             return BLACK;
         }
+        self.color_from_hit(hit_record, world, background, max_depth, max_depth, rng)
+    }
 
-        let hit_record = world.is_hit_by(self, 0.001, f64::INFINITY);
-
+    fn color_from_hit(
+        &self,
+        hit_record: Option<HitRecord>,
+        world: &Hittable,
+        background: &Color64,
+        max_depth: i32,
+        depth: i32,
+        rng: &mut impl Rng,
+    ) -> Color64 {
         match hit_record {
             Some(hit_record) => {
                 let emitted =
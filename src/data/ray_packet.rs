@@ -0,0 +1,60 @@
+use crate::data::ray::Ray;
+use crate::hittables::axis_aligned_bounding_box::AxisAlignedBoundingBox;
+use std::array;
+
+/// Number of rays traced together by [`Hittable::packet_hits`](crate::hittables::Hittable::packet_hits).
+pub const PACKET_WIDTH: usize = 4;
+
+/// Rays traced through a hierarchy together. Origins and inverse directions are stored one
+/// array per axis, so box tests run the same arithmetic across all lanes.
+pub struct RayPacket {
+    pub rays: [Ray; PACKET_WIDTH],
+    origins: [[f64; PACKET_WIDTH]; 3],
+    inv_directions: [[f64; PACKET_WIDTH]; 3],
+}
+
+impl RayPacket {
+    pub fn new(rays: [Ray; PACKET_WIDTH]) -> Self {
+        let origins = array::from_fn(|axis| array::from_fn(|lane| rays[lane].origin.0[axis]));
+        let inv_directions =
+            array::from_fn(|axis| array::from_fn(|lane| 1. / rays[lane].direction.0[axis]));
+        Self {
+            rays,
+            origins,
+            inv_directions,
+        }
+    }
+
+    pub fn inv_direction(&self, axis: usize, lane: usize) -> f64 {
+        self.inv_directions[axis][lane]
+    }
+
+    /// Which lanes enter `bounds` within `[t_min, t_max[lane]]`. Lanes outside `active` are
+    /// reported as misses.
+    pub fn hits_box(
+        &self,
+        bounds: &AxisAlignedBoundingBox,
+        active: [bool; PACKET_WIDTH],
+        t_min: f64,
+        t_max: &[f64; PACKET_WIDTH],
+    ) -> [bool; PACKET_WIDTH] {
+        let mut t0 = [t_min; PACKET_WIDTH];
+        let mut t1 = *t_max;
+        for axis in 0..3 {
+            for lane in 0..PACKET_WIDTH {
+                let inv_direction = self.inv_directions[axis][lane];
+                let ta = (bounds.minimum.0[axis] - self.origins[axis][lane]) * inv_direction;
+                let tb = (bounds.maximum.0[axis] - self.origins[axis][lane]) * inv_direction;
+                // Same comparisons as the single-ray slab test, so both agree at the edges.
+                let (near, far) = if inv_direction < 0. {
+                    (tb, ta)
+                } else {
+                    (ta, tb)
+                };
+                t0[lane] = t0[lane].max(near);
+                t1[lane] = t1[lane].min(far);
+            }
+        }
+        array::from_fn(|lane| active[lane] && t0[lane] < t1[lane])
+    }
+}
@@ -0,0 +1,111 @@
+//! Precision of stored geometry. Meshes and their hierarchies keep coordinates as [`Real`],
+//! which is `f32` with the `f32-geometry` feature and `f64` otherwise; everything computed
+//! from them, including intersections and shading, stays in `f64`.
+//!
+//! Single-precision vertices are widened exactly before intersecting, so a ray hits the
+//! stored (rounded) triangle with full double-precision accuracy and spawned rays need no
+//! more offset than with `f64` storage. Bounds are rounded outwards, so a box never shrinks
+//! away from the geometry inside it.
+
+use crate::data::point64::Point64;
+use crate::hittables::axis_aligned_bounding_box::AxisAlignedBoundingBox;
+use nalgebra::Vector3;
+
+#[cfg(feature = "f32-geometry")]
+pub type Real = f32;
+#[cfg(not(feature = "f32-geometry"))]
+pub type Real = f64;
+
+/// Exact conversion of a stored value for computing with.
+#[allow(clippy::useless_conversion)]
+pub fn widen(x: Real) -> f64 {
+    f64::from(x)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+pub struct StoredVector(pub [Real; 3]);
+
+impl StoredVector {
+    /// Nearest representable vector.
+    pub fn new(v: &Vector3<f64>) -> Self {
+        Self([v.x as Real, v.y as Real, v.z as Real])
+    }
+
+    /// Largest representable vector no greater than `v` in any component.
+    fn rounded_down(v: &Vector3<f64>) -> Self {
+        Self([0, 1, 2].map(|axis| {
+            let rounded = v[axis] as Real;
+            if widen(rounded) > v[axis] {
+                rounded.next_down()
+            } else {
+                rounded
+            }
+        }))
+    }
+
+    /// Smallest representable vector no less than `v` in any component.
+    fn rounded_up(v: &Vector3<f64>) -> Self {
+        Self([0, 1, 2].map(|axis| {
+            let rounded = v[axis] as Real;
+            if widen(rounded) < v[axis] {
+                rounded.next_up()
+            } else {
+                rounded
+            }
+        }))
+    }
+
+    pub fn load(&self) -> Vector3<f64> {
+        Vector3::new(widen(self.0[0]), widen(self.0[1]), widen(self.0[2]))
+    }
+}
+
+/// An [`AxisAlignedBoundingBox`] in stored precision, containing the box it was made from.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct StoredBounds {
+    pub minimum: StoredVector,
+    pub maximum: StoredVector,
+}
+
+impl StoredBounds {
+    pub fn new(bounds: &AxisAlignedBoundingBox) -> Self {
+        Self {
+            minimum: StoredVector::rounded_down(&bounds.minimum.0),
+            maximum: StoredVector::rounded_up(&bounds.maximum.0),
+        }
+    }
+
+    pub fn load(&self) -> AxisAlignedBoundingBox {
+        AxisAlignedBoundingBox {
+            minimum: Point64(self.minimum.load()),
+            maximum: Point64(self.maximum.load()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{StoredBounds, StoredVector};
+    use crate::data::point64::Point64;
+    use crate::hittables::axis_aligned_bounding_box::AxisAlignedBoundingBox;
+    use nalgebra::Vector3;
+
+    #[test]
+    fn stored_bounds_contain_the_original() {
+        let bounds = AxisAlignedBoundingBox {
+            minimum: Point64::new(0.1, -1. / 3., 1e7 + 0.3),
+            maximum: Point64::new(0.7, 2. / 3., 1e7 + 0.9),
+        };
+        let stored = StoredBounds::new(&bounds).load();
+        for axis in 0..3 {
+            assert!(stored.minimum.0[axis] <= bounds.minimum.0[axis]);
+            assert!(stored.maximum.0[axis] >= bounds.maximum.0[axis]);
+        }
+
+        // Values that are already representable are kept exactly.
+        let exact = Vector3::new(0.5, -2., 1024.);
+        assert_eq!(StoredVector::new(&exact).load(), exact);
+        assert_eq!(StoredVector::rounded_down(&exact).load(), exact);
+        assert_eq!(StoredVector::rounded_up(&exact).load(), exact);
+    }
+}
@@ -53,7 +53,7 @@ pub fn reflect(vec: &Vector, normal: &Vector) -> Vector {
 pub fn refract(vec: &Vector, normal: &Vector, etai_over_etat: f64) -> Vector {
     let cos_theta = -vec.dot(normal).min(1.);
     let r_out_normal = etai_over_etat * (*vec + cos_theta * *normal);
-    let r_out_parallel = -(1. - r_out_normal.magnitude()).abs().sqrt() * *normal;
+    let r_out_parallel = -(1. - r_out_normal.magnitude_squared()).abs().sqrt() * *normal;
     r_out_normal + r_out_parallel
 }
 
@@ -152,8 +152,22 @@ mod test {
         assert_abs_diff_eq!(reflect(&v1, &v2), Vector::new(-255.0, -318.0, -381.0));
         assert_abs_diff_eq!(
             refract(&v1, &v2, 0.5),
-            Vector::new(-6.556601564455425, -7.820751955569282, -9.084902346683137),
+            Vector::new(
+                -11.091663046625438,
+                -13.489578808281799,
+                -15.887494569938157
+            ),
             epsilon = EPSILON
         );
+
+        // Snell's law: a unit ray 60° off the normal leaves glass at asin(sin 60° / 1.5).
+        let (sin_i, cos_i) = 60_f64.to_radians().sin_cos();
+        let refracted = refract(
+            &Vector::new(sin_i, -cos_i, 0.),
+            &Vector::new(0., 1., 0.),
+            1. / 1.5,
+        );
+        assert_abs_diff_eq!(refracted.magnitude(), 1., epsilon = EPSILON);
+        assert_abs_diff_eq!(refracted.x, sin_i / 1.5, epsilon = EPSILON);
     }
 }
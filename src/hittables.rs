@@ -1,51 +1,75 @@
 use crate::data::point64::Point64;
 use crate::data::ray::Ray;
+use crate::data::ray_packet::{PACKET_WIDTH, RayPacket};
 use crate::hittables::axis_aligned_bounding_box::AxisAlignedBoundingBox;
 use crate::materials::Material;
 
-mod axis_aligned_bounding_box;
+pub mod axis_aligned_bounding_box;
 pub mod bounded_volume_hierarchy;
-mod bvh_comparators;
 pub mod constant_medium;
+pub mod csg;
 pub mod cuboid;
+pub mod curve;
+pub mod heightfield;
 pub mod hittable_vec;
+pub mod linear_bvh;
+pub mod metaballs;
 pub mod moving_sphere;
+pub mod plane;
 pub mod quad;
 pub mod rotation;
+pub mod signed_distance_field;
 pub mod sphere;
+pub mod top_level_bvh;
+pub mod torus;
 pub mod translation;
 pub mod triangle;
 
 use bounded_volume_hierarchy::BoundedVolumeHierarchy;
 pub use constant_medium::ConstantMedium;
+use csg::Csg;
 use cuboid::Cuboid;
+use curve::Curve;
+use heightfield::Heightfield;
 use hittable_vec::HittableVec;
+use linear_bvh::LinearBvh;
+use metaballs::Metaballs;
 use moving_sphere::MovingSphere;
+use plane::Plane;
 use quad::Quad;
 use rotation::Rotation;
+use signed_distance_field::SignedDistanceField;
 use sphere::Sphere;
+use top_level_bvh::TopLevelBvh;
+use torus::Torus;
 use translation::Translation;
 use triangle::Triangle;
 
 #[derive(Clone)]
-pub struct HitRecord {
+pub struct HitRecord<'a> {
     pub value: f64,
     pub u: f64,
     pub v: f64,
     pub location: Point64,
     pub normal: Point64,
     pub front_face: bool,
-    pub material: Material,
+    /// Index of refraction of the medium on the front side of the surface: 1 for air, or a
+    /// coating's when a [`Layered`](crate::materials::Layered) material scatters off its base.
+    /// Materials with an index of refraction use theirs relative to this one.
+    pub outside_ior: f64,
+    /// Borrowed from the hittable that was hit, so recording a hit never copies material
+    /// or texture data.
+    pub material: &'a Material,
 }
 
-impl HitRecord {
+impl<'a> HitRecord<'a> {
     pub fn new(
         value: f64,
         ray: &Ray,
         outward_normal: Point64,
-        material: Material,
+        material: &'a Material,
         uv: (f64, f64),
-    ) -> HitRecord {
+    ) -> HitRecord<'a> {
         let front_face = ray.direction.0.dot(&outward_normal.0) < 0.;
 
         let normal = if front_face {
@@ -61,11 +85,42 @@ impl HitRecord {
             location: ray.point_at_parameter(value),
             normal,
             front_face,
+            outside_ior: 1.,
             material,
         }
     }
 }
 
+/// A span of a ray inside a closed hittable, from the hit where it enters to the hit where
+/// it leaves.
+#[derive(Clone)]
+pub struct HitInterval<'a> {
+    pub enter: HitRecord<'a>,
+    pub exit: HitRecord<'a>,
+}
+
+/// Pairs hits sorted along a ray into entry/exit intervals using `front_face`. Unmatched
+/// hits (tangent grazes, numerical noise) are dropped.
+pub(crate) fn pair_intervals<'a>(
+    hits: impl IntoIterator<Item = HitRecord<'a>>,
+) -> Vec<HitInterval<'a>> {
+    let mut intervals = vec![];
+    let mut enter: Option<HitRecord> = None;
+
+    for hr in hits {
+        if hr.front_face {
+            enter.get_or_insert(hr);
+        } else if let Some(enter) = enter.take() {
+            intervals.push(HitInterval { enter, exit: hr });
+        }
+    }
+
+    intervals
+}
+
+/// Upper bound on hits collected by [`Hittable::hit_intervals`]'s generic fallback.
+const MAX_INTERVAL_HITS: usize = 64;
+
 #[allow(dead_code)]
 #[derive(Clone)]
 pub enum Hittable {
@@ -75,10 +130,20 @@ pub enum Hittable {
     Triangle(Triangle),
     HittableVec(HittableVec),
     Bvh(BoundedVolumeHierarchy),
+    LinearBvh(LinearBvh),
+    TopLevelBvh(TopLevelBvh),
     Translation(Translation),
     Cuboid(Cuboid),
-    Rotation(Rotation),
+    // Large, rarely repeated variants are boxed so that a mesh's triangles set the size.
+    Rotation(Box<Rotation>),
     ConstantMedium(ConstantMedium),
+    Torus(Torus),
+    Csg(Csg),
+    SignedDistanceField(SignedDistanceField),
+    Heightfield(Box<Heightfield>),
+    Curve(Curve),
+    Metaballs(Metaballs),
+    Plane(Plane),
 }
 
 impl Hittable {
@@ -90,14 +155,23 @@ impl Hittable {
             Hittable::Triangle(h) => h.bounding_box(time0, time1),
             Hittable::HittableVec(h) => h.bounding_box(time0, time1),
             Hittable::Bvh(h) => h.bounding_box(time0, time1),
+            Hittable::LinearBvh(h) => h.bounding_box(time0, time1),
+            Hittable::TopLevelBvh(h) => h.bounding_box(time0, time1),
             Hittable::Translation(h) => h.bounding_box(time0, time1),
             Hittable::Cuboid(h) => h.bounding_box(time0, time1),
             Hittable::Rotation(h) => h.bounding_box(time0, time1),
             Hittable::ConstantMedium(h) => h.bounding_box(time0, time1),
+            Hittable::Torus(h) => h.bounding_box(time0, time1),
+            Hittable::Csg(h) => h.bounding_box(time0, time1),
+            Hittable::SignedDistanceField(h) => h.bounding_box(time0, time1),
+            Hittable::Heightfield(h) => h.bounding_box(time0, time1),
+            Hittable::Curve(h) => h.bounding_box(time0, time1),
+            Hittable::Metaballs(h) => h.bounding_box(time0, time1),
+            Hittable::Plane(h) => h.bounding_box(time0, time1),
         }
     }
 
-    pub fn is_hit_by(&self, ray: &Ray, min_value: f64, max_value: f64) -> Option<HitRecord> {
+    pub fn is_hit_by(&self, ray: &Ray, min_value: f64, max_value: f64) -> Option<HitRecord<'_>> {
         match self {
             Hittable::Sphere(h) => h.is_hit_by(ray, min_value, max_value),
             Hittable::MovingSphere(h) => h.is_hit_by(ray, min_value, max_value),
@@ -105,10 +179,107 @@ impl Hittable {
             Hittable::Triangle(h) => h.is_hit_by(ray, min_value, max_value),
             Hittable::HittableVec(h) => h.is_hit_by(ray, min_value, max_value),
             Hittable::Bvh(h) => h.is_hit_by(ray, min_value, max_value),
+            Hittable::LinearBvh(h) => h.is_hit_by(ray, min_value, max_value),
+            Hittable::TopLevelBvh(h) => h.is_hit_by(ray, min_value, max_value),
             Hittable::Translation(h) => h.is_hit_by(ray, min_value, max_value),
             Hittable::Cuboid(h) => h.is_hit_by(ray, min_value, max_value),
             Hittable::Rotation(h) => h.is_hit_by(ray, min_value, max_value),
             Hittable::ConstantMedium(h) => h.is_hit_by(ray, min_value, max_value),
+            Hittable::Torus(h) => h.is_hit_by(ray, min_value, max_value),
+            Hittable::Csg(h) => h.is_hit_by(ray, min_value, max_value),
+            Hittable::SignedDistanceField(h) => h.is_hit_by(ray, min_value, max_value),
+            Hittable::Heightfield(h) => h.is_hit_by(ray, min_value, max_value),
+            Hittable::Curve(h) => h.is_hit_by(ray, min_value, max_value),
+            Hittable::Metaballs(h) => h.is_hit_by(ray, min_value, max_value),
+            Hittable::Plane(h) => h.is_hit_by(ray, min_value, max_value),
+        }
+    }
+
+    /// Whether anything blocks the ray within `(min_value, max_value)`, for shadow and other
+    /// visibility rays. Unlike [`is_hit_by`](Self::is_hit_by), hierarchies stop at the first
+    /// hit they find and common primitives skip building a [`HitRecord`]; the rest fall back
+    /// to the closest-hit query.
+    pub fn occluded(&self, ray: &Ray, min_value: f64, max_value: f64) -> bool {
+        match self {
+            Hittable::Sphere(h) => h.occluded(ray, min_value, max_value),
+            Hittable::Quad(h) => h.occluded(ray, min_value, max_value),
+            Hittable::Triangle(h) => h.occluded(ray, min_value, max_value),
+            Hittable::HittableVec(h) => h.occluded(ray, min_value, max_value),
+            Hittable::Bvh(h) => h.occluded(ray, min_value, max_value),
+            Hittable::LinearBvh(h) => h.occluded(ray, min_value, max_value),
+            Hittable::TopLevelBvh(h) => h.occluded(ray, min_value, max_value),
+            Hittable::Translation(h) => h.occluded(ray, min_value, max_value),
+            Hittable::Cuboid(h) => h.occluded(ray, min_value, max_value),
+            Hittable::Rotation(h) => h.occluded(ray, min_value, max_value),
+            Hittable::Plane(h) => h.occluded(ray, min_value, max_value),
+            _ => self.is_hit_by(ray, min_value, max_value).is_some(),
+        }
+    }
+
+    /// Closest hit for each ray of a packet, with a separate upper bound per lane.
+    /// Hierarchies test the whole packet against each node; everything else traces the rays
+    /// one at a time.
+    pub fn packet_hits(
+        &self,
+        packet: &RayPacket,
+        min_value: f64,
+        max_values: [f64; PACKET_WIDTH],
+    ) -> [Option<HitRecord<'_>>; PACKET_WIDTH] {
+        match self {
+            Hittable::HittableVec(h) => h.packet_hits(packet, min_value, max_values),
+            Hittable::LinearBvh(h) => h.packet_hits(packet, min_value, max_values),
+            _ => std::array::from_fn(|lane| {
+                self.is_hit_by(&packet.rays[lane], min_value, max_values[lane])
+            }),
+        }
+    }
+
+    /// Height of the first horizontal [`Plane`] found here or among the direct members of a
+    /// [`HittableVec`], which is where unbounded ground planes live.
+    pub fn ground_height(&self) -> Option<f64> {
+        match self {
+            Hittable::Plane(h) => h.horizontal_height(),
+            Hittable::HittableVec(h) => h.hittables.iter().find_map(Hittable::ground_height),
+            _ => None,
+        }
+    }
+
+    /// The first [`TopLevelBvh`] found here or among the direct members of a
+    /// [`HittableVec`], for moving its instances between frames.
+    pub fn top_level_bvh_mut(&mut self) -> Option<&mut TopLevelBvh> {
+        match self {
+            Hittable::TopLevelBvh(h) => Some(h),
+            Hittable::HittableVec(h) => {
+                h.hittables.iter_mut().find_map(Hittable::top_level_bvh_mut)
+            }
+            _ => None,
+        }
+    }
+
+    /// Every span of the (infinite) ray that lies inside this hittable, in ascending order.
+    /// Only meaningful for closed surfaces whose normals face outwards.
+    pub fn hit_intervals(&self, ray: &Ray) -> Vec<HitInterval<'_>> {
+        match self {
+            Hittable::Sphere(h) => h.hit_intervals(ray),
+            Hittable::Torus(h) => h.hit_intervals(ray),
+            Hittable::Translation(h) => h.hit_intervals(ray),
+            Hittable::Rotation(h) => h.hit_intervals(ray),
+            Hittable::Csg(h) => h.hit_intervals(ray),
+            _ => {
+                // Walk the ray one surface at a time using the closest-hit query.
+                let mut hits = vec![];
+                let mut search_from = f64::NEG_INFINITY;
+                while hits.len() < MAX_INTERVAL_HITS {
+                    match self.is_hit_by(ray, search_from, f64::INFINITY) {
+                        Some(hr) if hr.value > search_from => {
+                            search_from = hr.value + 1e-9 * hr.value.abs().max(1.);
+                            hits.push(hr);
+                        }
+                        _ => break,
+                    }
+                }
+                pair_intervals(hits)
+            }
         }
     }
 }
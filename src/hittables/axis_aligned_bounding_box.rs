@@ -1,5 +1,6 @@
 use crate::data::point64::Point64;
 use crate::data::ray::Ray;
+use nalgebra::Vector3;
 use std::mem;
 
 #[derive(PartialEq, Copy, Clone)]
@@ -35,6 +36,34 @@ impl AxisAlignedBoundingBox {
         Some((t0, t1))
     }
 
+    /// [`hit_interval`](Self::hit_interval) for callers testing many boxes against one ray,
+    /// which can compute `1 / ray.direction` once.
+    pub fn hit_interval_with_inverse(
+        &self,
+        origin: &Point64,
+        inv_direction: &Vector3<f64>,
+        t_min: f64,
+        t_max: f64,
+    ) -> Option<(f64, f64)> {
+        let mut t0 = t_min;
+        let mut t1 = t_max;
+        for idx in 0..3 {
+            let mut ta = (self.minimum.0[idx] - origin.0[idx]) * inv_direction[idx];
+            let mut tb = (self.maximum.0[idx] - origin.0[idx]) * inv_direction[idx];
+
+            if inv_direction[idx] < 0. {
+                mem::swap(&mut ta, &mut tb);
+            }
+
+            t0 = t0.max(ta);
+            t1 = t1.min(tb);
+            if t0 >= t1 {
+                return None;
+            }
+        }
+        Some((t0, t1))
+    }
+
     pub fn surrounding_box_with(self, other: &AxisAlignedBoundingBox) -> AxisAlignedBoundingBox {
         if self == *other {
             return self;
@@ -54,4 +83,13 @@ impl AxisAlignedBoundingBox {
 
         AxisAlignedBoundingBox { minimum, maximum }
     }
+
+    pub fn surface_area(&self) -> f64 {
+        let extent = self.maximum.0 - self.minimum.0;
+        2. * (extent.x * extent.y + extent.y * extent.z + extent.z * extent.x)
+    }
+
+    pub fn centroid(&self) -> Point64 {
+        Point64((self.minimum.0 + self.maximum.0) * 0.5)
+    }
 }
@@ -1,16 +1,236 @@
+use crate::data::point64::Point64;
 use crate::data::ray::Ray;
 use crate::hittables::axis_aligned_bounding_box::AxisAlignedBoundingBox;
-use crate::hittables::bvh_comparators::BOX_COMPARATORS;
+use crate::hittables::hittable_vec::HittableVec;
+use crate::hittables::linear_bvh::{LinearBvh, MAX_DEPTH};
 use crate::hittables::{HitRecord, Hittable};
-use rand::prelude::IndexedRandom;
+use std::fmt;
+use std::str::FromStr;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How [`BoundedVolumeHierarchy::build`] divides primitives between the two children of a node.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BvhStrategy {
+    /// Sort along a pseudo-random axis drawn from [`BvhBuildOptions::seed`] and split at the
+    /// median, down to one primitive per leaf.
+    RandomMedian,
+    /// Pick the axis and position with the lowest binned surface area heuristic cost.
+    Sah,
+}
+
+impl FromStr for BvhStrategy {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "median" => Ok(BvhStrategy::RandomMedian),
+            "sah" => Ok(BvhStrategy::Sah),
+            _ => anyhow::bail!("unknown BVH strategy: {s}"),
+        }
+    }
+}
+
+impl fmt::Display for BvhStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BvhStrategy::RandomMedian => write!(f, "random median"),
+            BvhStrategy::Sah => write!(f, "SAH"),
+        }
+    }
+}
+
+/// How [`BoundedVolumeHierarchy::build`] stores the finished hierarchy.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BvhLayout {
+    /// Boxed [`BoundedVolumeHierarchy`] nodes, traversed recursively.
+    Tree,
+    /// A flat [`LinearBvh`].
+    Linear,
+}
+
+impl FromStr for BvhLayout {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "tree" => Ok(BvhLayout::Tree),
+            "linear" => Ok(BvhLayout::Linear),
+            _ => anyhow::bail!("unknown BVH layout: {s}"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct BvhBuildOptions {
+    pub strategy: BvhStrategy,
+    pub layout: BvhLayout,
+    /// SAH only: ranges of at most this many primitives become a single leaf when that is
+    /// cheaper than splitting them. Larger ranges are always split.
+    pub max_leaf_size: usize,
+    /// SAH only: number of centroid bins tried per axis.
+    pub bins: usize,
+    /// Cost of visiting an interior node, relative to intersecting one primitive.
+    pub traversal_cost: f64,
+    /// Threads to build the upper levels with. The tree does not depend on this.
+    pub threads: usize,
+    /// Median only: seeds the choice of split axes, so equal seeds give equal trees.
+    pub seed: u64,
+}
+
+impl Default for BvhBuildOptions {
+    fn default() -> Self {
+        Self {
+            strategy: BvhStrategy::Sah,
+            layout: BvhLayout::Linear,
+            max_leaf_size: 4,
+            bins: 16,
+            traversal_cost: 1.,
+            threads: num_cpus::get(),
+            seed: 0,
+        }
+    }
+}
+
+/// Shape and cost of a built hierarchy. Nested hierarchies passed in as primitives count as
+/// single primitives.
+#[derive(Clone, Copy, Debug)]
+pub struct BvhStats {
+    pub strategy: BvhStrategy,
+    pub primitives: usize,
+    pub interior_nodes: usize,
+    pub leaves: usize,
+    pub max_leaf_size: usize,
+    pub max_depth: usize,
+    /// Expected cost of tracing a ray that hits the root box, in primitive intersections,
+    /// assuming hits on a node are proportional to its surface area.
+    pub sah_cost: f64,
+    pub build_time: Duration,
+    pub threads: usize,
+}
+
+impl BvhStats {
+    fn new(strategy: BvhStrategy) -> Self {
+        Self {
+            strategy,
+            primitives: 0,
+            interior_nodes: 0,
+            leaves: 0,
+            max_leaf_size: 0,
+            max_depth: 0,
+            sah_cost: 0.,
+            build_time: Duration::ZERO,
+            threads: 1,
+        }
+    }
+
+    /// Adds the nodes recorded while building a subtree on another thread.
+    fn merge(&mut self, other: &BvhStats) {
+        self.interior_nodes += other.interior_nodes;
+        self.leaves += other.leaves;
+        self.max_leaf_size = self.max_leaf_size.max(other.max_leaf_size);
+        self.max_depth = self.max_depth.max(other.max_depth);
+        self.sah_cost += other.sah_cost;
+    }
+
+    fn record_interior(&mut self, bounds: &AxisAlignedBoundingBox, traversal_cost: f64) {
+        self.interior_nodes += 1;
+        self.sah_cost += traversal_cost * bounds.surface_area();
+    }
+
+    fn record_leaf(&mut self, bounds: &AxisAlignedBoundingBox, primitives: usize, depth: usize) {
+        self.leaves += 1;
+        self.max_leaf_size = self.max_leaf_size.max(primitives);
+        self.max_depth = self.max_depth.max(depth);
+        self.sah_cost += primitives as f64 * bounds.surface_area();
+    }
+}
+
+impl fmt::Display for BvhStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} BVH over {} primitives: {} interior nodes, {} leaves (up to {} primitives), depth {}, SAH cost {:.2}, built in {:.1?} on {} thread{}",
+            self.strategy,
+            self.primitives,
+            self.interior_nodes,
+            self.leaves,
+            self.max_leaf_size,
+            self.max_depth,
+            self.sah_cost,
+            self.build_time,
+            self.threads,
+            if self.threads == 1 { "" } else { "s" },
+        )
+    }
+}
+
+/// Ranges with fewer primitives than this are always built on the current thread.
+const MIN_PARALLEL_PRIMITIVES: usize = 1024;
+
+/// A primitive waiting to be placed in the hierarchy: its index among the objects being
+/// built, with its bounds cached. Builders partition these in place rather than the objects.
+#[derive(Clone, Copy)]
+struct Primitive {
+    index: usize,
+    bounds: AxisAlignedBoundingBox,
+    centroid: Point64,
+}
+
+/// What stays the same while building every node of one hierarchy.
+struct BuildContext<'a> {
+    objects: &'a [Hittable],
+    time0: f64,
+    time1: f64,
+    options: &'a BvhBuildOptions,
+    /// Nodes shallower than this build their two children on separate threads.
+    parallel_depth: usize,
+}
+
+/// Mixes the bits of `x` (SplitMix64's finalizer), for deriving per-node randomness.
+fn mix(mut x: u64) -> u64 {
+    x = x.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    x ^ (x >> 31)
+}
+
+#[derive(Clone, Copy)]
+struct SahSplit {
+    axis: usize,
+    /// Primitives whose centroid falls in this bin or an earlier one go to the left child.
+    last_left_bin: usize,
+    cost: f64,
+}
+
+#[derive(Clone, Copy)]
+struct Bin {
+    count: usize,
+    bounds: Option<AxisAlignedBoundingBox>,
+}
+
+fn bin_index(
+    centroid: &Point64,
+    centroid_bounds: &AxisAlignedBoundingBox,
+    axis: usize,
+    bins: usize,
+) -> usize {
+    let extent = centroid_bounds.maximum.0[axis] - centroid_bounds.minimum.0[axis];
+    let offset = (centroid.0[axis] - centroid_bounds.minimum.0[axis]) / extent;
+    ((offset * bins as f64) as usize).min(bins - 1)
+}
+
+fn union(a: Option<AxisAlignedBoundingBox>, b: &AxisAlignedBoundingBox) -> AxisAlignedBoundingBox {
+    a.map_or(*b, |a| a.surrounding_box_with(b))
+}
 
 #[derive(Clone)]
 pub struct BoundedVolumeHierarchy {
-    left_child: Box<Hittable>,
-    right_child: Box<Hittable>,
-    left_bounds: AxisAlignedBoundingBox,
-    right_bounds: AxisAlignedBoundingBox,
-    bounding_box: AxisAlignedBoundingBox,
+    pub(super) left_child: Box<Hittable>,
+    pub(super) right_child: Box<Hittable>,
+    pub(super) left_bounds: AxisAlignedBoundingBox,
+    pub(super) right_bounds: AxisAlignedBoundingBox,
+    pub(super) bounding_box: AxisAlignedBoundingBox,
 }
 
 impl BoundedVolumeHierarchy {
@@ -18,7 +238,7 @@ impl BoundedVolumeHierarchy {
         Some(self.bounding_box)
     }
 
-    pub fn is_hit_by(&self, ray: &Ray, min_value: f64, max_value: f64) -> Option<HitRecord> {
+    pub fn is_hit_by(&self, ray: &Ray, min_value: f64, max_value: f64) -> Option<HitRecord<'_>> {
         if !self.bounding_box.is_hit_by(ray, min_value, max_value) {
             return None;
         }
@@ -45,62 +265,471 @@ impl BoundedVolumeHierarchy {
         }
     }
 
-    pub fn create_bvh(objects: &mut [Hittable], time0: f64, time1: f64) -> Hittable {
-        let comparator = BOX_COMPARATORS.choose(&mut rand::rng()).unwrap();
+    pub fn occluded(&self, ray: &Ray, min_value: f64, max_value: f64) -> bool {
+        self.bounding_box.is_hit_by(ray, min_value, max_value)
+            && [
+                (&self.left_bounds, &self.left_child),
+                (&self.right_bounds, &self.right_child),
+            ]
+            .into_iter()
+            .any(|(bounds, child)| {
+                bounds.is_hit_by(ray, min_value, max_value)
+                    && child.occluded(ray, min_value, max_value)
+            })
+    }
+
+    /// Builds a hierarchy with the default [`BvhBuildOptions`].
+    pub fn create_bvh(objects: &[Hittable], time0: f64, time1: f64) -> Hittable {
+        Self::build(objects, time0, time1, &BvhBuildOptions::default()).0
+    }
+
+    pub fn build(
+        objects: &[Hittable],
+        time0: f64,
+        time1: f64,
+        options: &BvhBuildOptions,
+    ) -> (Hittable, BvhStats) {
+        if objects.is_empty() {
+            panic!("empty list of hittables passed to BoundedVolumeHierarchy::build");
+        }
+
+        let start = Instant::now();
+        let threads = options.threads.max(1);
+        let mut stats = BvhStats::new(options.strategy);
+        stats.threads = threads;
+
+        let mut primitives: Vec<Primitive> = objects
+            .iter()
+            .enumerate()
+            .map(|(index, hittable)| {
+                let bounds = hittable
+                    .bounding_box(time0, time1)
+                    .expect("No bounding box in bvh_node constructor for hittable");
+                Primitive {
+                    index,
+                    bounds,
+                    centroid: bounds.centroid(),
+                }
+            })
+            .collect();
+        let context = BuildContext {
+            objects,
+            time0,
+            time1,
+            options,
+            parallel_depth: threads.next_power_of_two().trailing_zeros() as usize,
+        };
+        let hittable = Self::build_range(&context, &mut primitives, 0, &mut stats);
+
+        let hittable = match hittable {
+            Hittable::Bvh(_) if options.layout == BvhLayout::Linear => {
+                Hittable::LinearBvh(LinearBvh::from_tree(hittable, time0, time1))
+            }
+            _ => hittable,
+        };
+
+        let root_area = hittable
+            .bounding_box(time0, time1)
+            .map_or(0., |bounds| bounds.surface_area());
+        if root_area > 0. {
+            stats.sah_cost /= root_area;
+        }
+        stats.primitives = objects.len();
+        stats.build_time = start.elapsed();
+
+        (hittable, stats)
+    }
+
+    /// Builds the subtree over `primitives`, reordering them so each child's are contiguous.
+    fn build_range(
+        context: &BuildContext,
+        primitives: &mut [Primitive],
+        depth: usize,
+        stats: &mut BvhStats,
+    ) -> Hittable {
+        let count = primitives.len();
+        let bounds = primitives
+            .iter()
+            .fold(None, |acc, primitive| Some(union(acc, &primitive.bounds)))
+            .unwrap();
+
+        let mid = match context.options.strategy {
+            // However lopsided the splits, the tree must stay shallow enough to flatten.
+            _ if depth + 1 >= MAX_DEPTH => None,
+            BvhStrategy::RandomMedian if count > 1 => {
+                // The range's contents depend only on the seed and the nodes above it, so
+                // this axis is the same however the build is spread across threads.
+                let axis =
+                    (mix(context.options.seed ^ mix(primitives[0].index as u64)) % 3) as usize;
+                primitives.sort_unstable_by(|a, b| {
+                    a.bounds.minimum.0[axis]
+                        .partial_cmp(&b.bounds.minimum.0[axis])
+                        .expect("got an NaN as a box dimension value")
+                        .then(a.index.cmp(&b.index))
+                });
+                Some(count / 2)
+            }
+            BvhStrategy::RandomMedian => None,
+            BvhStrategy::Sah => Self::sah_partition(primitives, &bounds, context.options),
+        };
+
+        let Some(mid) = mid else {
+            stats.record_leaf(&bounds, count, depth);
+            let objects = context.objects;
+            return if count == 1 {
+                objects[primitives[0].index].clone()
+            } else {
+                Hittable::HittableVec(HittableVec {
+                    hittables: primitives
+                        .iter()
+                        .map(|p| objects[p.index].clone())
+                        .collect(),
+                })
+            };
+        };
+
+        let (left, right) = primitives.split_at_mut(mid);
+        let (left_child, right_child) =
+            if depth < context.parallel_depth && count >= MIN_PARALLEL_PRIMITIVES {
+                let strategy = stats.strategy;
+                thread::scope(|scope| {
+                    let left_build = scope.spawn(move || {
+                        let mut left_stats = BvhStats::new(strategy);
+                        let child = Self::build_range(context, left, depth + 1, &mut left_stats);
+                        (child, left_stats)
+                    });
+                    let right_child = Self::build_range(context, right, depth + 1, stats);
+                    let (left_child, left_stats) = left_build.join().unwrap();
+                    stats.merge(&left_stats);
+                    (left_child, right_child)
+                })
+            } else {
+                (
+                    Self::build_range(context, left, depth + 1, stats),
+                    Self::build_range(context, right, depth + 1, stats),
+                )
+            };
+
+        Self::node(left_child, right_child, context, stats)
+    }
+
+    /// Moves the primitives of the cheapest SAH split to the front and returns how many
+    /// there are, or `None` if the range should stay a leaf.
+    fn sah_partition(
+        primitives: &mut [Primitive],
+        bounds: &AxisAlignedBoundingBox,
+        options: &BvhBuildOptions,
+    ) -> Option<usize> {
+        let count = primitives.len();
+        if count == 1 {
+            return None;
+        }
+
+        let centroid_bounds = primitives
+            .iter()
+            .fold(None, |acc, primitive| {
+                let point = AxisAlignedBoundingBox {
+                    minimum: primitive.centroid,
+                    maximum: primitive.centroid,
+                };
+                Some(union(acc, &point))
+            })
+            .unwrap();
+
+        let split = Self::best_sah_split(primitives, bounds, &centroid_bounds, options);
+        let leaf_cost = count as f64;
+        if count <= options.max_leaf_size && split.is_none_or(|split| leaf_cost <= split.cost) {
+            return None;
+        }
+
+        let Some(split) = split else {
+            // All centroids coincide, so no plane separates them; split the range in half.
+            return Some(count / 2);
+        };
+
+        let mut mid = 0;
+        for index in 0..count {
+            let bin = bin_index(
+                &primitives[index].centroid,
+                &centroid_bounds,
+                split.axis,
+                options.bins.max(2),
+            );
+            if bin <= split.last_left_bin {
+                primitives.swap(mid, index);
+                mid += 1;
+            }
+        }
+        Some(mid)
+    }
+
+    /// The cheapest split between centroid bins over all axes, if any axis has extent.
+    fn best_sah_split(
+        primitives: &[Primitive],
+        bounds: &AxisAlignedBoundingBox,
+        centroid_bounds: &AxisAlignedBoundingBox,
+        options: &BvhBuildOptions,
+    ) -> Option<SahSplit> {
+        let bins = options.bins.max(2);
+        let parent_area = bounds.surface_area();
+        let mut best: Option<SahSplit> = None;
 
-        let left_child: Box<Hittable>;
-        let right_child: Box<Hittable>;
+        for axis in 0..3 {
+            if centroid_bounds.maximum.0[axis] <= centroid_bounds.minimum.0[axis] {
+                continue;
+            }
 
-        match objects.len() {
-            0 => panic!("empty list of hittables passed to BoundedVolumeHierarchy::new"),
-            1 => {
-                left_child = Box::new(objects[0].clone());
-                right_child = Box::new(objects[0].clone());
+            let mut binned = vec![
+                Bin {
+                    count: 0,
+                    bounds: None,
+                };
+                bins
+            ];
+            for primitive in primitives {
+                let bin = &mut binned[bin_index(&primitive.centroid, centroid_bounds, axis, bins)];
+                bin.count += 1;
+                bin.bounds = Some(union(bin.bounds, &primitive.bounds));
             }
-            2 => {
-                let o1 = &objects[0];
-                let o2 = &objects[1];
-
-                match comparator(o1, o2) {
-                    std::cmp::Ordering::Less => {
-                        left_child = Box::new(objects[0].clone());
-                        right_child = Box::new(objects[1].clone());
-                    }
-                    _ => {
-                        left_child = Box::new(objects[1].clone());
-                        right_child = Box::new(objects[0].clone());
-                    }
+
+            // Area times count of everything right of each boundary, swept from the right.
+            let mut right_costs = vec![0.; bins];
+            let mut right = Bin {
+                count: 0,
+                bounds: None,
+            };
+            for boundary in (1..bins).rev() {
+                right.count += binned[boundary].count;
+                if let Some(bin_bounds) = binned[boundary].bounds {
+                    right.bounds = Some(union(right.bounds, &bin_bounds));
                 }
+                right_costs[boundary - 1] = right
+                    .bounds
+                    .map_or(0., |b| b.surface_area() * right.count as f64);
             }
-            _ => {
-                objects.sort_by(comparator);
-                let mid = objects.len() / 2;
-                left_child = Box::new(BoundedVolumeHierarchy::create_bvh(
-                    &mut objects[0..mid].to_vec(),
-                    time0,
-                    time1,
-                ));
-                right_child = Box::new(BoundedVolumeHierarchy::create_bvh(
-                    &mut objects[mid..].to_vec(),
-                    time0,
-                    time1,
-                ));
+
+            let mut left = Bin {
+                count: 0,
+                bounds: None,
+            };
+            for (last_left_bin, bin) in binned[..bins - 1].iter().enumerate() {
+                left.count += bin.count;
+                if let Some(bin_bounds) = bin.bounds {
+                    left.bounds = Some(union(left.bounds, &bin_bounds));
+                }
+                if left.count == 0 || left.count == primitives.len() {
+                    continue;
+                }
+
+                let left_cost = left
+                    .bounds
+                    .map_or(0., |b| b.surface_area() * left.count as f64);
+                let cost = options.traversal_cost
+                    + if parent_area > 0. {
+                        (left_cost + right_costs[last_left_bin]) / parent_area
+                    } else {
+                        primitives.len() as f64
+                    };
+                if best.is_none_or(|best| cost < best.cost) {
+                    best = Some(SahSplit {
+                        axis,
+                        last_left_bin,
+                        cost,
+                    });
+                }
             }
         }
 
+        best
+    }
+
+    fn node(
+        left_child: Hittable,
+        right_child: Hittable,
+        context: &BuildContext,
+        stats: &mut BvhStats,
+    ) -> Hittable {
         let box_left = left_child
-            .bounding_box(time0, time1)
+            .bounding_box(context.time0, context.time1)
             .expect("No bounding box in bvh_node constructor for hittable");
         let box_right = right_child
-            .bounding_box(time0, time1)
+            .bounding_box(context.time0, context.time1)
             .expect("No bounding box in bvh_node constructor for hittable");
+        let bounding_box = box_left.surrounding_box_with(&box_right);
+        stats.record_interior(&bounding_box, context.options.traversal_cost);
 
         Hittable::Bvh(BoundedVolumeHierarchy {
-            left_child,
-            right_child,
+            left_child: Box::new(left_child),
+            right_child: Box::new(right_child),
             left_bounds: box_left,
             right_bounds: box_right,
-            bounding_box: box_left.surrounding_box_with(&box_right),
+            bounding_box,
         })
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::{BoundedVolumeHierarchy, BvhBuildOptions, BvhLayout, BvhStrategy, MAX_DEPTH};
+    use crate::data::point64::Point64;
+    use crate::data::ray::Ray;
+    use crate::data::vector3::rand_range;
+    use crate::hittables::Hittable;
+    use crate::hittables::hittable_vec::HittableVec;
+    use crate::hittables::sphere::Sphere;
+    use crate::materials::Material;
+    use crate::materials::dielectric::Dielectric;
+
+    /// Clumps of small spheres far apart, which a median split handles poorly.
+    fn clustered_spheres() -> Vec<Hittable> {
+        let material = Material::Dielectric(Dielectric::new(1.5));
+        [(-50., 0., 0.), (0., 30., 5.), (60., -10., 20.)]
+            .iter()
+            .flat_map(|&(x, y, z)| {
+                let material = material.clone();
+                (0..100).map(move |_| {
+                    Hittable::Sphere(Sphere {
+                        center: Point64::new(x, y, z) + Point64(rand_range(-5., 5.)),
+                        radius: 0.5,
+                        material: material.clone(),
+                    })
+                })
+            })
+            .collect()
+    }
+
+    #[test]
+    fn lopsided_splits_stop_at_the_depth_limit() {
+        // Exponentially spaced spheres: every SAH split peels off only the farthest one.
+        let material = Material::Dielectric(Dielectric::new(1.5));
+        let spheres: Vec<Hittable> = (0..300)
+            .map(|i| {
+                Hittable::Sphere(Sphere {
+                    center: Point64::new(2f64.powi(i), 0., 0.),
+                    radius: 0.1,
+                    material: material.clone(),
+                })
+            })
+            .collect();
+
+        let (bvh, stats) =
+            BoundedVolumeHierarchy::build(&spheres, 0., 1., &BvhBuildOptions::default());
+        assert_eq!(stats.max_depth, MAX_DEPTH - 1);
+
+        let ray = Ray {
+            origin: Point64::new(1., 0., -5.),
+            direction: Point64::new(0., 0., 1.),
+            exposure_time: 0.,
+        };
+        let hr = bvh.is_hit_by(&ray, 0.001, f64::INFINITY).unwrap();
+        assert!((hr.value - 4.9).abs() < 1e-9);
+    }
+
+    #[test]
+    fn strategies_agree_with_brute_force() {
+        let spheres = clustered_spheres();
+        let flat = Hittable::HittableVec(HittableVec {
+            hittables: spheres.clone(),
+        });
+
+        let (median, median_stats) = BoundedVolumeHierarchy::build(
+            &spheres,
+            0.,
+            1.,
+            &BvhBuildOptions {
+                strategy: BvhStrategy::RandomMedian,
+                ..BvhBuildOptions::default()
+            },
+        );
+        let options = BvhBuildOptions::default();
+        let (sah, sah_stats) = BoundedVolumeHierarchy::build(&spheres, 0., 1., &options);
+        let (sah_tree, _) = BoundedVolumeHierarchy::build(
+            &spheres,
+            0.,
+            1.,
+            &BvhBuildOptions {
+                layout: BvhLayout::Tree,
+                ..options
+            },
+        );
+        assert!(matches!(sah, Hittable::LinearBvh(_)));
+        assert!(matches!(sah_tree, Hittable::Bvh(_)));
+
+        for target in &spheres[..60] {
+            let Hittable::Sphere(target) = target else {
+                unreachable!()
+            };
+            let ray = Ray {
+                origin: Point64(rand_range(-100., 100.)),
+                direction: target.center - Point64(rand_range(-100., 100.)),
+                exposure_time: 0.,
+            };
+            let expected = flat
+                .is_hit_by(&ray, 0.001, f64::INFINITY)
+                .map(|hr| hr.value);
+            for tree in [&median, &sah, &sah_tree] {
+                let actual = tree
+                    .is_hit_by(&ray, 0.001, f64::INFINITY)
+                    .map(|hr| hr.value);
+                assert_eq!(actual, expected);
+            }
+        }
+
+        assert_eq!(median_stats.primitives, 300);
+        assert_eq!(median_stats.max_leaf_size, 1);
+        assert_eq!(sah_stats.primitives, 300);
+        assert!(sah_stats.max_leaf_size <= options.max_leaf_size);
+        assert!(sah_stats.leaves < median_stats.leaves);
+        assert!(sah_stats.sah_cost < median_stats.sah_cost);
+    }
+
+    #[test]
+    fn coincident_primitives_are_split_by_count() {
+        let spheres: Vec<Hittable> = (0..9)
+            .map(|_| {
+                Hittable::Sphere(Sphere {
+                    center: Point64::new(1., 2., 3.),
+                    radius: 1.,
+                    material: Material::Dielectric(Dielectric::new(1.5)),
+                })
+            })
+            .collect();
+
+        let (_, stats) =
+            BoundedVolumeHierarchy::build(&spheres, 0., 1., &BvhBuildOptions::default());
+        assert_eq!(stats.leaves, 3);
+        assert_eq!(stats.max_leaf_size, 4);
+    }
+
+    #[test]
+    fn threaded_builds_match_single_threaded_ones() {
+        let spheres: Vec<Hittable> = (0..4).flat_map(|_| clustered_spheres()).collect();
+        let layout = |bvh: &Hittable| {
+            let Hittable::LinearBvh(bvh) = bvh else {
+                panic!("expected a linear BVH")
+            };
+            bvh.nodes()
+                .iter()
+                .map(|node| (node.bounds.minimum.0, node.offset, node.primitive_count))
+                .collect::<Vec<_>>()
+        };
+
+        for strategy in [BvhStrategy::RandomMedian, BvhStrategy::Sah] {
+            let build = |threads, seed| {
+                let options = BvhBuildOptions {
+                    strategy,
+                    threads,
+                    seed,
+                    ..BvhBuildOptions::default()
+                };
+                layout(&BoundedVolumeHierarchy::build(&spheres, 0., 1., &options).0)
+            };
+            let single = build(1, 7);
+            assert_eq!(build(4, 7), single);
+            assert_eq!(build(3, 7), single);
+            if strategy == BvhStrategy::RandomMedian {
+                assert_ne!(build(4, 8), single);
+            }
+        }
+    }
+}
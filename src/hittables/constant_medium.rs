@@ -25,42 +25,50 @@ impl ConstantMedium {
         self.boundary.bounding_box(time0, time1)
     }
 
-    pub fn is_hit_by(&self, ray: &Ray, min_value: f64, max_value: f64) -> Option<HitRecord> {
-        let rec1 = self
-            .boundary
-            .is_hit_by(ray, f64::NEG_INFINITY, f64::INFINITY)?;
-        let rec2 = self
-            .boundary
-            .is_hit_by(ray, rec1.value + 0.0001, f64::INFINITY)?;
+    /// Samples a scattering event inside the boundary. Non-convex boundaries (e.g. a torus)
+    /// are handled by walking the ray through each entry/exit pair in turn.
+    pub fn is_hit_by(&self, ray: &Ray, min_value: f64, max_value: f64) -> Option<HitRecord<'_>> {
+        let ray_length = ray.direction.0.magnitude();
+        let mut search_from = f64::NEG_INFINITY;
 
-        let t_min = rec1.value.max(min_value);
-        let t_max = rec2.value.min(max_value);
+        loop {
+            let rec1 = self.boundary.is_hit_by(ray, search_from, f64::INFINITY)?;
+            let rec2 = self
+                .boundary
+                .is_hit_by(ray, rec1.value + 0.0001, f64::INFINITY)?;
+            search_from = rec2.value + 0.0001;
 
-        if t_min >= t_max {
-            return None;
-        }
+            if rec1.value >= max_value {
+                return None;
+            }
 
-        let t_min = t_min.max(0.0);
+            let t_min = rec1.value.max(min_value).max(0.0);
+            let t_max = rec2.value.min(max_value);
 
-        let ray_length = ray.direction.0.magnitude();
-        let distance_inside_boundary = (t_max - t_min) * ray_length;
-        let hit_distance = self.neg_inv_density * rand::rng().random::<f64>().ln();
+            if t_min >= t_max {
+                continue;
+            }
 
-        if hit_distance > distance_inside_boundary {
-            return None;
-        }
+            let distance_inside_boundary = (t_max - t_min) * ray_length;
+            let hit_distance = self.neg_inv_density * rand::rng().random::<f64>().ln();
+
+            if hit_distance > distance_inside_boundary {
+                continue;
+            }
 
-        let t = t_min + hit_distance / ray_length;
-        let location = ray.point_at_parameter(t);
+            let t = t_min + hit_distance / ray_length;
+            let location = ray.point_at_parameter(t);
 
-        Some(HitRecord {
-            value: t,
-            u: 0.0,
-            v: 0.0,
-            location,
-            normal: Point64::new(1.0, 0.0, 0.0), // arbitrary
-            front_face: true,                    // arbitrary
-            material: self.phase_function.clone(),
-        })
+            return Some(HitRecord {
+                value: t,
+                u: 0.0,
+                v: 0.0,
+                location,
+                normal: Point64::new(1.0, 0.0, 0.0), // arbitrary
+                front_face: true,                    // arbitrary
+                outside_ior: 1.,
+                material: &self.phase_function,
+            });
+        }
     }
 }
@@ -0,0 +1,267 @@
+use crate::data::point64::Point64;
+use crate::data::ray::Ray;
+use crate::hittables::axis_aligned_bounding_box::AxisAlignedBoundingBox;
+use crate::hittables::{HitInterval, HitRecord, Hittable};
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum CsgOperation {
+    /// Points inside either operand.
+    Union,
+    /// Points inside both operands.
+    Intersection,
+    /// Points inside the left operand but not the right.
+    Difference,
+}
+
+impl CsgOperation {
+    fn contains(self, inside_left: bool, inside_right: bool) -> bool {
+        match self {
+            CsgOperation::Union => inside_left || inside_right,
+            CsgOperation::Intersection => inside_left && inside_right,
+            CsgOperation::Difference => inside_left && !inside_right,
+        }
+    }
+}
+
+/// Constructive solid geometry node combining two closed hittables.
+///
+/// Both operands must be closed surfaces with outward-facing normals, so that every hit
+/// can be classified as an entry or an exit (see [`Hittable::hit_intervals`]).
+#[derive(Clone)]
+pub struct Csg {
+    left: Box<Hittable>,
+    right: Box<Hittable>,
+    operation: CsgOperation,
+    bounding_box: Option<AxisAlignedBoundingBox>,
+}
+
+impl Csg {
+    pub fn new(
+        operation: CsgOperation,
+        left: Hittable,
+        right: Hittable,
+        time0: f64,
+        time1: f64,
+    ) -> Self {
+        let left_box = left.bounding_box(time0, time1);
+        let right_box = right.bounding_box(time0, time1);
+
+        let bounding_box = match operation {
+            CsgOperation::Union => match (left_box, right_box) {
+                (Some(l), Some(r)) => Some(l.surrounding_box_with(&r)),
+                _ => None,
+            },
+            CsgOperation::Intersection => match (left_box, right_box) {
+                (Some(l), Some(r)) => Some(overlap(&l, &r).unwrap_or(l)),
+                (l, r) => l.or(r),
+            },
+            CsgOperation::Difference => left_box,
+        };
+
+        Self {
+            left: Box::new(left),
+            right: Box::new(right),
+            operation,
+            bounding_box,
+        }
+    }
+
+    pub fn bounding_box(&self, _time0: f64, _time1: f64) -> Option<AxisAlignedBoundingBox> {
+        self.bounding_box
+    }
+
+    pub fn is_hit_by(&self, ray: &Ray, min_value: f64, max_value: f64) -> Option<HitRecord<'_>> {
+        if let Some(bb) = self.bounding_box
+            && !bb.is_hit_by(ray, min_value, max_value)
+        {
+            return None;
+        }
+
+        self.hit_intervals(ray)
+            .into_iter()
+            .flat_map(|interval| [interval.enter, interval.exit])
+            .find(|hr| min_value < hr.value && hr.value < max_value)
+    }
+
+    /// Merges the operands' intervals with a sweep along the ray, tracking whether the ray is
+    /// currently inside each operand and emitting a boundary whenever the combined state flips.
+    pub fn hit_intervals(&self, ray: &Ray) -> Vec<HitInterval<'_>> {
+        let mut events: Vec<(bool, HitRecord)> = vec![];
+        for interval in self.left.hit_intervals(ray) {
+            events.push((true, interval.enter));
+            events.push((true, interval.exit));
+        }
+        for interval in self.right.hit_intervals(ray) {
+            events.push((false, interval.enter));
+            events.push((false, interval.exit));
+        }
+        events.sort_by(|a, b| a.1.value.total_cmp(&b.1.value));
+
+        let mut inside_left = false;
+        let mut inside_right = false;
+        let mut inside = false;
+        let mut enter: Option<HitRecord> = None;
+        let mut intervals = vec![];
+
+        for (from_left, mut hr) in events {
+            if from_left {
+                inside_left = hr.front_face;
+            } else {
+                inside_right = hr.front_face;
+            }
+
+            let now_inside = self.operation.contains(inside_left, inside_right);
+            if now_inside == inside {
+                continue;
+            }
+            inside = now_inside;
+
+            // Surfaces of a subtracted solid face the other way in the result. The stored
+            // normal already opposes the ray, so only the side needs flipping.
+            if !from_left && self.operation == CsgOperation::Difference {
+                hr.front_face = !hr.front_face;
+            }
+
+            if inside {
+                enter = Some(hr);
+            } else if let Some(enter) = enter.take() {
+                intervals.push(HitInterval { enter, exit: hr });
+            }
+        }
+
+        intervals
+    }
+}
+
+fn overlap(
+    a: &AxisAlignedBoundingBox,
+    b: &AxisAlignedBoundingBox,
+) -> Option<AxisAlignedBoundingBox> {
+    let minimum = Point64(a.minimum.0.sup(&b.minimum.0));
+    let maximum = Point64(a.maximum.0.inf(&b.maximum.0));
+
+    if (0..3).any(|i| minimum.0[i] > maximum.0[i]) {
+        None
+    } else {
+        Some(AxisAlignedBoundingBox { minimum, maximum })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Csg, CsgOperation};
+    use crate::data::color64::Color64;
+    use crate::data::point64::Point64;
+    use crate::data::ray::Ray;
+    use crate::hittables::Hittable;
+    use crate::hittables::cuboid::Cuboid;
+    use crate::hittables::sphere::Sphere;
+    use crate::materials::Material;
+    use crate::materials::lambertian::Lambertian;
+    use crate::textures::Texture;
+    use approx::assert_abs_diff_eq;
+
+    fn material(gray: f64) -> Material {
+        Material::Lambertian(Lambertian {
+            albedo: Texture::solid(Color64::gray(gray)),
+        })
+    }
+
+    fn albedo(material: &Material) -> f64 {
+        match material {
+            Material::Lambertian(l) => l.albedo.value(0., 0., &Point64::default()).r(),
+            _ => panic!("unexpected material"),
+        }
+    }
+
+    fn ray_along_x() -> Ray {
+        Ray {
+            origin: Point64::new(-10., 0., 0.),
+            direction: Point64::new(1., 0., 0.),
+            exposure_time: 0.,
+        }
+    }
+
+    /// A 2×2×2 cube at the origin with a sphere of radius 0.5 carved out of its center.
+    fn cube_minus_sphere() -> Csg {
+        Csg::new(
+            CsgOperation::Difference,
+            Hittable::Cuboid(Cuboid::new(
+                Point64::new(-1., -1., -1.),
+                Point64::new(1., 1., 1.),
+                material(0.2),
+            )),
+            Hittable::Sphere(Sphere {
+                center: Point64::new(0., 0., 0.),
+                radius: 0.5,
+                material: material(0.8),
+            }),
+            0.,
+            1.,
+        )
+    }
+
+    #[test]
+    fn difference_carves_cavity() {
+        let csg = cube_minus_sphere();
+        let ray = ray_along_x();
+
+        let intervals = csg.hit_intervals(&ray);
+        assert_eq!(intervals.len(), 2);
+        assert_abs_diff_eq!(intervals[0].enter.value, 9., epsilon = 1e-9);
+        assert_abs_diff_eq!(intervals[0].exit.value, 9.5, epsilon = 1e-9);
+        assert_abs_diff_eq!(intervals[1].enter.value, 10.5, epsilon = 1e-9);
+        assert_abs_diff_eq!(intervals[1].exit.value, 11., epsilon = 1e-9);
+
+        // Starting inside the cavity, the first surface is the sphere wall, seen from within
+        // the sphere but from outside the solid: it must be a front face with the sphere's
+        // material and a normal pointing back at the ray.
+        let hr = csg.is_hit_by(&ray, 10., f64::INFINITY).unwrap();
+        assert_abs_diff_eq!(hr.value, 10.5, epsilon = 1e-9);
+        assert!(hr.front_face);
+        assert_abs_diff_eq!(hr.normal.x(), -1., epsilon = 1e-9);
+        assert_abs_diff_eq!(albedo(hr.material), 0.8);
+
+        // Leaving the solid into the cavity uses the carved surface as an exit.
+        let exit = &intervals[0].exit;
+        assert!(!exit.front_face);
+        assert_abs_diff_eq!(exit.normal.x(), -1., epsilon = 1e-9);
+
+        let hr = csg.is_hit_by(&ray, 0.001, f64::INFINITY).unwrap();
+        assert_abs_diff_eq!(hr.value, 9., epsilon = 1e-9);
+        assert_abs_diff_eq!(albedo(hr.material), 0.2);
+    }
+
+    #[test]
+    fn intersection_and_union() {
+        let sphere = |x: f64| {
+            Hittable::Sphere(Sphere {
+                center: Point64::new(x, 0., 0.),
+                radius: 1.,
+                material: material(0.5),
+            })
+        };
+        let ray = ray_along_x();
+
+        let lens = Csg::new(
+            CsgOperation::Intersection,
+            sphere(-0.5),
+            sphere(0.5),
+            0.,
+            1.,
+        );
+        let intervals = lens.hit_intervals(&ray);
+        assert_eq!(intervals.len(), 1);
+        assert_abs_diff_eq!(intervals[0].enter.value, 9.5, epsilon = 1e-9);
+        assert_abs_diff_eq!(intervals[0].exit.value, 10.5, epsilon = 1e-9);
+
+        let blob = Csg::new(CsgOperation::Union, sphere(-0.5), sphere(0.5), 0., 1.);
+        let intervals = blob.hit_intervals(&ray);
+        assert_eq!(intervals.len(), 1);
+        assert_abs_diff_eq!(intervals[0].enter.value, 8.5, epsilon = 1e-9);
+        assert_abs_diff_eq!(intervals[0].exit.value, 11.5, epsilon = 1e-9);
+
+        let disjoint = Csg::new(CsgOperation::Intersection, sphere(-3.), sphere(3.), 0., 1.);
+        assert!(disjoint.is_hit_by(&ray, 0.001, f64::INFINITY).is_none());
+    }
+}
@@ -31,13 +31,13 @@ impl Cuboid {
                         dz,
                         material.clone(),
                     )),
-                    // X- face
-                    Hittable::Quad(Quad::new(p0, dy, dz, material.clone())),
-                    // Y+ face
+                    // X- face, edges swapped so that dz × dy faces out along -x
+                    Hittable::Quad(Quad::new(p0, dz, dy, material.clone())),
+                    // Y+ face, edges swapped so that dz × dx faces out along +y
                     Hittable::Quad(Quad::new(
                         Point64::new(p0.x(), p1.y(), p0.z()),
-                        dx,
                         dz,
+                        dx,
                         material.clone(),
                     )),
                     // Y- face
@@ -49,8 +49,8 @@ impl Cuboid {
                         dy,
                         material.clone(),
                     )),
-                    // Z- face
-                    Hittable::Quad(Quad::new(p0, dx, dy, material)),
+                    // Z- face, edges swapped so that dy × dx faces out along -z
+                    Hittable::Quad(Quad::new(p0, dy, dx, material)),
                 ],
             },
         }
@@ -63,7 +63,11 @@ impl Cuboid {
         })
     }
 
-    pub fn is_hit_by(&self, ray: &Ray, min_value: f64, max_value: f64) -> Option<HitRecord> {
+    pub fn is_hit_by(&self, ray: &Ray, min_value: f64, max_value: f64) -> Option<HitRecord<'_>> {
         self.sides.is_hit_by(ray, min_value, max_value)
     }
+
+    pub fn occluded(&self, ray: &Ray, min_value: f64, max_value: f64) -> bool {
+        self.sides.occluded(ray, min_value, max_value)
+    }
 }
@@ -0,0 +1,372 @@
+use crate::data::point64::Point64;
+use crate::data::ray::Ray;
+use crate::hittables::HitRecord;
+use crate::hittables::axis_aligned_bounding_box::AxisAlignedBoundingBox;
+use crate::materials::Material;
+use nalgebra::Vector3;
+
+/// How the swept width of a [`Curve`] is shaded.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum CurveShape {
+    /// A flat strip that always faces the incoming ray, like a blade of grass seen edge-on
+    /// never disappearing.
+    Ribbon,
+    /// A flat strip whose normal is bent across its width to shade like a round tube.
+    /// Cheaper than true cylinders and indistinguishable at hair widths.
+    Tube,
+}
+
+/// A cubic Bézier curve swept with a width varying linearly from `widths.0` at the root to
+/// `widths.1` at the tip.
+///
+/// Hits report `u` as the curve parameter and `v` across the width (0 on the left edge of
+/// the curve as seen along the ray, 1 on the right).
+#[derive(Clone)]
+pub struct Curve {
+    control_points: [Vector3<f64>; 4],
+    widths: (f64, f64),
+    shape: CurveShape,
+    max_depth: u32,
+    pub material: Material,
+}
+
+/// The two halves of a cubic Bézier split at its midpoint (de Casteljau).
+fn subdivide(cp: &[Vector3<f64>; 4]) -> ([Vector3<f64>; 4], [Vector3<f64>; 4]) {
+    let p01 = (cp[0] + cp[1]) * 0.5;
+    let p12 = (cp[1] + cp[2]) * 0.5;
+    let p23 = (cp[2] + cp[3]) * 0.5;
+    let p012 = (p01 + p12) * 0.5;
+    let p123 = (p12 + p23) * 0.5;
+    let mid = (p012 + p123) * 0.5;
+
+    ([cp[0], p01, p012, mid], [mid, p123, p23, cp[3]])
+}
+
+/// Point and tangent of a cubic Bézier at `t`.
+fn evaluate(cp: &[Vector3<f64>; 4], t: f64) -> (Vector3<f64>, Vector3<f64>) {
+    let a = cp[0].lerp(&cp[1], t);
+    let b = cp[1].lerp(&cp[2], t);
+    let c = cp[2].lerp(&cp[3], t);
+    let d = a.lerp(&b, t);
+    let e = b.lerp(&c, t);
+
+    (d.lerp(&e, t), (e - d) * 3.)
+}
+
+impl Curve {
+    pub fn new(
+        control_points: [Point64; 4],
+        widths: (f64, f64),
+        shape: CurveShape,
+        material: Material,
+    ) -> Self {
+        let control_points = control_points.map(|p| p.0);
+
+        // Subdivide until the segments are flat to within a small fraction of the width,
+        // using the bound on a cubic's deviation from its chord after n halvings.
+        let deviation = (0..2)
+            .map(|i| {
+                (control_points[i] - 2. * control_points[i + 1] + control_points[i + 2]).magnitude()
+            })
+            .fold(0., f64::max);
+        let tolerance = 0.05 * widths.0.max(widths.1).max(1e-9);
+        let max_depth = if deviation > 0. {
+            ((6. * deviation / (8. * tolerance)).log2() / 2.)
+                .ceil()
+                .clamp(0., 10.) as u32
+        } else {
+            0
+        };
+
+        Self {
+            control_points,
+            widths,
+            shape,
+            max_depth,
+            material,
+        }
+    }
+
+    pub fn bounding_box(&self, _time0: f64, _time1: f64) -> Option<AxisAlignedBoundingBox> {
+        // The curve lies in the convex hull of its control points.
+        let minimum = self.control_points[1..]
+            .iter()
+            .fold(self.control_points[0], |acc, p| acc.inf(p));
+        let maximum = self.control_points[1..]
+            .iter()
+            .fold(self.control_points[0], |acc, p| acc.sup(p));
+        let pad = Vector3::repeat(0.5 * self.widths.0.max(self.widths.1));
+
+        Some(AxisAlignedBoundingBox {
+            minimum: Point64(minimum - pad),
+            maximum: Point64(maximum + pad),
+        })
+    }
+
+    fn width_at(&self, u: f64) -> f64 {
+        self.widths.0 + (self.widths.1 - self.widths.0) * u
+    }
+
+    /// Intersects in "ray space", where the ray starts at the origin and runs along +Z with unit
+    /// speed, so testing a segment reduces to a 2D distance from the origin.
+    pub fn is_hit_by(&self, ray: &Ray, min_value: f64, max_value: f64) -> Option<HitRecord<'_>> {
+        let ray_length = ray.direction.0.magnitude();
+        if ray_length == 0. {
+            return None;
+        }
+        let forward = ray.direction.0 / ray_length;
+        let helper = if forward.x.abs() > 0.9 {
+            Vector3::y()
+        } else {
+            Vector3::x()
+        };
+        let right = forward.cross(&helper).normalize();
+        let up = forward.cross(&right);
+
+        let to_ray_space = |p: &Vector3<f64>| {
+            let d = p - ray.origin.0;
+            Vector3::new(d.dot(&right), d.dot(&up), d.dot(&forward))
+        };
+        let control_points = self.control_points.map(|p| to_ray_space(&p));
+
+        let mut hit = None;
+        let mut z_max = max_value * ray_length;
+        self.recursive_hit(
+            &control_points,
+            (0., 1.),
+            self.max_depth,
+            min_value * ray_length,
+            &mut z_max,
+            &mut hit,
+        );
+
+        let (z, u, v, ray_space_normal) = hit?;
+        let outward_normal = Point64(
+            (right * ray_space_normal.x + up * ray_space_normal.y + forward * ray_space_normal.z)
+                .normalize(),
+        );
+
+        Some(HitRecord::new(
+            z / ray_length,
+            ray,
+            outward_normal,
+            &self.material,
+            (u, v),
+        ))
+    }
+
+    /// Keeps the nearest hit below `z_max` in `hit` as (z, u, v, ray-space normal).
+    fn recursive_hit(
+        &self,
+        cp: &[Vector3<f64>; 4],
+        (u0, u1): (f64, f64),
+        depth: u32,
+        z_min: f64,
+        z_max: &mut f64,
+        hit: &mut Option<(f64, f64, f64, Vector3<f64>)>,
+    ) {
+        let half_width = 0.5 * self.width_at(u0).max(self.width_at(u1));
+        let minimum = cp[1..].iter().fold(cp[0], |acc, p| acc.inf(p));
+        let maximum = cp[1..].iter().fold(cp[0], |acc, p| acc.sup(p));
+        if minimum.x - half_width > 0.
+            || maximum.x + half_width < 0.
+            || minimum.y - half_width > 0.
+            || maximum.y + half_width < 0.
+            || minimum.z - half_width > *z_max
+            || maximum.z + half_width < z_min
+        {
+            return;
+        }
+
+        if depth > 0 {
+            let (near, far) = subdivide(cp);
+            let u_mid = 0.5 * (u0 + u1);
+            self.recursive_hit(&near, (u0, u_mid), depth - 1, z_min, z_max, hit);
+            self.recursive_hit(&far, (u_mid, u1), depth - 1, z_min, z_max, hit);
+            return;
+        }
+
+        // Reject hits beyond the segment's end caps, which are perpendicular to the curve's
+        // end tangents so neighbouring segments tile without gaps or overlaps.
+        let start_tangent = cp[1] - cp[0];
+        if start_tangent.x * -cp[0].x + start_tangent.y * -cp[0].y < 0. {
+            return;
+        }
+        let end_tangent = cp[2] - cp[3];
+        if end_tangent.x * -cp[3].x + end_tangent.y * -cp[3].y < 0. {
+            return;
+        }
+
+        // Closest point to the origin on the segment's chord, in the XY plane.
+        let chord = cp[3] - cp[0];
+        let chord_length2 = chord.x * chord.x + chord.y * chord.y;
+        if chord_length2 == 0. {
+            return;
+        }
+        let w = ((-cp[0].x * chord.x - cp[0].y * chord.y) / chord_length2).clamp(0., 1.);
+        let u = u0 + (u1 - u0) * w;
+
+        let (point, tangent) = evaluate(cp, w);
+        let half_width = 0.5 * self.width_at(u);
+        let distance2 = point.x * point.x + point.y * point.y;
+        if distance2 > half_width * half_width || point.z < z_min || point.z > *z_max {
+            return;
+        }
+
+        // Signed offset across the width: negative on the tangent's left as seen along the ray.
+        let distance = distance2.sqrt();
+        let edge = tangent.x * -point.y + tangent.y * point.x;
+        let offset = if edge > 0. { -distance } else { distance } / half_width;
+        let v = 0.5 + 0.5 * offset;
+
+        let facing = Vector3::new(0., 0., -1.);
+        let normal = match self.shape {
+            CurveShape::Ribbon => facing,
+            CurveShape::Tube => {
+                let side = Vector3::new(tangent.y, -tangent.x, 0.);
+                let side = if side.magnitude() > 0. {
+                    side.normalize()
+                } else {
+                    Vector3::x()
+                };
+                side * offset + facing * (1. - offset * offset).max(0.).sqrt()
+            }
+        };
+
+        *z_max = point.z;
+        *hit = Some((point.z, u, v, normal));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Curve, CurveShape};
+    use crate::data::color64::Color64;
+    use crate::data::point64::Point64;
+    use crate::data::ray::Ray;
+    use crate::materials::Material;
+    use crate::materials::lambertian::Lambertian;
+    use crate::textures::Texture;
+    use approx::assert_abs_diff_eq;
+
+    /// A straight strand along +X from the origin, 0.2 wide at the root and 0.1 at the tip.
+    fn straight_curve(shape: CurveShape) -> Curve {
+        Curve::new(
+            [
+                Point64::new(0., 0., 0.),
+                Point64::new(1., 0., 0.),
+                Point64::new(2., 0., 0.),
+                Point64::new(3., 0., 0.),
+            ],
+            (0.2, 0.1),
+            shape,
+            Material::Lambertian(Lambertian {
+                albedo: Texture::solid(Color64::gray(0.5)),
+            }),
+        )
+    }
+
+    fn downward_ray(x: f64, z: f64) -> Ray {
+        Ray {
+            origin: Point64::new(x, 5., z),
+            direction: Point64::new(0., -2., 0.),
+            exposure_time: 0.,
+        }
+    }
+
+    #[test]
+    fn hits_within_varying_width() {
+        let curve = straight_curve(CurveShape::Ribbon);
+
+        let hr = curve
+            .is_hit_by(&downward_ray(1.5, 0.), 0.001, f64::INFINITY)
+            .unwrap();
+        assert_abs_diff_eq!(hr.value, 2.5, epsilon = 1e-9);
+        assert_abs_diff_eq!(hr.u, 0.5, epsilon = 1e-6);
+        assert_abs_diff_eq!(hr.v, 0.5, epsilon = 1e-6);
+        assert_abs_diff_eq!(hr.normal.y(), 1., epsilon = 1e-9);
+
+        // Half width is 0.1 at the root and 0.05 at the tip.
+        assert!(
+            curve
+                .is_hit_by(&downward_ray(0.1, 0.09), 0.001, f64::INFINITY)
+                .is_some()
+        );
+        assert!(
+            curve
+                .is_hit_by(&downward_ray(2.9, 0.09), 0.001, f64::INFINITY)
+                .is_none()
+        );
+        assert!(
+            curve
+                .is_hit_by(&downward_ray(2.9, 0.04), 0.001, f64::INFINITY)
+                .is_some()
+        );
+
+        // Past the end caps.
+        assert!(
+            curve
+                .is_hit_by(&downward_ray(-0.05, 0.), 0.001, f64::INFINITY)
+                .is_none()
+        );
+        assert!(
+            curve
+                .is_hit_by(&downward_ray(3.05, 0.), 0.001, f64::INFINITY)
+                .is_none()
+        );
+
+        // Outside the ray's range.
+        assert!(curve.is_hit_by(&downward_ray(1.5, 0.), 0.001, 2.).is_none());
+    }
+
+    #[test]
+    fn tube_normals_bend_across_width() {
+        let curve = straight_curve(CurveShape::Tube);
+
+        let left = curve
+            .is_hit_by(&downward_ray(1.5, -0.07), 0.001, f64::INFINITY)
+            .unwrap();
+        let right = curve
+            .is_hit_by(&downward_ray(1.5, 0.07), 0.001, f64::INFINITY)
+            .unwrap();
+
+        assert!(left.normal.z() * right.normal.z() < 0.);
+        assert!(left.normal.y() > 0. && right.normal.y() > 0.);
+        assert_abs_diff_eq!(left.v + right.v, 1., epsilon = 1e-6);
+        assert_abs_diff_eq!(left.normal.0.magnitude(), 1., epsilon = 1e-9);
+    }
+
+    #[test]
+    fn bent_curve_is_hit_along_its_path() {
+        // Control points of a symmetric arch in the XY plane; its apex is at (1.5, 0.75).
+        let curve = Curve::new(
+            [
+                Point64::new(0., 0., 0.),
+                Point64::new(1., 1., 0.),
+                Point64::new(2., 1., 0.),
+                Point64::new(3., 0., 0.),
+            ],
+            (0.05, 0.05),
+            CurveShape::Ribbon,
+            Material::Lambertian(Lambertian {
+                albedo: Texture::solid(Color64::gray(0.5)),
+            }),
+        );
+
+        let ray = Ray {
+            origin: Point64::new(1.5, 0.75, 5.),
+            direction: Point64::new(0., 0., -1.),
+            exposure_time: 0.,
+        };
+        let hr = curve.is_hit_by(&ray, 0.001, f64::INFINITY).unwrap();
+        assert_abs_diff_eq!(hr.value, 5., epsilon = 1e-6);
+        assert_abs_diff_eq!(hr.u, 0.5, epsilon = 1e-3);
+
+        // The chord between the endpoints is far from the curve.
+        let ray = Ray {
+            origin: Point64::new(1.5, 0., 5.),
+            ..ray
+        };
+        assert!(curve.is_hit_by(&ray, 0.001, f64::INFINITY).is_none());
+    }
+}
@@ -0,0 +1,389 @@
+use crate::data::point64::Point64;
+use crate::data::ray::Ray;
+use crate::hittables::HitRecord;
+use crate::hittables::axis_aligned_bounding_box::AxisAlignedBoundingBox;
+use crate::materials::Material;
+use nalgebra::Vector3;
+use std::path::Path;
+
+/// Terrain built from a grid of heights: one vertex per sample, two triangles per grid cell.
+///
+/// The field spans `size.x` along X and `size.z` along Z starting at `origin`; a sample of 1.0
+/// rises `size.y` above `origin.y`. Sample columns run along +X and rows along +Z, so image
+/// pixel `(x, y)` becomes the vertex at column `x`, row `y`. Texture coordinates span the whole
+/// field with the same orientation as [`ImageTexture`](crate::textures::image::ImageTexture),
+/// so the source image (or any other) drapes over it without distortion.
+#[derive(Clone)]
+pub struct Heightfield {
+    columns: usize,
+    rows: usize,
+    /// World-space heights, row-major.
+    heights: Vec<f64>,
+    /// Smooth per-vertex normals, row-major.
+    normals: Vec<Vector3<f64>>,
+    origin: Point64,
+    cell_size: (f64, f64),
+    bounding_box: AxisAlignedBoundingBox,
+    pub material: Material,
+}
+
+impl Heightfield {
+    /// `samples` holds `columns * rows` heights in [0, 1], row-major.
+    pub fn new(
+        samples: &[f64],
+        columns: usize,
+        rows: usize,
+        origin: Point64,
+        size: Vector3<f64>,
+        material: Material,
+    ) -> Self {
+        assert!(
+            columns >= 2 && rows >= 2,
+            "heightfield needs at least 2×2 samples"
+        );
+        assert_eq!(
+            samples.len(),
+            columns * rows,
+            "heightfield sample count mismatch"
+        );
+
+        let heights: Vec<f64> = samples.iter().map(|s| origin.y() + s * size.y).collect();
+        let cell_size = (size.x / (columns - 1) as f64, size.z / (rows - 1) as f64);
+
+        let height_at = |i: usize, j: usize| heights[j * columns + i];
+        let normals = (0..rows)
+            .flat_map(|j| (0..columns).map(move |i| (i, j)))
+            .map(|(i, j)| {
+                // Central differences, one-sided at the borders.
+                let (i0, i1) = (i.saturating_sub(1), (i + 1).min(columns - 1));
+                let (j0, j1) = (j.saturating_sub(1), (j + 1).min(rows - 1));
+                let dh_dx =
+                    (height_at(i1, j) - height_at(i0, j)) / ((i1 - i0) as f64 * cell_size.0);
+                let dh_dz =
+                    (height_at(i, j1) - height_at(i, j0)) / ((j1 - j0) as f64 * cell_size.1);
+                Vector3::new(-dh_dx, 1., -dh_dz).normalize()
+            })
+            .collect();
+
+        let (min_height, max_height) = heights
+            .iter()
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), h| {
+                (lo.min(*h), hi.max(*h))
+            });
+        // Keep the box from collapsing on perfectly flat terrain.
+        const DELTA: f64 = 0.0001;
+
+        Self {
+            columns,
+            rows,
+            heights,
+            normals,
+            origin,
+            cell_size,
+            bounding_box: AxisAlignedBoundingBox {
+                minimum: Point64::new(origin.x(), min_height - DELTA, origin.z()),
+                maximum: Point64::new(origin.x() + size.x, max_height + DELTA, origin.z() + size.z),
+            },
+            material,
+        }
+    }
+
+    /// Loads a grayscale height map. 8- and 16-bit images are both read at full precision;
+    /// color images are converted to luminance.
+    pub fn from_image(
+        path: &Path,
+        origin: Point64,
+        size: Vector3<f64>,
+        material: Material,
+    ) -> anyhow::Result<Self> {
+        let image = image::open(path)?.into_luma16();
+        let (columns, rows) = (image.width() as usize, image.height() as usize);
+        if columns < 2 || rows < 2 {
+            anyhow::bail!("height map {} is smaller than 2×2", path.display());
+        }
+
+        let samples: Vec<f64> = image
+            .pixels()
+            .map(|p| p.0[0] as f64 / u16::MAX as f64)
+            .collect();
+
+        Ok(Self::new(&samples, columns, rows, origin, size, material))
+    }
+
+    pub fn bounding_box(&self, _time0: f64, _time1: f64) -> Option<AxisAlignedBoundingBox> {
+        Some(self.bounding_box)
+    }
+
+    fn vertex(&self, i: usize, j: usize) -> Vector3<f64> {
+        Vector3::new(
+            self.origin.x() + i as f64 * self.cell_size.0,
+            self.heights[j * self.columns + i],
+            self.origin.z() + j as f64 * self.cell_size.1,
+        )
+    }
+
+    /// Walks the cells under the ray front to back (Amanatides–Woo grid DDA), skipping any cell
+    /// whose height range the ray passes entirely above or below.
+    pub fn is_hit_by(&self, ray: &Ray, min_value: f64, max_value: f64) -> Option<HitRecord<'_>> {
+        let (t_enter, t_exit) = self.bounding_box.hit_interval(ray, min_value, max_value)?;
+
+        let start = ray.point_at_parameter(t_enter);
+        let grid_x = (start.x() - self.origin.x()) / self.cell_size.0;
+        let grid_z = (start.z() - self.origin.z()) / self.cell_size.1;
+        let mut cell_x = (grid_x.floor() as isize).clamp(0, self.columns as isize - 2);
+        let mut cell_z = (grid_z.floor() as isize).clamp(0, self.rows as isize - 2);
+
+        let axis_setup = |direction: f64, grid: f64, cell: isize, size: f64| {
+            if direction > 0. {
+                (
+                    1,
+                    t_enter + ((cell + 1) as f64 - grid) * size / direction,
+                    size / direction,
+                )
+            } else if direction < 0. {
+                (
+                    -1,
+                    t_enter + (cell as f64 - grid) * size / direction,
+                    -size / direction,
+                )
+            } else {
+                (0, f64::INFINITY, f64::INFINITY)
+            }
+        };
+        let (step_x, mut next_x, delta_x) =
+            axis_setup(ray.direction.x(), grid_x, cell_x, self.cell_size.0);
+        let (step_z, mut next_z, delta_z) =
+            axis_setup(ray.direction.z(), grid_z, cell_z, self.cell_size.1);
+
+        let mut t_cell_enter = t_enter;
+        loop {
+            let t_cell_exit = next_x.min(next_z).min(t_exit);
+
+            if let Some(hr) = self.hit_cell(
+                ray,
+                cell_x as usize,
+                cell_z as usize,
+                t_cell_enter,
+                t_cell_exit,
+            ) {
+                return Some(hr);
+            }
+
+            if t_cell_exit >= t_exit {
+                return None;
+            }
+
+            if next_x < next_z {
+                cell_x += step_x;
+                next_x += delta_x;
+            } else {
+                cell_z += step_z;
+                next_z += delta_z;
+            }
+            t_cell_enter = t_cell_exit;
+
+            if cell_x < 0
+                || cell_z < 0
+                || cell_x > self.columns as isize - 2
+                || cell_z > self.rows as isize - 2
+            {
+                return None;
+            }
+        }
+    }
+
+    fn hit_cell(
+        &self,
+        ray: &Ray,
+        i: usize,
+        j: usize,
+        t_enter: f64,
+        t_exit: f64,
+    ) -> Option<HitRecord<'_>> {
+        let corners = [
+            self.vertex(i, j),
+            self.vertex(i + 1, j),
+            self.vertex(i, j + 1),
+            self.vertex(i + 1, j + 1),
+        ];
+
+        let cell_min = corners.iter().map(|c| c.y).fold(f64::INFINITY, f64::min);
+        let cell_max = corners
+            .iter()
+            .map(|c| c.y)
+            .fold(f64::NEG_INFINITY, f64::max);
+        let y_enter = ray.origin.y() + ray.direction.y() * t_enter;
+        let y_exit = ray.origin.y() + ray.direction.y() * t_exit;
+        if y_enter.min(y_exit) > cell_max || y_enter.max(y_exit) < cell_min {
+            return None;
+        }
+
+        // Split along the (i, j)–(i+1, j+1) diagonal. Each entry is the corner indices
+        // (into `corners` / the vertex grid offsets) of one triangle.
+        const TRIANGLES: [[usize; 3]; 2] = [[0, 1, 3], [0, 3, 2]];
+        const OFFSETS: [(usize, usize); 4] = [(0, 0), (1, 0), (0, 1), (1, 1)];
+
+        // Widen the range slightly so hits exactly on a cell boundary are not lost.
+        let slack = 1e-9 * (t_exit - t_enter).abs().max(1.);
+        let mut closest: Option<(f64, [usize; 3], f64, f64)> = None;
+        for triangle in TRIANGLES {
+            let max_t = closest.map_or(t_exit + slack, |(t, ..)| t);
+            if let Some((t, b1, b2)) = intersect_triangle(
+                ray,
+                &corners[triangle[0]],
+                &corners[triangle[1]],
+                &corners[triangle[2]],
+                t_enter - slack,
+                max_t,
+            ) {
+                closest = Some((t, triangle, b1, b2));
+            }
+        }
+
+        let (t, triangle, b1, b2) = closest?;
+        let b0 = 1. - b1 - b2;
+        let vertex_normal = |k: usize| {
+            let (di, dj) = OFFSETS[triangle[k]];
+            self.normals[(j + dj) * self.columns + i + di]
+        };
+        let normal =
+            (b0 * vertex_normal(0) + b1 * vertex_normal(1) + b2 * vertex_normal(2)).normalize();
+
+        let location = ray.point_at_parameter(t);
+        let u = (location.x() - self.origin.x()) / (self.cell_size.0 * (self.columns - 1) as f64);
+        let v = 1. - (location.z() - self.origin.z()) / (self.cell_size.1 * (self.rows - 1) as f64);
+
+        Some(HitRecord::new(
+            t,
+            ray,
+            Point64(normal),
+            &self.material,
+            (u.clamp(0., 1.), v.clamp(0., 1.)),
+        ))
+    }
+}
+
+/// Möller–Trumbore, returning `(t, b1, b2)` for hits strictly inside `(min_value, max_value)`.
+fn intersect_triangle(
+    ray: &Ray,
+    p0: &Vector3<f64>,
+    p1: &Vector3<f64>,
+    p2: &Vector3<f64>,
+    min_value: f64,
+    max_value: f64,
+) -> Option<(f64, f64, f64)> {
+    let e1 = p1 - p0;
+    let e2 = p2 - p0;
+    let p = ray.direction.0.cross(&e2);
+    let determinant = e1.dot(&p);
+    if determinant == 0. {
+        return None;
+    }
+
+    let inv_determinant = 1. / determinant;
+    let s = ray.origin.0 - p0;
+    let b1 = s.dot(&p) * inv_determinant;
+    if !(0.0..=1.0).contains(&b1) {
+        return None;
+    }
+
+    let q = s.cross(&e1);
+    let b2 = ray.direction.0.dot(&q) * inv_determinant;
+    if b2 < 0. || b1 + b2 > 1. {
+        return None;
+    }
+
+    let t = e2.dot(&q) * inv_determinant;
+    if min_value < t && t < max_value {
+        Some((t, b1, b2))
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Heightfield;
+    use crate::data::point64::Point64;
+    use crate::data::ray::Ray;
+    use crate::materials::Material;
+    use crate::materials::dielectric::Dielectric;
+    use approx::assert_abs_diff_eq;
+    use nalgebra::Vector3;
+
+    /// A 3×3 field over [0, 2]² with a single peak of height 1 in the middle.
+    fn peak() -> Heightfield {
+        #[rustfmt::skip]
+        let samples = [
+            0., 0., 0.,
+            0., 1., 0.,
+            0., 0., 0.,
+        ];
+        Heightfield::new(
+            &samples,
+            3,
+            3,
+            Point64::new(0., 0., 0.),
+            Vector3::new(2., 1., 2.),
+            Material::Dielectric(Dielectric::new(1.5)),
+        )
+    }
+
+    fn down_at(x: f64, z: f64) -> Ray {
+        Ray {
+            origin: Point64::new(x, 5., z),
+            direction: Point64::new(0., -1., 0.),
+            exposure_time: 0.,
+        }
+    }
+
+    #[test]
+    fn vertical_rays_hit_interpolated_height() {
+        let field = peak();
+
+        let hr = field
+            .is_hit_by(&down_at(1., 1.), 0.001, f64::INFINITY)
+            .unwrap();
+        assert_abs_diff_eq!(hr.location.y(), 1., epsilon = 1e-9);
+        assert_abs_diff_eq!(hr.normal.0, Vector3::new(0., 1., 0.), epsilon = 1e-9);
+        assert_abs_diff_eq!(hr.u, 0.5, epsilon = 1e-9);
+        assert_abs_diff_eq!(hr.v, 0.5, epsilon = 1e-9);
+
+        let hr = field
+            .is_hit_by(&down_at(0.5, 0.5), 0.001, f64::INFINITY)
+            .unwrap();
+        assert_abs_diff_eq!(hr.location.y(), 0.5, epsilon = 1e-9);
+
+        let hr = field
+            .is_hit_by(&down_at(2., 2.), 0.001, f64::INFINITY)
+            .unwrap();
+        assert_abs_diff_eq!(hr.location.y(), 0., epsilon = 1e-9);
+        assert_abs_diff_eq!(hr.u, 1., epsilon = 1e-9);
+        assert_abs_diff_eq!(hr.v, 0., epsilon = 1e-9);
+
+        assert!(
+            field
+                .is_hit_by(&down_at(2.5, 1.), 0.001, f64::INFINITY)
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn slanted_ray_crosses_cells() {
+        let field = peak();
+
+        // Skims across the field from -X at a height of 0.75: it passes over the first column
+        // of cells and strikes the rising side of the peak.
+        let ray = Ray {
+            origin: Point64::new(-1., 0.75, 1.),
+            direction: Point64::new(1., 0., 0.),
+            exposure_time: 0.,
+        };
+        let hr = field.is_hit_by(&ray, 0.001, f64::INFINITY).unwrap();
+        assert_abs_diff_eq!(hr.location.x(), 0.75, epsilon = 1e-9);
+        assert!(hr.front_face);
+
+        // The same ray limited to end before the peak misses.
+        assert!(field.is_hit_by(&ray, 0.001, 1.5).is_none());
+    }
+}
@@ -1,4 +1,5 @@
 use crate::data::ray::Ray;
+use crate::data::ray_packet::{PACKET_WIDTH, RayPacket};
 use crate::hittables::axis_aligned_bounding_box::AxisAlignedBoundingBox;
 use crate::hittables::{HitRecord, Hittable};
 
@@ -19,7 +20,7 @@ impl HittableVec {
         )
     }
 
-    pub fn is_hit_by(&self, ray: &Ray, min_value: f64, max_value: f64) -> Option<HitRecord> {
+    pub fn is_hit_by(&self, ray: &Ray, min_value: f64, max_value: f64) -> Option<HitRecord<'_>> {
         let mut winner: Option<HitRecord> = None;
 
         for hittable in self.hittables.iter() {
@@ -36,4 +37,32 @@ impl HittableVec {
 
         winner
     }
+
+    pub fn occluded(&self, ray: &Ray, min_value: f64, max_value: f64) -> bool {
+        self.hittables
+            .iter()
+            .any(|hittable| hittable.occluded(ray, min_value, max_value))
+    }
+
+    pub fn packet_hits(
+        &self,
+        packet: &RayPacket,
+        min_value: f64,
+        max_values: [f64; PACKET_WIDTH],
+    ) -> [Option<HitRecord<'_>>; PACKET_WIDTH] {
+        let mut winners: [Option<HitRecord>; PACKET_WIDTH] = Default::default();
+        let mut closest = max_values;
+
+        for hittable in self.hittables.iter() {
+            let results = hittable.packet_hits(packet, min_value, closest);
+            for (lane, result) in results.into_iter().enumerate() {
+                if let Some(hr) = result {
+                    closest[lane] = hr.value;
+                    winners[lane] = Some(hr);
+                }
+            }
+        }
+
+        winners
+    }
 }
@@ -0,0 +1,484 @@
+use crate::data::ray::Ray;
+use crate::data::ray_packet::{PACKET_WIDTH, RayPacket};
+use crate::data::storage::StoredBounds;
+use crate::hittables::axis_aligned_bounding_box::AxisAlignedBoundingBox;
+use crate::hittables::{HitRecord, Hittable};
+use nalgebra::Vector3;
+
+/// Deepest tree [`LinearBvh`] can traverse; its stack lives on the call stack. Builders stop
+/// splitting one level short of it.
+pub(crate) const MAX_DEPTH: usize = 64;
+
+/// A [`BoundedVolumeHierarchy`](super::bounded_volume_hierarchy::BoundedVolumeHierarchy)
+/// flattened into one array of nodes in depth-first order, with the primitives of each leaf
+/// stored contiguously. Traversal uses an explicit stack and visits the child nearer the ray
+/// origin first.
+#[derive(Clone)]
+pub struct LinearBvh {
+    nodes: Vec<LinearBvhNode>,
+    primitives: Vec<Hittable>,
+}
+
+#[derive(Clone, Copy)]
+pub(crate) struct LinearBvhNode {
+    /// Rounded outwards when stored in single precision.
+    pub(crate) bounds: StoredBounds,
+    /// For leaves, the index of the first primitive. For interior nodes, the index of the
+    /// second child; the first child immediately follows its parent.
+    pub(crate) offset: u32,
+    /// Number of primitives in a leaf, zero for interior nodes.
+    pub(crate) primitive_count: u32,
+    /// Axis along which the children are furthest apart, used to order traversal.
+    pub(crate) axis: u8,
+}
+
+impl LinearBvh {
+    /// Flattens the hierarchy rooted at `root`, which must have a bounding box. Nested
+    /// hierarchies are merged into this one, and multi-primitive leaves
+    /// ([`HittableVec`](super::hittable_vec::HittableVec)) are unpacked.
+    pub fn from_tree(root: Hittable, time0: f64, time1: f64) -> Self {
+        let mut linear = LinearBvh {
+            nodes: vec![],
+            primitives: vec![],
+        };
+        let bounds = root
+            .bounding_box(time0, time1)
+            .expect("LinearBvh needs a bounded root");
+        linear.flatten(root, bounds, 0);
+        linear
+    }
+
+    /// Reassembles a hierarchy from the [`nodes`](Self::nodes) and
+    /// [`primitives`](Self::primitives) of another, checking that every index is in range and
+    /// that the tree is shallow enough to traverse.
+    pub(crate) fn from_parts(
+        nodes: Vec<LinearBvhNode>,
+        primitives: Vec<Hittable>,
+    ) -> anyhow::Result<Self> {
+        anyhow::ensure!(!nodes.is_empty(), "BVH has no nodes");
+        let mut depths = vec![0; nodes.len()];
+        for (index, node) in nodes.iter().enumerate() {
+            if node.primitive_count == 0 {
+                // Children always come after their parent, so there can be no cycles, and
+                // every node's depth is known before its children are visited.
+                anyhow::ensure!(
+                    index + 1 < node.offset as usize && (node.offset as usize) < nodes.len(),
+                    "BVH node {index} has children out of range"
+                );
+                anyhow::ensure!(node.axis < 3, "BVH node {index} has a bad axis");
+                let depth = depths[index] + 1;
+                anyhow::ensure!(depth < MAX_DEPTH, "BVH is deeper than {MAX_DEPTH} levels");
+                for child in [index + 1, node.offset as usize] {
+                    depths[child] = depths[child].max(depth);
+                }
+            } else {
+                anyhow::ensure!(
+                    node.offset as usize + node.primitive_count as usize <= primitives.len(),
+                    "BVH leaf {index} has primitives out of range"
+                );
+            }
+        }
+
+        Ok(LinearBvh { nodes, primitives })
+    }
+
+    pub(crate) fn nodes(&self) -> &[LinearBvhNode] {
+        &self.nodes
+    }
+
+    /// Primitives in leaf order.
+    pub(crate) fn primitives(&self) -> &[Hittable] {
+        &self.primitives
+    }
+
+    fn flatten(&mut self, hittable: Hittable, bounds: AxisAlignedBoundingBox, depth: usize) {
+        assert!(depth < MAX_DEPTH, "BVH is deeper than {MAX_DEPTH} levels");
+        let index = self.nodes.len();
+        let stored_bounds = StoredBounds::new(&bounds);
+
+        match hittable {
+            Hittable::Bvh(bvh) => {
+                let separation = bvh.right_bounds.centroid().0 - bvh.left_bounds.centroid().0;
+                let axis = separation.abs().imax();
+                self.nodes.push(LinearBvhNode {
+                    bounds: stored_bounds,
+                    offset: 0,
+                    primitive_count: 0,
+                    axis: axis as u8,
+                });
+                // Store the child with the smaller centroid along `axis` first, so rays
+                // heading in the positive direction visit it first.
+                let (first, first_bounds, second, second_bounds) = if separation[axis] >= 0. {
+                    (
+                        bvh.left_child,
+                        bvh.left_bounds,
+                        bvh.right_child,
+                        bvh.right_bounds,
+                    )
+                } else {
+                    (
+                        bvh.right_child,
+                        bvh.right_bounds,
+                        bvh.left_child,
+                        bvh.left_bounds,
+                    )
+                };
+                self.flatten(*first, first_bounds, depth + 1);
+                self.nodes[index].offset = self.nodes.len() as u32;
+                self.flatten(*second, second_bounds, depth + 1);
+            }
+            Hittable::HittableVec(leaf) => {
+                let first = self.primitives.len() as u32;
+                self.primitives.extend(leaf.hittables);
+                self.nodes.push(LinearBvhNode {
+                    bounds: stored_bounds,
+                    offset: first,
+                    primitive_count: self.primitives.len() as u32 - first,
+                    axis: 0,
+                });
+            }
+            primitive => {
+                self.nodes.push(LinearBvhNode {
+                    bounds: stored_bounds,
+                    offset: self.primitives.len() as u32,
+                    primitive_count: 1,
+                    axis: 0,
+                });
+                self.primitives.push(primitive);
+            }
+        }
+    }
+
+    pub fn bounding_box(&self, _time0: f64, _time1: f64) -> Option<AxisAlignedBoundingBox> {
+        Some(self.nodes[0].bounds.load())
+    }
+
+    pub fn is_hit_by(&self, ray: &Ray, min_value: f64, max_value: f64) -> Option<HitRecord<'_>> {
+        let inv_direction = Vector3::repeat(1.).component_div(&ray.direction.0);
+        let mut winner: Option<HitRecord> = None;
+        let mut closest = max_value;
+
+        let mut stack = [0u32; MAX_DEPTH];
+        let mut stack_len = 0;
+        let mut index = 0;
+
+        loop {
+            let node = &self.nodes[index];
+            let hit = node
+                .bounds
+                .load()
+                .hit_interval_with_inverse(&ray.origin, &inv_direction, min_value, closest)
+                .is_some();
+
+            if hit && node.primitive_count == 0 {
+                let (near, far) = if inv_direction[node.axis as usize] < 0. {
+                    (node.offset, index as u32 + 1)
+                } else {
+                    (index as u32 + 1, node.offset)
+                };
+                stack[stack_len] = far;
+                stack_len += 1;
+                index = near as usize;
+                continue;
+            }
+
+            if hit {
+                let first = node.offset as usize;
+                for primitive in &self.primitives[first..first + node.primitive_count as usize] {
+                    if let Some(hr) = primitive.is_hit_by(ray, min_value, closest) {
+                        closest = hr.value;
+                        winner = Some(hr);
+                    }
+                }
+            }
+
+            if stack_len == 0 {
+                break;
+            }
+            stack_len -= 1;
+            index = stack[stack_len] as usize;
+        }
+
+        winner
+    }
+
+    /// Whether anything lies along the ray within `(min_value, max_value)`. Stops at the first
+    /// primitive hit, in whatever order the tree is walked.
+    pub fn occluded(&self, ray: &Ray, min_value: f64, max_value: f64) -> bool {
+        let inv_direction = Vector3::repeat(1.).component_div(&ray.direction.0);
+        let mut stack = [0u32; MAX_DEPTH];
+        let mut stack_len = 0;
+        let mut index = 0;
+
+        loop {
+            let node = &self.nodes[index];
+            let hit = node
+                .bounds
+                .load()
+                .hit_interval_with_inverse(&ray.origin, &inv_direction, min_value, max_value)
+                .is_some();
+
+            if hit && node.primitive_count == 0 {
+                stack[stack_len] = node.offset;
+                stack_len += 1;
+                index += 1;
+                continue;
+            }
+
+            if hit {
+                let first = node.offset as usize;
+                if self.primitives[first..first + node.primitive_count as usize]
+                    .iter()
+                    .any(|primitive| primitive.occluded(ray, min_value, max_value))
+                {
+                    return true;
+                }
+            }
+
+            if stack_len == 0 {
+                return false;
+            }
+            stack_len -= 1;
+            index = stack[stack_len] as usize;
+        }
+    }
+
+    /// [`is_hit_by`](Self::is_hit_by) for a packet of rays. Each node is tested against every
+    /// lane still interested in it, and the packet descends while any lane hits. Children
+    /// are ordered by the direction of the first such lane, which for coherent rays is the
+    /// direction of all of them.
+    pub fn packet_hits(
+        &self,
+        packet: &RayPacket,
+        min_value: f64,
+        max_values: [f64; PACKET_WIDTH],
+    ) -> [Option<HitRecord<'_>>; PACKET_WIDTH] {
+        let mut winners: [Option<HitRecord>; PACKET_WIDTH] = Default::default();
+        let mut closest = max_values;
+
+        // Each deferred node remembers which lanes reached its parent.
+        let mut stack = [(0u32, [false; PACKET_WIDTH]); MAX_DEPTH];
+        let mut stack_len = 0;
+        let mut index = 0;
+        let mut active = [true; PACKET_WIDTH];
+
+        loop {
+            let node = &self.nodes[index];
+            let hits = packet.hits_box(&node.bounds.load(), active, min_value, &closest);
+
+            if let Some(lead) = hits.iter().position(|&hit| hit) {
+                if node.primitive_count == 0 {
+                    let (near, far) = if packet.inv_direction(node.axis as usize, lead) < 0. {
+                        (node.offset, index as u32 + 1)
+                    } else {
+                        (index as u32 + 1, node.offset)
+                    };
+                    stack[stack_len] = (far, hits);
+                    stack_len += 1;
+                    index = near as usize;
+                    active = hits;
+                    continue;
+                }
+
+                let first = node.offset as usize;
+                for primitive in &self.primitives[first..first + node.primitive_count as usize] {
+                    for lane in (0..PACKET_WIDTH).filter(|&lane| hits[lane]) {
+                        if let Some(hr) =
+                            primitive.is_hit_by(&packet.rays[lane], min_value, closest[lane])
+                        {
+                            closest[lane] = hr.value;
+                            winners[lane] = Some(hr);
+                        }
+                    }
+                }
+            }
+
+            if stack_len == 0 {
+                break;
+            }
+            stack_len -= 1;
+            (index, active) = (stack[stack_len].0 as usize, stack[stack_len].1);
+        }
+
+        winners
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{LinearBvh, LinearBvhNode, MAX_DEPTH};
+    use crate::data::point64::Point64;
+    use crate::data::ray::Ray;
+    use crate::data::ray_packet::{PACKET_WIDTH, RayPacket};
+    use crate::data::storage::StoredBounds;
+    use crate::data::vector3::rand_range;
+    use crate::hittables::Hittable;
+    use crate::hittables::bounded_volume_hierarchy::{
+        BoundedVolumeHierarchy, BvhBuildOptions, BvhLayout,
+    };
+    use crate::hittables::hittable_vec::HittableVec;
+    use crate::hittables::plane::Plane;
+    use crate::hittables::quad::Quad;
+    use crate::hittables::sphere::Sphere;
+    use crate::hittables::top_level_bvh::{Instance, TopLevelBvh};
+    use crate::hittables::triangle::Triangle;
+    use crate::materials::Material;
+    use crate::materials::dielectric::Dielectric;
+    use nalgebra::{Isometry3, Vector3};
+    use std::array;
+    use std::sync::Arc;
+
+    #[test]
+    fn packets_find_the_same_hits_as_single_rays() {
+        let material = Material::Dielectric(Dielectric::new(1.5));
+        let spheres: Vec<Hittable> = (0..200)
+            .map(|_| {
+                Hittable::Sphere(Sphere {
+                    center: Point64(rand_range(-10., 10.)),
+                    radius: 0.7,
+                    material: material.clone(),
+                })
+            })
+            .collect();
+        let bvh = BoundedVolumeHierarchy::create_bvh(&spheres, 0., 1.);
+        assert!(matches!(bvh, Hittable::LinearBvh(_)));
+        let world = Hittable::HittableVec(HittableVec {
+            hittables: vec![bvh, Hittable::Plane(Plane::horizontal(-8., material))],
+        });
+
+        let origin = Point64::new(0., 2., 30.);
+        for packet in 0..200 {
+            let rays = array::from_fn(|lane| {
+                // Mostly neighbouring rays from one eye; every fifth packet is incoherent.
+                let (origin, target) = if packet % 5 == 4 {
+                    (
+                        Point64(rand_range(-30., 30.)),
+                        Point64(rand_range(-10., 10.)),
+                    )
+                } else {
+                    let step = (packet * PACKET_WIDTH + lane) as f64;
+                    let target = Point64::new(step % 40. / 2. - 10., step / 80. - 10., 0.);
+                    (origin, target)
+                };
+                Ray {
+                    origin,
+                    direction: target - origin,
+                    exposure_time: 0.,
+                }
+            });
+            let packet = RayPacket::new(rays);
+            let max_values = [f64::INFINITY, f64::INFINITY, 25., f64::INFINITY];
+            let hits = world.packet_hits(&packet, 0.001, max_values);
+
+            for (lane, hit) in hits.iter().enumerate() {
+                let expected = world.is_hit_by(&packet.rays[lane], 0.001, max_values[lane]);
+                assert_eq!(
+                    hit.as_ref().map(|hr| (hr.value, hr.normal)),
+                    expected.map(|hr| (hr.value, hr.normal))
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn occlusion_agrees_with_closest_hits() {
+        let material = Material::Dielectric(Dielectric::new(1.5));
+        let shapes: Vec<Hittable> = (0..300)
+            .map(|i| {
+                let corner = Point64(rand_range(-10., 10.));
+                match i % 3 {
+                    0 => Hittable::Sphere(Sphere {
+                        center: corner,
+                        radius: 0.5,
+                        material: material.clone(),
+                    }),
+                    1 => Hittable::Triangle(Triangle::new(
+                        corner,
+                        corner + Point64(rand_range(-1., 1.)),
+                        corner + Point64(rand_range(-1., 1.)),
+                        Arc::new(material.clone()),
+                    )),
+                    _ => Hittable::Quad(Quad::new(
+                        corner,
+                        Point64(rand_range(-1., 1.)),
+                        Point64(rand_range(-1., 1.)),
+                        material.clone(),
+                    )),
+                }
+            })
+            .collect();
+        let linear = BoundedVolumeHierarchy::create_bvh(&shapes, 0., 1.);
+        let (tree, _) = BoundedVolumeHierarchy::build(
+            &shapes,
+            0.,
+            1.,
+            &BvhBuildOptions {
+                layout: BvhLayout::Tree,
+                ..BvhBuildOptions::default()
+            },
+        );
+        let tlas = Hittable::TopLevelBvh(TopLevelBvh::new(vec![
+            Instance::new(Arc::new(linear.clone()), Isometry3::identity()),
+            Instance::new(
+                Arc::new(tree.clone()),
+                Isometry3::new(Vector3::new(5., 0., 0.), Vector3::y()),
+            ),
+        ]));
+
+        let mut blocked = 0;
+        for _ in 0..2000 {
+            let origin = Point64(rand_range(-12., 12.));
+            let ray = Ray {
+                origin,
+                direction: Point64(rand_range(-12., 12.)) - origin,
+                exposure_time: 0.,
+            };
+            for hittable in [&linear, &tree, &tlas] {
+                let expected = hittable.is_hit_by(&ray, 0.001, 1.).is_some();
+                assert_eq!(hittable.occluded(&ray, 0.001, 1.), expected);
+                blocked += expected as usize;
+            }
+        }
+        assert!(blocked > 100);
+    }
+
+    /// A cache file may hold any layout, so a chain too deep for the traversal stack must be
+    /// rejected rather than overflow it.
+    #[test]
+    fn reassembly_rejects_trees_too_deep_to_traverse() {
+        let sphere = Hittable::Sphere(Sphere {
+            center: Point64::new(0., 0., 0.),
+            radius: 1.,
+            material: Material::Dielectric(Dielectric::new(1.5)),
+        });
+        let bounds = StoredBounds::new(&sphere.bounding_box(0., 0.).unwrap());
+        // Interior nodes each lead to the next, with the last node as every second child.
+        let chain = |interior: usize| {
+            let mut nodes: Vec<LinearBvhNode> = (0..interior)
+                .map(|_| LinearBvhNode {
+                    bounds,
+                    offset: interior as u32 + 1,
+                    primitive_count: 0,
+                    axis: 0,
+                })
+                .collect();
+            let leaf = LinearBvhNode {
+                bounds,
+                offset: 0,
+                primitive_count: 1,
+                axis: 0,
+            };
+            nodes.extend([leaf, leaf]);
+            LinearBvh::from_parts(nodes, vec![sphere.clone()])
+        };
+
+        let deepest = chain(MAX_DEPTH - 1).unwrap();
+        let ray = Ray {
+            origin: Point64::new(0., 0., 5.),
+            direction: Point64::new(0., 0., -1.),
+            exposure_time: 0.,
+        };
+        assert!(deepest.is_hit_by(&ray, 0.001, f64::INFINITY).is_some());
+        assert!(chain(MAX_DEPTH).is_err());
+    }
+}
@@ -0,0 +1,340 @@
+use crate::data::point64::Point64;
+use crate::data::ray::Ray;
+use crate::hittables::HitRecord;
+use crate::hittables::axis_aligned_bounding_box::AxisAlignedBoundingBox;
+use crate::hittables::sphere::get_sphere_uv;
+use crate::materials::Material;
+use nalgebra::Vector3;
+
+/// One source of a [`Metaballs`] field. Its influence falls smoothly from `weight` at the
+/// center to zero at `radius`, and its center moves from `center0` to `center1` over the
+/// owning field's `time0..time1` like a [`MovingSphere`](super::moving_sphere::MovingSphere).
+#[derive(Clone)]
+pub struct Metaball {
+    pub center0: Point64,
+    pub center1: Point64,
+    pub radius: f64,
+    pub weight: f64,
+}
+
+impl Metaball {
+    pub fn fixed(center: Point64, radius: f64, weight: f64) -> Self {
+        Self {
+            center0: center,
+            center1: center,
+            radius,
+            weight,
+        }
+    }
+}
+
+/// The iso-surface where the summed influence of all balls equals `threshold`.
+///
+/// Each ball contributes `weight·(1 − d²/radius²)³`, which has compact support, so the surface
+/// lies inside the union of the balls' spheres and rays are only searched there.
+#[derive(Clone)]
+pub struct Metaballs {
+    pub balls: Vec<Metaball>,
+    pub threshold: f64,
+    pub time0: f64,
+    pub time1: f64,
+    pub material: Material,
+}
+
+/// Root finding stops once the bracket is this small (in ray parameter units, relative to
+/// the smallest ball).
+const ROOT_TOLERANCE: f64 = 1e-9;
+/// Samples per smallest ball radius when scanning for a sign change.
+const STEPS_PER_RADIUS: f64 = 16.;
+
+impl Metaballs {
+    fn center_at(&self, ball: &Metaball, time: f64) -> Point64 {
+        if self.time1 == self.time0 {
+            return ball.center0;
+        }
+        ball.center0
+            + (ball.center1 - ball.center0) * ((time - self.time0) / (self.time1 - self.time0))
+    }
+
+    pub fn bounding_box(&self, time0: f64, time1: f64) -> Option<AxisAlignedBoundingBox> {
+        self.balls
+            .iter()
+            .flat_map(|ball| {
+                let half_box_side = Vector3::repeat(ball.radius);
+                [self.center_at(ball, time0), self.center_at(ball, time1)].map(|center| {
+                    AxisAlignedBoundingBox {
+                        minimum: Point64(center.0 - half_box_side),
+                        maximum: Point64(center.0 + half_box_side),
+                    }
+                })
+            })
+            .reduce(|acc, bbox| acc.surrounding_box_with(&bbox))
+    }
+
+    /// Field value minus the threshold, and its gradient, at `p` for the given ball centers.
+    fn field(&self, centers: &[Vector3<f64>], p: &Vector3<f64>) -> (f64, Vector3<f64>) {
+        let mut value = -self.threshold;
+        let mut gradient = Vector3::zeros();
+
+        for (ball, center) in self.balls.iter().zip(centers) {
+            let offset = p - center;
+            let r2 = ball.radius * ball.radius;
+            let falloff = 1. - offset.norm_squared() / r2;
+            if falloff > 0. {
+                value += ball.weight * falloff * falloff * falloff;
+                gradient -= offset * (6. * ball.weight * falloff * falloff / r2);
+            }
+        }
+
+        (value, gradient)
+    }
+
+    pub fn is_hit_by(&self, ray: &Ray, min_value: f64, max_value: f64) -> Option<HitRecord<'_>> {
+        let direction_length2 = ray.direction.0.norm_squared();
+        if direction_length2 == 0. || self.balls.is_empty() {
+            return None;
+        }
+
+        let centers: Vec<Vector3<f64>> = self
+            .balls
+            .iter()
+            .map(|ball| self.center_at(ball, ray.exposure_time).0)
+            .collect();
+
+        // Parameter ranges where the ray is inside at least one ball's support, merged.
+        let mut spans: Vec<(f64, f64)> = self
+            .balls
+            .iter()
+            .zip(&centers)
+            .filter_map(|(ball, center)| {
+                let to_origin = ray.origin.0 - center;
+                let half_b = to_origin.dot(&ray.direction.0);
+                let c = to_origin.norm_squared() - ball.radius * ball.radius;
+                let discriminant = half_b * half_b - direction_length2 * c;
+                if discriminant <= 0. {
+                    return None;
+                }
+                let root = discriminant.sqrt();
+                let enter = ((-half_b - root) / direction_length2).max(min_value);
+                let exit = ((-half_b + root) / direction_length2).min(max_value);
+                (enter < exit).then_some((enter, exit))
+            })
+            .collect();
+        spans.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        let min_radius = self
+            .balls
+            .iter()
+            .map(|ball| ball.radius)
+            .fold(f64::INFINITY, f64::min);
+        let step = min_radius / STEPS_PER_RADIUS / direction_length2.sqrt();
+        let tolerance = ROOT_TOLERANCE * min_radius / direction_length2.sqrt();
+        let value_at = |t: f64| self.field(&centers, &ray.point_at_parameter(t).0).0;
+
+        let mut merged_end = f64::NEG_INFINITY;
+        for (index, &(enter, exit)) in spans.iter().enumerate() {
+            if exit <= merged_end {
+                continue;
+            }
+            let mut end = exit;
+            for &(next_enter, next_exit) in &spans[index + 1..] {
+                if next_enter > end {
+                    break;
+                }
+                end = end.max(next_exit);
+            }
+            let start = enter.max(merged_end);
+            merged_end = end;
+
+            // March until the field crosses the threshold, then refine the bracket.
+            let mut t0 = start;
+            let mut f0 = value_at(t0);
+            while t0 < end {
+                let t1 = (t0 + step).min(end);
+                let f1 = value_at(t1);
+                if f0 == 0. || f0.signum() != f1.signum() {
+                    let t = if f0 == 0. {
+                        t0
+                    } else {
+                        illinois(value_at, (t0, f0), (t1, f1), tolerance)
+                    };
+                    if min_value < t && t < max_value {
+                        return Some(self.hit_record_at(ray, &centers, t));
+                    }
+                }
+                t0 = t1;
+                f0 = f1;
+            }
+        }
+
+        None
+    }
+
+    fn hit_record_at(&self, ray: &Ray, centers: &[Vector3<f64>], t: f64) -> HitRecord<'_> {
+        let location = ray.point_at_parameter(t);
+        let (_, gradient) = self.field(centers, &location.0);
+        // The field grows inward, so the surface normal points down the gradient.
+        let outward_normal = if gradient.norm_squared() > 0. {
+            Point64(-gradient.normalize())
+        } else {
+            -ray.direction / ray.direction.0.magnitude()
+        };
+
+        HitRecord::new(
+            t,
+            ray,
+            outward_normal,
+            &self.material,
+            get_sphere_uv(outward_normal),
+        )
+    }
+}
+
+/// Root of `f` inside a sign-changing bracket by the Illinois variant of regula falsi, which
+/// halves the weight of an endpoint retained twice in a row so convergence stays superlinear.
+fn illinois(
+    f: impl Fn(f64) -> f64,
+    (mut a, mut fa): (f64, f64),
+    (mut b, mut fb): (f64, f64),
+    tolerance: f64,
+) -> f64 {
+    let mut side = 0;
+
+    for _ in 0..64 {
+        if (b - a).abs() <= tolerance {
+            break;
+        }
+        let c = (a * fb - b * fa) / (fb - fa);
+        let fc = f(c);
+        if fc == 0. {
+            return c;
+        }
+
+        if fc.signum() == fb.signum() {
+            b = c;
+            fb = fc;
+            if side == -1 {
+                fa *= 0.5;
+            }
+            side = -1;
+        } else {
+            a = c;
+            fa = fc;
+            if side == 1 {
+                fb *= 0.5;
+            }
+            side = 1;
+        }
+    }
+
+    0.5 * (a + b)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Metaball, Metaballs};
+    use crate::data::point64::Point64;
+    use crate::data::ray::Ray;
+    use crate::materials::Material;
+    use crate::materials::dielectric::Dielectric;
+    use approx::assert_abs_diff_eq;
+
+    fn blob(balls: Vec<Metaball>) -> Metaballs {
+        Metaballs {
+            balls,
+            threshold: 0.25,
+            time0: 0.,
+            time1: 1.,
+            material: Material::Dielectric(Dielectric::new(1.5)),
+        }
+    }
+
+    fn ray_along_x(y: f64, exposure_time: f64) -> Ray {
+        Ray {
+            origin: Point64::new(-10., y, 0.),
+            direction: Point64::new(2., 0., 0.),
+            exposure_time,
+        }
+    }
+
+    #[test]
+    fn single_ball_is_a_sphere() {
+        // weight·(1 − d²/R²)³ = threshold at d = R·sqrt(1 − (threshold/weight)^(1/3)).
+        let metaballs = blob(vec![Metaball::fixed(Point64::new(0., 0., 0.), 2., 1.)]);
+        let surface = 2. * (1. - 0.25f64.cbrt()).sqrt();
+
+        let hr = metaballs
+            .is_hit_by(&ray_along_x(0., 0.), 0.001, f64::INFINITY)
+            .unwrap();
+        assert_abs_diff_eq!(hr.location.x(), -surface, epsilon = 1e-7);
+        assert_abs_diff_eq!(hr.value, (10. - surface) / 2., epsilon = 1e-7);
+        assert_abs_diff_eq!(hr.normal.x(), -1., epsilon = 1e-9);
+        assert!(hr.front_face);
+
+        // From inside, the exit is found.
+        let hr = metaballs
+            .is_hit_by(&ray_along_x(0., 0.), 5., f64::INFINITY)
+            .unwrap();
+        assert_abs_diff_eq!(hr.location.x(), surface, epsilon = 1e-7);
+        assert!(!hr.front_face);
+
+        assert!(
+            metaballs
+                .is_hit_by(&ray_along_x(surface + 1e-3, 0.), 0.001, f64::INFINITY)
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn nearby_balls_merge() {
+        // Each ball alone reaches the threshold within ~1.22 of its center, so two balls 2.6
+        // apart would not touch on their own but are bridged by their summed field.
+        let metaballs = blob(vec![
+            Metaball::fixed(Point64::new(-1.3, 0., 0.), 2., 1.),
+            Metaball::fixed(Point64::new(1.3, 0., 0.), 2., 1.),
+        ]);
+
+        let down = Ray {
+            origin: Point64::new(0., 10., 0.),
+            direction: Point64::new(0., -1., 0.),
+            exposure_time: 0.,
+        };
+        let hr = metaballs.is_hit_by(&down, 0.001, f64::INFINITY).unwrap();
+        assert!(hr.location.y() > 0.);
+        assert_abs_diff_eq!(hr.normal.y(), 1., epsilon = 1e-9);
+
+        let bbox = metaballs.bounding_box(0., 1.).unwrap();
+        assert_abs_diff_eq!(bbox.minimum.x(), -3.3);
+        assert_abs_diff_eq!(bbox.maximum.x(), 3.3);
+    }
+
+    #[test]
+    fn balls_move_over_the_exposure() {
+        let metaballs = blob(vec![Metaball {
+            center0: Point64::new(0., 0., 0.),
+            center1: Point64::new(0., 4., 0.),
+            radius: 2.,
+            weight: 1.,
+        }]);
+
+        assert!(
+            metaballs
+                .is_hit_by(&ray_along_x(0., 0.), 0.001, f64::INFINITY)
+                .is_some()
+        );
+        assert!(
+            metaballs
+                .is_hit_by(&ray_along_x(0., 1.), 0.001, f64::INFINITY)
+                .is_none()
+        );
+        assert!(
+            metaballs
+                .is_hit_by(&ray_along_x(4., 1.), 0.001, f64::INFINITY)
+                .is_some()
+        );
+
+        let bbox = metaballs.bounding_box(0., 1.).unwrap();
+        assert_abs_diff_eq!(bbox.minimum.y(), -2.);
+        assert_abs_diff_eq!(bbox.maximum.y(), 6.);
+    }
+}
@@ -40,7 +40,7 @@ impl MovingSphere {
         Some(box0.surrounding_box_with(&box1))
     }
 
-    pub fn is_hit_by(&self, ray: &Ray, min_value: f64, max_value: f64) -> Option<HitRecord> {
+    pub fn is_hit_by(&self, ray: &Ray, min_value: f64, max_value: f64) -> Option<HitRecord<'_>> {
         let ray_origin_to_center = ray.origin - self.center_at(ray.exposure_time);
         let a = ray.direction.0.dot(&ray.direction.0);
         let half_b = ray_origin_to_center.0.dot(&ray.direction.0);
@@ -68,7 +68,7 @@ impl MovingSphere {
                     root,
                     ray,
                     outward_normal,
-                    self.material.clone(),
+                    &self.material,
                     get_sphere_uv(outward_normal),
                 ))
             } else {
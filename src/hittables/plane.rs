@@ -0,0 +1,157 @@
+use crate::data::point64::Point64;
+use crate::data::ray::Ray;
+use crate::hittables::HitRecord;
+use crate::hittables::axis_aligned_bounding_box::AxisAlignedBoundingBox;
+use crate::materials::Material;
+use nalgebra::Vector3;
+
+/// An infinite plane through `point` facing `normal`.
+///
+/// It has no bounding box, so keep it out of
+/// [`BoundedVolumeHierarchy`](super::bounded_volume_hierarchy::BoundedVolumeHierarchy) and
+/// list it next to the hierarchy instead. Texture coordinates are planar: `u` and `v` measure
+/// distance from `point` along two axes in the plane, divided by `uv_scale`, and keep growing
+/// without wrapping, which suits tiling textures such as
+/// [`Texture::UvChecker`](crate::textures::Texture::UvChecker).
+#[derive(Clone)]
+pub struct Plane {
+    point: Point64,
+    normal: Vector3<f64>,
+    u_axis: Vector3<f64>,
+    v_axis: Vector3<f64>,
+    uv_scale: f64,
+    pub material: Material,
+}
+
+impl Plane {
+    pub fn new(point: Point64, normal: Vector3<f64>, material: Material) -> Self {
+        let normal = normal.normalize();
+        // For horizontal planes this makes u run along +X and v along -Z, matching how
+        // `Quad` grounds are usually laid out.
+        let helper = if normal.x.abs() > 0.9 {
+            Vector3::y()
+        } else {
+            Vector3::x()
+        };
+        let v_axis = normal.cross(&helper).normalize();
+        let u_axis = v_axis.cross(&normal);
+
+        Self {
+            point,
+            normal,
+            u_axis,
+            v_axis,
+            uv_scale: 1.,
+            material,
+        }
+    }
+
+    /// A plane facing +Y at height `y`.
+    pub fn horizontal(y: f64, material: Material) -> Self {
+        Self::new(Point64::new(0., y, 0.), Vector3::y(), material)
+    }
+
+    /// Size in world units of one unit of texture coordinate.
+    pub fn with_uv_scale(mut self, uv_scale: f64) -> Self {
+        self.uv_scale = uv_scale;
+        self
+    }
+
+    /// The plane's height if it is horizontal.
+    pub fn horizontal_height(&self) -> Option<f64> {
+        (self.normal.y.abs() > 1. - 1e-9).then_some(self.point.y())
+    }
+
+    pub fn bounding_box(&self, _time0: f64, _time1: f64) -> Option<AxisAlignedBoundingBox> {
+        None
+    }
+
+    fn intersect(&self, ray: &Ray, min_value: f64, max_value: f64) -> Option<f64> {
+        let denom = self.normal.dot(&ray.direction.0);
+        if denom == 0. {
+            return None;
+        }
+
+        let t = self.normal.dot(&(self.point.0 - ray.origin.0)) / denom;
+        (min_value..max_value).contains(&t).then_some(t)
+    }
+
+    pub fn occluded(&self, ray: &Ray, min_value: f64, max_value: f64) -> bool {
+        self.intersect(ray, min_value, max_value).is_some()
+    }
+
+    pub fn is_hit_by(&self, ray: &Ray, min_value: f64, max_value: f64) -> Option<HitRecord<'_>> {
+        let t = self.intersect(ray, min_value, max_value)?;
+
+        let offset = ray.point_at_parameter(t).0 - self.point.0;
+        Some(HitRecord::new(
+            t,
+            ray,
+            Point64(self.normal),
+            &self.material,
+            (
+                offset.dot(&self.u_axis) / self.uv_scale,
+                offset.dot(&self.v_axis) / self.uv_scale,
+            ),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Plane;
+    use crate::data::point64::Point64;
+    use crate::data::ray::Ray;
+    use crate::materials::Material;
+    use crate::materials::dielectric::Dielectric;
+    use approx::assert_abs_diff_eq;
+    use nalgebra::Vector3;
+
+    fn material() -> Material {
+        Material::Dielectric(Dielectric::new(1.5))
+    }
+
+    #[test]
+    fn planar_uvs() {
+        let plane = Plane::horizontal(-1., material()).with_uv_scale(2.);
+        assert_eq!(plane.horizontal_height(), Some(-1.));
+
+        let ray = Ray {
+            origin: Point64::new(3., 4., -5.),
+            direction: Point64::new(0., -1., 0.),
+            exposure_time: 0.,
+        };
+        let hr = plane.is_hit_by(&ray, 0.001, f64::INFINITY).unwrap();
+        assert_abs_diff_eq!(hr.value, 5.);
+        assert_abs_diff_eq!(hr.u, 1.5);
+        assert_abs_diff_eq!(hr.v, 2.5);
+        assert!(hr.front_face);
+
+        assert!(plane.is_hit_by(&ray, 0.001, 4.).is_none());
+    }
+
+    #[test]
+    fn slanted_plane() {
+        let plane = Plane::new(
+            Point64::new(0., 0., 0.),
+            Vector3::new(1., 1., 0.),
+            material(),
+        );
+        assert_eq!(plane.horizontal_height(), None);
+        assert!(plane.bounding_box(0., 1.).is_none());
+
+        let ray = Ray {
+            origin: Point64::new(-3., 1., 7.),
+            direction: Point64::new(1., 0., 0.),
+            exposure_time: 0.,
+        };
+        let hr = plane.is_hit_by(&ray, 0.001, f64::INFINITY).unwrap();
+        assert_abs_diff_eq!(hr.location.x(), -1., epsilon = 1e-12);
+
+        let parallel = Ray {
+            direction: Point64::new(1., -1., 0.),
+            ..ray
+        };
+        assert!(plane.is_hit_by(&parallel, 0.001, f64::INFINITY).is_none());
+    }
+}
@@ -57,7 +57,8 @@ impl Quad {
         Some(bbox)
     }
 
-    pub fn is_hit_by(&self, ray: &Ray, min_value: f64, max_value: f64) -> Option<HitRecord> {
+    /// Hit distance and planar coordinates of the hit along `u` and `v`.
+    fn intersect(&self, ray: &Ray, min_value: f64, max_value: f64) -> Option<(f64, f64, f64)> {
         let denom = self.normal.0.dot(&ray.direction.0);
         // Ray is parallel to the plane — no hit.
         if denom.abs() < 1e-8 {
@@ -78,11 +79,20 @@ impl Quad {
             return None;
         }
 
+        Some((t, alpha, beta))
+    }
+
+    pub fn occluded(&self, ray: &Ray, min_value: f64, max_value: f64) -> bool {
+        self.intersect(ray, min_value, max_value).is_some()
+    }
+
+    pub fn is_hit_by(&self, ray: &Ray, min_value: f64, max_value: f64) -> Option<HitRecord<'_>> {
+        let (t, alpha, beta) = self.intersect(ray, min_value, max_value)?;
         Some(HitRecord::new(
             t,
             ray,
             self.normal,
-            self.material.clone(),
+            &self.material,
             (alpha, beta),
         ))
     }
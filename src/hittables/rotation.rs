@@ -1,8 +1,8 @@
 use crate::data::point64::Point64;
 use crate::data::ray::Ray;
 use crate::hittables::axis_aligned_bounding_box::AxisAlignedBoundingBox;
-use crate::hittables::{HitRecord, Hittable};
-use nalgebra::{Rotation3, Vector3};
+use crate::hittables::{HitInterval, HitRecord, Hittable};
+use nalgebra::{Rotation3, Unit, Vector3};
 
 #[derive(Clone)]
 pub enum AxisAlignment {
@@ -11,6 +11,16 @@ pub enum AxisAlignment {
     Z,
 }
 
+impl AxisAlignment {
+    pub fn unit_vector(&self) -> Unit<Vector3<f64>> {
+        match self {
+            AxisAlignment::X => Vector3::x_axis(),
+            AxisAlignment::Y => Vector3::y_axis(),
+            AxisAlignment::Z => Vector3::z_axis(),
+        }
+    }
+}
+
 /// Rotates child geometry about an axis through the origin (right-handed, angle in radians).
 #[derive(Clone)]
 pub struct Rotation {
@@ -28,12 +38,7 @@ impl Rotation {
         time0: f64,
         time1: f64,
     ) -> Self {
-        let axis = match axis_alignment {
-            AxisAlignment::X => Vector3::x_axis(),
-            AxisAlignment::Y => Vector3::y_axis(),
-            AxisAlignment::Z => Vector3::z_axis(),
-        };
-        let rot = Rotation3::from_axis_angle(&axis, angle_radians);
+        let rot = Rotation3::from_axis_angle(&axis_alignment.unit_vector(), angle_radians);
         let inv_rot = rot.inverse();
         let bounding_box = hittable
             .bounding_box(time0, time1)
@@ -51,25 +56,48 @@ impl Rotation {
         self.bounding_box
     }
 
-    pub fn is_hit_by(&self, ray: &Ray, min_value: f64, max_value: f64) -> Option<HitRecord> {
-        let origin = Point64(self.inv_rot * ray.origin.0);
-        let direction = Point64(self.inv_rot * ray.direction.0);
-        let local_ray = Ray {
-            origin,
-            direction,
+    fn local_ray(&self, ray: &Ray) -> Ray {
+        Ray {
+            origin: Point64(self.inv_rot * ray.origin.0),
+            direction: Point64(self.inv_rot * ray.direction.0),
             exposure_time: ray.exposure_time,
-        };
+        }
+    }
 
-        let hr = self.hittable.is_hit_by(&local_ray, min_value, max_value)?;
-        let world_normal = (self.rot * hr.normal.0).normalize();
+    /// Re-expresses a hit on the local ray in world space.
+    fn to_world<'a>(&self, ray: &Ray, hr: HitRecord<'a>) -> HitRecord<'a> {
+        let outward_normal = if hr.front_face { hr.normal } else { -hr.normal };
+        let world_normal = (self.rot * outward_normal.0).normalize();
 
-        Some(HitRecord::new(
+        HitRecord::new(
             hr.value,
             ray,
             Point64(world_normal),
             hr.material,
             (hr.u, hr.v),
-        ))
+        )
+    }
+
+    pub fn is_hit_by(&self, ray: &Ray, min_value: f64, max_value: f64) -> Option<HitRecord<'_>> {
+        self.hittable
+            .is_hit_by(&self.local_ray(ray), min_value, max_value)
+            .map(|hr| self.to_world(ray, hr))
+    }
+
+    pub fn occluded(&self, ray: &Ray, min_value: f64, max_value: f64) -> bool {
+        self.hittable
+            .occluded(&self.local_ray(ray), min_value, max_value)
+    }
+
+    pub fn hit_intervals(&self, ray: &Ray) -> Vec<HitInterval<'_>> {
+        self.hittable
+            .hit_intervals(&self.local_ray(ray))
+            .into_iter()
+            .map(|interval| HitInterval {
+                enter: self.to_world(ray, interval.enter),
+                exit: self.to_world(ray, interval.exit),
+            })
+            .collect()
     }
 }
 
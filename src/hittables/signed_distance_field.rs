@@ -0,0 +1,358 @@
+use crate::data::point64::Point64;
+use crate::data::ray::Ray;
+use crate::hittables::HitRecord;
+use crate::hittables::axis_aligned_bounding_box::AxisAlignedBoundingBox;
+use crate::hittables::sphere::get_sphere_uv;
+use crate::materials::Material;
+use crate::textures::perlin::PerlinGenerator;
+use nalgebra::Vector3;
+
+/// A signed distance function: negative inside the surface, positive outside, and (ideally)
+/// never larger in magnitude than the true distance to the surface.
+#[allow(dead_code)]
+#[derive(Clone)]
+pub enum Sdf {
+    Sphere {
+        radius: f64,
+    },
+    Box {
+        half_extents: Vector3<f64>,
+    },
+    /// Ring torus around the Y axis.
+    Torus {
+        major_radius: f64,
+        minor_radius: f64,
+    },
+    /// Distance estimate for the power-`power` Mandelbulb, roughly filling the unit sphere.
+    Mandelbulb {
+        power: f64,
+        iterations: u32,
+    },
+    Translate {
+        offset: Vector3<f64>,
+        sdf: Box<Sdf>,
+    },
+    Scale {
+        factor: f64,
+        sdf: Box<Sdf>,
+    },
+    /// Grows the surface outwards by `radius`, rounding off edges and corners.
+    Round {
+        radius: f64,
+        sdf: Box<Sdf>,
+    },
+    Union(Box<Sdf>, Box<Sdf>),
+    Intersection(Box<Sdf>, Box<Sdf>),
+    /// The first shape with the second carved out of it.
+    Subtraction(Box<Sdf>, Box<Sdf>),
+    /// Union that blends the two shapes together over a distance of about `k`.
+    SmoothUnion {
+        a: Box<Sdf>,
+        b: Box<Sdf>,
+        k: f64,
+    },
+    /// Infinite repetition on a lattice; an axis with a period of zero is not repeated.
+    Repeat {
+        period: Vector3<f64>,
+        sdf: Box<Sdf>,
+    },
+    /// Twists space around the Y axis by `rate` radians per unit of height.
+    Twist {
+        rate: f64,
+        sdf: Box<Sdf>,
+    },
+    /// Offsets the surface by Perlin noise. `scale` is the noise frequency, `amplitude` its
+    /// height.
+    Displace {
+        noise: Box<PerlinGenerator>,
+        scale: f64,
+        amplitude: f64,
+        sdf: Box<Sdf>,
+    },
+}
+
+impl Sdf {
+    pub fn distance(&self, p: &Vector3<f64>) -> f64 {
+        match self {
+            Sdf::Sphere { radius } => p.magnitude() - radius,
+            Sdf::Box { half_extents } => {
+                let q = p.abs() - half_extents;
+                q.sup(&Vector3::zeros()).magnitude() + q.max().min(0.)
+            }
+            Sdf::Torus {
+                major_radius,
+                minor_radius,
+            } => {
+                let radial = (p.x * p.x + p.z * p.z).sqrt() - major_radius;
+                (radial * radial + p.y * p.y).sqrt() - minor_radius
+            }
+            Sdf::Mandelbulb { power, iterations } => mandelbulb(p, *power, *iterations),
+            Sdf::Translate { offset, sdf } => sdf.distance(&(p - offset)),
+            Sdf::Scale { factor, sdf } => sdf.distance(&(p / *factor)) * factor,
+            Sdf::Round { radius, sdf } => sdf.distance(p) - radius,
+            Sdf::Union(a, b) => a.distance(p).min(b.distance(p)),
+            Sdf::Intersection(a, b) => a.distance(p).max(b.distance(p)),
+            Sdf::Subtraction(a, b) => a.distance(p).max(-b.distance(p)),
+            Sdf::SmoothUnion { a, b, k } => {
+                let da = a.distance(p);
+                let db = b.distance(p);
+                let h = (0.5 + 0.5 * (db - da) / k).clamp(0., 1.);
+                db + (da - db) * h - k * h * (1. - h)
+            }
+            Sdf::Repeat { period, sdf } => {
+                let q = Vector3::from_fn(|i, _| {
+                    if period[i] > 0. {
+                        p[i] - period[i] * (p[i] / period[i]).round()
+                    } else {
+                        p[i]
+                    }
+                });
+                sdf.distance(&q)
+            }
+            Sdf::Twist { rate, sdf } => {
+                let (s, c) = (rate * p.y).sin_cos();
+                sdf.distance(&Vector3::new(c * p.x - s * p.z, p.y, s * p.x + c * p.z))
+            }
+            Sdf::Displace {
+                noise,
+                scale,
+                amplitude,
+                sdf,
+            } => sdf.distance(p) + amplitude * noise.noise(&Point64(p * *scale)),
+        }
+    }
+}
+
+fn mandelbulb(p: &Vector3<f64>, power: f64, iterations: u32) -> f64 {
+    let mut z = *p;
+    let mut dr = 1.;
+    let mut r = z.magnitude();
+
+    for _ in 0..iterations {
+        if r > 2. || r == 0. {
+            break;
+        }
+
+        let theta = (z.z / r).acos() * power;
+        let phi = z.y.atan2(z.x) * power;
+        dr = r.powf(power - 1.) * power * dr + 1.;
+
+        let zr = r.powf(power);
+        z =
+            zr * Vector3::new(
+                theta.sin() * phi.cos(),
+                phi.sin() * theta.sin(),
+                theta.cos(),
+            ) + p;
+        r = z.magnitude();
+    }
+
+    if r == 0. {
+        return 0.;
+    }
+    0.5 * r.ln() * r / dr
+}
+
+const DEFAULT_MAX_STEPS: u32 = 256;
+const DEFAULT_HIT_EPSILON: f64 = 1e-4;
+
+/// Surface rendered by sphere tracing an [`Sdf`] inside a user-supplied bounding box.
+#[derive(Clone)]
+pub struct SignedDistanceField {
+    sdf: Sdf,
+    bounding_box: AxisAlignedBoundingBox,
+    material: Material,
+    /// Fraction of the distance estimate taken per step. Operators that stretch space
+    /// (twist, displacement) over-estimate distances and need a value below 1.
+    step_scale: f64,
+    max_steps: u32,
+    hit_epsilon: f64,
+}
+
+impl SignedDistanceField {
+    pub fn new(sdf: Sdf, bounding_box: AxisAlignedBoundingBox, material: Material) -> Self {
+        Self {
+            sdf,
+            bounding_box,
+            material,
+            step_scale: 1.,
+            max_steps: DEFAULT_MAX_STEPS,
+            hit_epsilon: DEFAULT_HIT_EPSILON,
+        }
+    }
+
+    pub fn with_step_scale(mut self, step_scale: f64) -> Self {
+        self.step_scale = step_scale;
+        self
+    }
+
+    pub fn with_max_steps(mut self, max_steps: u32) -> Self {
+        self.max_steps = max_steps;
+        self
+    }
+
+    pub fn bounding_box(&self, _time0: f64, _time1: f64) -> Option<AxisAlignedBoundingBox> {
+        Some(self.bounding_box)
+    }
+
+    /// Outward normal from the gradient of the distance field (tetrahedral central differences).
+    fn gradient_normal(&self, p: &Vector3<f64>) -> Vector3<f64> {
+        let h = self.hit_epsilon;
+        let offsets = [
+            Vector3::new(1., -1., -1.),
+            Vector3::new(-1., -1., 1.),
+            Vector3::new(-1., 1., -1.),
+            Vector3::new(1., 1., 1.),
+        ];
+
+        let gradient = offsets.iter().fold(Vector3::zeros(), |acc, k| {
+            acc + k * self.sdf.distance(&(p + k * h))
+        });
+
+        if gradient.magnitude() > 0. {
+            gradient.normalize()
+        } else {
+            Vector3::new(0., 1., 0.)
+        }
+    }
+
+    pub fn is_hit_by(&self, ray: &Ray, min_value: f64, max_value: f64) -> Option<HitRecord<'_>> {
+        let (t_enter, t_exit) = self.bounding_box.hit_interval(ray, min_value, max_value)?;
+        let ray_length = ray.direction.0.magnitude();
+        let direction = ray.direction.0 / ray_length;
+
+        let distance_at = |t: f64| self.sdf.distance(&ray.point_at_parameter(t).0);
+
+        // March on whichever side of the surface the ray starts. A ray leaving a surface it
+        // just hit starts within epsilon of it, so take the side from the direction of travel
+        // and ignore the surface until the ray has moved clear of it.
+        let start = ray.point_at_parameter(t_enter).0;
+        let d0 = self.sdf.distance(&start);
+        let side = if d0.abs() > self.hit_epsilon {
+            d0.signum()
+        } else if self.gradient_normal(&start).dot(&direction) >= 0. {
+            1.
+        } else {
+            -1.
+        };
+        let mut clear_of_surface = d0.abs() > self.hit_epsilon;
+
+        let mut t = t_enter;
+        let mut previous_t = t;
+
+        for _ in 0..self.max_steps {
+            let d = side * distance_at(t);
+
+            if d < 0. && clear_of_surface {
+                // Overshot: bisect back to the crossing.
+                let (mut lo, mut hi) = (previous_t, t);
+                for _ in 0..32 {
+                    let mid = 0.5 * (lo + hi);
+                    if side * distance_at(mid) > 0. {
+                        lo = mid;
+                    } else {
+                        hi = mid;
+                    }
+                }
+                t = hi;
+                break;
+            }
+
+            if d < self.hit_epsilon && clear_of_surface {
+                break;
+            }
+
+            if d > self.hit_epsilon {
+                clear_of_surface = true;
+            }
+
+            previous_t = t;
+            t += d.abs().max(self.hit_epsilon) * self.step_scale / ray_length;
+            if t > t_exit {
+                return None;
+            }
+        }
+
+        if !(min_value < t && t < max_value) || side * distance_at(t) > 10. * self.hit_epsilon {
+            return None;
+        }
+
+        let location = ray.point_at_parameter(t);
+        let outward_normal = Point64(self.gradient_normal(&location.0));
+        let uv_direction =
+            location.0 - 0.5 * (self.bounding_box.minimum.0 + self.bounding_box.maximum.0);
+        let uv = if uv_direction.magnitude() > 0. {
+            get_sphere_uv(Point64(uv_direction.normalize()))
+        } else {
+            (0., 0.)
+        };
+
+        Some(HitRecord::new(t, ray, outward_normal, &self.material, uv))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Sdf, SignedDistanceField};
+    use crate::data::point64::Point64;
+    use crate::data::ray::Ray;
+    use crate::hittables::axis_aligned_bounding_box::AxisAlignedBoundingBox;
+    use crate::materials::Material;
+    use crate::materials::dielectric::Dielectric;
+    use approx::assert_abs_diff_eq;
+    use nalgebra::Vector3;
+
+    fn unit_sphere_field() -> SignedDistanceField {
+        SignedDistanceField::new(
+            Sdf::Sphere { radius: 1. },
+            AxisAlignedBoundingBox {
+                minimum: Point64::new(-1.1, -1.1, -1.1),
+                maximum: Point64::new(1.1, 1.1, 1.1),
+            },
+            Material::Dielectric(Dielectric::new(1.5)),
+        )
+    }
+
+    #[test]
+    fn sphere_traced_sphere_matches_analytic() {
+        let field = unit_sphere_field();
+        let ray = Ray {
+            origin: Point64::new(0.3, 0.2, -5.),
+            direction: Point64::new(0., 0., 2.),
+            exposure_time: 0.,
+        };
+
+        let hr = field.is_hit_by(&ray, 0.001, f64::INFINITY).unwrap();
+        let expected_z = -(1. - 0.3_f64.powi(2) - 0.2_f64.powi(2)).sqrt();
+        assert_abs_diff_eq!(hr.location.z(), expected_z, epsilon = 1e-3);
+        assert!(hr.front_face);
+        assert_abs_diff_eq!(hr.normal.0, hr.location.0.normalize(), epsilon = 1e-3);
+
+        // Continuing from the entry point finds the far side, seen from inside.
+        let exit = field
+            .is_hit_by(&ray, hr.value + 0.001, f64::INFINITY)
+            .unwrap();
+        assert_abs_diff_eq!(exit.location.z(), -expected_z, epsilon = 1e-3);
+        assert!(!exit.front_face);
+    }
+
+    #[test]
+    fn smooth_union_bridges_gap() {
+        let sphere_at = |x: f64| Sdf::Translate {
+            offset: Vector3::new(x, 0., 0.),
+            sdf: Box::new(Sdf::Sphere { radius: 1. }),
+        };
+        let blend = Sdf::SmoothUnion {
+            a: Box::new(sphere_at(-1.1)),
+            b: Box::new(sphere_at(1.1)),
+            k: 0.5,
+        };
+
+        // The plain union leaves a gap at the origin; the smooth union fills it.
+        let origin = Vector3::zeros();
+        assert!(
+            Sdf::Union(Box::new(sphere_at(-1.1)), Box::new(sphere_at(1.1))).distance(&origin) > 0.
+        );
+        assert!(blend.distance(&origin) < 0.);
+    }
+}
@@ -1,7 +1,7 @@
 use crate::data::point64::Point64;
 use crate::data::ray::Ray;
-use crate::hittables::HitRecord;
 use crate::hittables::axis_aligned_bounding_box::AxisAlignedBoundingBox;
+use crate::hittables::{HitInterval, HitRecord};
 use crate::materials::Material;
 use nalgebra::Vector3;
 use std::f64::consts::{PI, TAU};
@@ -28,40 +28,62 @@ impl Sphere {
         })
     }
 
-    pub fn is_hit_by(&self, ray: &Ray, min_value: f64, max_value: f64) -> Option<HitRecord> {
+    /// Ray parameters where the ray enters and leaves the sphere, if it crosses it at all.
+    fn roots(&self, ray: &Ray) -> Option<(f64, f64)> {
         let ray_origin_to_center: Point64 = ray.origin - self.center;
         let a = ray.direction.0.dot(&ray.direction.0);
         let half_b = ray_origin_to_center.0.dot(&ray.direction.0);
         let c = ray_origin_to_center.0.dot(&ray_origin_to_center.0) - self.radius.powi(2);
         let discriminant = half_b.powi(2) - a * c;
+
+        if discriminant < 0. {
+            return None;
+        }
+
         let sqrt_discriminant = discriminant.sqrt();
-        let root_one = (-half_b - sqrt_discriminant) / a;
-        let root_two = (-half_b + sqrt_discriminant) / a;
+        Some((
+            (-half_b - sqrt_discriminant) / a,
+            (-half_b + sqrt_discriminant) / a,
+        ))
+    }
 
-        if discriminant >= 0. {
-            let root_one_in_range = min_value < root_one && root_one < max_value;
-            let root_two_in_range = min_value < root_two && root_two < max_value;
+    fn hit_record_at(&self, ray: &Ray, root: f64) -> HitRecord<'_> {
+        let location = ray.point_at_parameter(root);
+        let outward_normal = (location - self.center) / self.radius;
 
-            if root_one_in_range || root_two_in_range {
-                let root = if root_one_in_range {
-                    root_one
-                } else {
-                    root_two
-                };
+        HitRecord::new(
+            root,
+            ray,
+            outward_normal,
+            &self.material,
+            get_sphere_uv(outward_normal),
+        )
+    }
 
-                let location = ray.point_at_parameter(root);
-                let outward_normal = (location - self.center) / self.radius;
+    /// The nearer of the ray's crossings that lies strictly within `(min_value, max_value)`.
+    fn nearest_root(&self, ray: &Ray, min_value: f64, max_value: f64) -> Option<f64> {
+        let (root_one, root_two) = self.roots(ray)?;
+        [root_one, root_two]
+            .into_iter()
+            .find(|root| min_value < *root && *root < max_value)
+    }
 
-                return Some(HitRecord::new(
-                    root,
-                    ray,
-                    outward_normal,
-                    self.material.clone(),
-                    get_sphere_uv(outward_normal),
-                ));
-            }
-        }
+    pub fn occluded(&self, ray: &Ray, min_value: f64, max_value: f64) -> bool {
+        self.nearest_root(ray, min_value, max_value).is_some()
+    }
 
-        None
+    pub fn is_hit_by(&self, ray: &Ray, min_value: f64, max_value: f64) -> Option<HitRecord<'_>> {
+        self.nearest_root(ray, min_value, max_value)
+            .map(|root| self.hit_record_at(ray, root))
+    }
+
+    pub fn hit_intervals(&self, ray: &Ray) -> Vec<HitInterval<'_>> {
+        match self.roots(ray) {
+            Some((enter, exit)) if enter < exit => vec![HitInterval {
+                enter: self.hit_record_at(ray, enter),
+                exit: self.hit_record_at(ray, exit),
+            }],
+            _ => vec![],
+        }
     }
 }
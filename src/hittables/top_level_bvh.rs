@@ -0,0 +1,432 @@
+use crate::data::point64::Point64;
+use crate::data::ray::Ray;
+use crate::hittables::axis_aligned_bounding_box::AxisAlignedBoundingBox;
+use crate::hittables::{HitRecord, Hittable};
+use crate::materials::Material;
+use nalgebra::{Isometry3, Point3, Vector3};
+use std::sync::Arc;
+
+/// Deepest tree [`TopLevelBvh`] can traverse; its stack lives on the call stack.
+const MAX_DEPTH: usize = 64;
+
+/// A shared, bounded hittable (usually a bottom-level BVH) placed in the world by a rigid
+/// transform. Moving an instance only changes its transform, never the shared geometry.
+#[derive(Clone)]
+pub struct Instance {
+    blas: Arc<Hittable>,
+    /// Replaces the materials of `blas`, so one geometry can appear in several materials.
+    material: Option<Arc<Material>>,
+    /// Bounds of `blas` in its own space.
+    local_bounds: AxisAlignedBoundingBox,
+    transform: Isometry3<f64>,
+    inverse: Isometry3<f64>,
+    bounds: AxisAlignedBoundingBox,
+}
+
+impl Instance {
+    pub fn new(blas: Arc<Hittable>, transform: Isometry3<f64>) -> Self {
+        let local_bounds = blas
+            .bounding_box(0., 1.)
+            .expect("instanced hittables need a bounding box");
+
+        Self {
+            blas,
+            material: None,
+            local_bounds,
+            transform,
+            inverse: transform.inverse(),
+            bounds: transform_aabb(&local_bounds, &transform),
+        }
+    }
+
+    /// Renders the instance in `material` instead of the materials of its geometry.
+    pub fn with_material(mut self, material: Material) -> Self {
+        self.material = Some(Arc::new(material));
+        self
+    }
+
+    pub fn transform(&self) -> &Isometry3<f64> {
+        &self.transform
+    }
+
+    pub fn bounding_box(&self) -> AxisAlignedBoundingBox {
+        self.bounds
+    }
+
+    fn set_transform(&mut self, transform: Isometry3<f64>) {
+        self.transform = transform;
+        self.inverse = transform.inverse();
+        self.bounds = transform_aabb(&self.local_bounds, &transform);
+    }
+
+    /// `ray` in the instance's own space. Isometries keep lengths, so hit distances carry
+    /// over unchanged.
+    fn local_ray(&self, ray: &Ray) -> Ray {
+        Ray {
+            origin: Point64((self.inverse * Point3::from(ray.origin.0)).coords),
+            direction: Point64(self.inverse * ray.direction.0),
+            exposure_time: ray.exposure_time,
+        }
+    }
+
+    pub fn is_hit_by(&self, ray: &Ray, min_value: f64, max_value: f64) -> Option<HitRecord<'_>> {
+        self.blas
+            .is_hit_by(&self.local_ray(ray), min_value, max_value)
+            .map(|hr| {
+                let outward_normal = if hr.front_face { hr.normal } else { -hr.normal };
+                HitRecord::new(
+                    hr.value,
+                    ray,
+                    Point64(self.transform.rotation * outward_normal.0),
+                    self.material.as_deref().unwrap_or(hr.material),
+                    (hr.u, hr.v),
+                )
+            })
+    }
+
+    pub fn occluded(&self, ray: &Ray, min_value: f64, max_value: f64) -> bool {
+        self.blas
+            .occluded(&self.local_ray(ray), min_value, max_value)
+    }
+}
+
+fn transform_aabb(
+    bounds: &AxisAlignedBoundingBox,
+    transform: &Isometry3<f64>,
+) -> AxisAlignedBoundingBox {
+    let corners = (0..8).map(|i: u8| {
+        let corner = Vector3::from_fn(|axis, _| {
+            if i & (1 << axis) == 0 {
+                bounds.minimum.0[axis]
+            } else {
+                bounds.maximum.0[axis]
+            }
+        });
+        (transform * Point3::from(corner)).coords
+    });
+
+    let (minimum, maximum) = corners.fold(
+        (
+            Vector3::repeat(f64::INFINITY),
+            Vector3::repeat(f64::NEG_INFINITY),
+        ),
+        |(minimum, maximum), corner| (minimum.inf(&corner), maximum.sup(&corner)),
+    );
+    AxisAlignedBoundingBox {
+        minimum: Point64(minimum),
+        maximum: Point64(maximum),
+    }
+}
+
+/// A BVH over [`Instance`]s, for scenes whose objects move between frames.
+///
+/// The bottom-level hierarchies inside the instances are built once. When instances move,
+/// [`refit`](Self::refit) recomputes node bounds in place, keeping the tree's shape; that is
+/// much cheaper than a rebuild but the tree gets looser as objects drift from where it was
+/// built.
+#[derive(Clone)]
+pub struct TopLevelBvh {
+    instances: Vec<Instance>,
+    nodes: Vec<TopLevelNode>,
+}
+
+#[derive(Clone, Copy)]
+struct TopLevelNode {
+    bounds: AxisAlignedBoundingBox,
+    /// For leaves, the index of the instance. For interior nodes, the index of the second
+    /// child; the first child immediately follows its parent.
+    offset: u32,
+    is_leaf: bool,
+}
+
+impl TopLevelBvh {
+    pub fn new(instances: Vec<Instance>) -> Self {
+        assert!(
+            !instances.is_empty(),
+            "empty list of instances passed to TopLevelBvh::new"
+        );
+
+        let mut tlas = Self {
+            nodes: Vec::with_capacity(2 * instances.len() - 1),
+            instances,
+        };
+        let mut order: Vec<usize> = (0..tlas.instances.len()).collect();
+        tlas.build(&mut order, 0);
+        tlas
+    }
+
+    /// Appends the subtree over `order`, split at the median centroid along the axis where
+    /// the centroids spread furthest.
+    fn build(&mut self, order: &mut [usize], depth: usize) {
+        assert!(
+            depth < MAX_DEPTH,
+            "TopLevelBvh is deeper than {MAX_DEPTH} levels"
+        );
+        let index = self.nodes.len();
+        let bounds = order
+            .iter()
+            .map(|&i| self.instances[i].bounds)
+            .reduce(|acc, bounds| acc.surrounding_box_with(&bounds))
+            .unwrap();
+
+        if let [instance] = order {
+            self.nodes.push(TopLevelNode {
+                bounds,
+                offset: *instance as u32,
+                is_leaf: true,
+            });
+            return;
+        }
+
+        let centroids: Vec<Vector3<f64>> = order
+            .iter()
+            .map(|&i| self.instances[i].bounds.centroid().0)
+            .collect();
+        let lowest = centroids
+            .iter()
+            .fold(Vector3::repeat(f64::INFINITY), |a, c| a.inf(c));
+        let highest = centroids
+            .iter()
+            .fold(Vector3::repeat(f64::NEG_INFINITY), |a, c| a.sup(c));
+        let axis = (highest - lowest).imax();
+        order.sort_by(|&a, &b| {
+            let ca = self.instances[a].bounds.centroid().0[axis];
+            let cb = self.instances[b].bounds.centroid().0[axis];
+            ca.total_cmp(&cb)
+        });
+
+        self.nodes.push(TopLevelNode {
+            bounds,
+            offset: 0,
+            is_leaf: false,
+        });
+        let (left, right) = order.split_at_mut(order.len() / 2);
+        self.build(left, depth + 1);
+        self.nodes[index].offset = self.nodes.len() as u32;
+        self.build(right, depth + 1);
+    }
+
+    pub fn instances(&self) -> &[Instance] {
+        &self.instances
+    }
+
+    /// Moves one instance. Call [`refit`](Self::refit) once all instances for the frame have
+    /// been moved.
+    pub fn set_transform(&mut self, instance: usize, transform: Isometry3<f64>) {
+        self.instances[instance].set_transform(transform);
+    }
+
+    /// Recomputes every node's bounds from the instances' current positions.
+    pub fn refit(&mut self) {
+        // Children always follow their parent, so a reverse sweep sees them first.
+        for index in (0..self.nodes.len()).rev() {
+            let node = self.nodes[index];
+            self.nodes[index].bounds = if node.is_leaf {
+                self.instances[node.offset as usize].bounds
+            } else {
+                self.nodes[index + 1]
+                    .bounds
+                    .surrounding_box_with(&self.nodes[node.offset as usize].bounds)
+            };
+        }
+    }
+
+    pub fn bounding_box(&self, _time0: f64, _time1: f64) -> Option<AxisAlignedBoundingBox> {
+        Some(self.nodes[0].bounds)
+    }
+
+    pub fn is_hit_by(&self, ray: &Ray, min_value: f64, max_value: f64) -> Option<HitRecord<'_>> {
+        let inv_direction = Vector3::repeat(1.).component_div(&ray.direction.0);
+        let box_hit = |node: usize, max_value: f64| {
+            self.nodes[node].bounds.hit_interval_with_inverse(
+                &ray.origin,
+                &inv_direction,
+                min_value,
+                max_value,
+            )
+        };
+
+        box_hit(0, max_value)?;
+
+        let mut winner: Option<HitRecord> = None;
+        let mut closest = max_value;
+        let mut stack = [0usize; MAX_DEPTH];
+        let mut stack_len = 0;
+        let mut index = 0;
+
+        loop {
+            let node = self.nodes[index];
+            if node.is_leaf {
+                let instance = &self.instances[node.offset as usize];
+                if let Some(hr) = instance.is_hit_by(ray, min_value, closest) {
+                    closest = hr.value;
+                    winner = Some(hr);
+                }
+            } else {
+                let (first, second) = (index + 1, node.offset as usize);
+                match (box_hit(first, closest), box_hit(second, closest)) {
+                    (Some((first_enter, _)), Some((second_enter, _))) => {
+                        let (near, far) = if first_enter <= second_enter {
+                            (first, second)
+                        } else {
+                            (second, first)
+                        };
+                        stack[stack_len] = far;
+                        stack_len += 1;
+                        index = near;
+                        continue;
+                    }
+                    (Some(_), None) => {
+                        index = first;
+                        continue;
+                    }
+                    (None, Some(_)) => {
+                        index = second;
+                        continue;
+                    }
+                    (None, None) => {}
+                }
+            }
+
+            // Resume with the nearest deferred node that can still beat the closest hit.
+            loop {
+                if stack_len == 0 {
+                    return winner;
+                }
+                stack_len -= 1;
+                index = stack[stack_len];
+                if box_hit(index, closest).is_some() {
+                    break;
+                }
+            }
+        }
+    }
+
+    pub fn occluded(&self, ray: &Ray, min_value: f64, max_value: f64) -> bool {
+        let inv_direction = Vector3::repeat(1.).component_div(&ray.direction.0);
+        let mut stack = [0usize; MAX_DEPTH];
+        let mut stack_len = 0;
+        let mut index = 0;
+
+        loop {
+            let node = self.nodes[index];
+            let hit = node
+                .bounds
+                .hit_interval_with_inverse(&ray.origin, &inv_direction, min_value, max_value)
+                .is_some();
+
+            if hit && node.is_leaf {
+                if self.instances[node.offset as usize].occluded(ray, min_value, max_value) {
+                    return true;
+                }
+            } else if hit {
+                stack[stack_len] = node.offset as usize;
+                stack_len += 1;
+                index += 1;
+                continue;
+            }
+
+            if stack_len == 0 {
+                return false;
+            }
+            stack_len -= 1;
+            index = stack[stack_len];
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Instance, TopLevelBvh};
+    use crate::data::color64::Color64;
+    use crate::data::point64::Point64;
+    use crate::data::ray::Ray;
+    use crate::hittables::Hittable;
+    use crate::hittables::cuboid::Cuboid;
+    use crate::materials::Material;
+    use crate::materials::dielectric::Dielectric;
+    use crate::materials::metal::Metal;
+    use approx::assert_abs_diff_eq;
+    use nalgebra::{Isometry3, Vector3};
+    use std::f64::consts::FRAC_PI_2;
+    use std::sync::Arc;
+
+    fn unit_box() -> Arc<Hittable> {
+        Arc::new(Hittable::Cuboid(Cuboid::new(
+            Point64::new(-0.5, -0.5, -0.5),
+            Point64::new(0.5, 0.5, 0.5),
+            Material::Dielectric(Dielectric::new(1.5)),
+        )))
+    }
+
+    fn down_at(x: f64, z: f64) -> Ray {
+        Ray {
+            origin: Point64::new(x, 10., z),
+            direction: Point64::new(0., -1., 0.),
+            exposure_time: 0.,
+        }
+    }
+
+    #[test]
+    fn refit_follows_moved_instances() {
+        let blas = unit_box();
+        let mut tlas = TopLevelBvh::new(
+            (0..5)
+                .map(|i| Instance::new(blas.clone(), Isometry3::translation(3. * i as f64, 0., 0.)))
+                .collect(),
+        );
+
+        let hr = tlas
+            .is_hit_by(&down_at(6., 0.), 0.001, f64::INFINITY)
+            .unwrap();
+        assert_abs_diff_eq!(hr.value, 9.5);
+        assert!(
+            tlas.is_hit_by(&down_at(6., 5.), 0.001, f64::INFINITY)
+                .is_none()
+        );
+
+        // Lift the middle box, lay it on its side, and move it out along z.
+        let moved = Isometry3::new(Vector3::new(6., 2., 5.), Vector3::x() * FRAC_PI_2);
+        tlas.set_transform(2, moved);
+        tlas.refit();
+
+        assert!(
+            tlas.is_hit_by(&down_at(6., 0.), 0.001, f64::INFINITY)
+                .is_none()
+        );
+        let hr = tlas
+            .is_hit_by(&down_at(6., 5.), 0.001, f64::INFINITY)
+            .unwrap();
+        assert_abs_diff_eq!(hr.value, 7.5, epsilon = 1e-12);
+        assert_abs_diff_eq!(hr.normal.y(), 1., epsilon = 1e-12);
+        assert_abs_diff_eq!(hr.location.z(), 5., epsilon = 1e-12);
+
+        let bounds = tlas.bounding_box(0., 1.).unwrap();
+        assert_abs_diff_eq!(bounds.maximum.y(), 2.5, epsilon = 1e-12);
+        assert_abs_diff_eq!(bounds.maximum.z(), 5.5, epsilon = 1e-12);
+        assert_eq!(Arc::strong_count(&blas), 6);
+    }
+
+    #[test]
+    fn instances_can_override_the_material() {
+        let blas = unit_box();
+        let tlas = TopLevelBvh::new(vec![
+            Instance::new(blas.clone(), Isometry3::identity()),
+            Instance::new(blas, Isometry3::translation(3., 0., 0.)).with_material(Material::Metal(
+                Metal {
+                    albedo: Color64::gray(0.5),
+                    fuzz: 0.,
+                },
+            )),
+        ]);
+
+        let material_at = |x: f64| {
+            tlas.is_hit_by(&down_at(x, 0.), 0.001, f64::INFINITY)
+                .unwrap()
+                .material
+                .variant_index()
+        };
+        assert_eq!(material_at(0.), 1);
+        assert_eq!(material_at(3.), 4);
+    }
+}
@@ -0,0 +1,234 @@
+use crate::data::point64::Point64;
+use crate::data::polynomial::solve_quartic;
+use crate::data::ray::Ray;
+use crate::hittables::axis_aligned_bounding_box::AxisAlignedBoundingBox;
+use crate::hittables::{HitInterval, HitRecord, pair_intervals};
+use crate::materials::Material;
+use nalgebra::Vector3;
+use std::f64::consts::{PI, TAU};
+
+/// A ring torus centered on `center` with its axis of symmetry along +Y.
+/// `major_radius` is the distance from the center to the middle of the tube and
+/// `minor_radius` is the radius of the tube itself. Wrap in [`Rotation`](super::rotation::Rotation)
+/// for other orientations.
+#[derive(Clone)]
+pub struct Torus {
+    pub center: Point64,
+    pub major_radius: f64,
+    pub minor_radius: f64,
+    pub material: Material,
+}
+
+/// `u` is the angle around the main axis, `v` the angle around the tube (both in [0, 1]).
+fn get_torus_uv(p: &Vector3<f64>, major_radius: f64) -> (f64, f64) {
+    let phi = (-p.z).atan2(p.x) + PI;
+    let radial = (p.x * p.x + p.z * p.z).sqrt() - major_radius;
+    let theta = p.y.atan2(radial) + PI;
+
+    (phi / TAU, theta / TAU)
+}
+
+impl Torus {
+    pub fn bounding_box(&self, _time0: f64, _time1: f64) -> Option<AxisAlignedBoundingBox> {
+        let outer = self.major_radius + self.minor_radius;
+        let half_extent = Vector3::new(outer, self.minor_radius, outer);
+
+        Some(AxisAlignedBoundingBox {
+            minimum: Point64(self.center.0 - half_extent),
+            maximum: Point64(self.center.0 + half_extent),
+        })
+    }
+
+    /// Ray parameters of every intersection with the torus, in ascending order.
+    fn intersections(&self, ray: &Ray) -> Vec<f64> {
+        let ray_length = ray.direction.0.magnitude();
+        if ray_length == 0. {
+            return vec![];
+        }
+        let direction = ray.direction.0 / ray_length;
+        let origin = ray.origin.0 - self.center.0;
+
+        // Clip against the bounding sphere first and restart the ray at its near side. Keeping
+        // the origin close to the torus keeps the quartic's coefficients small, which is what
+        // makes distant and grazing rays stable.
+        let outer = self.major_radius + self.minor_radius;
+        let half_b = origin.dot(&direction);
+        let c = origin.dot(&origin) - outer * outer;
+        let discriminant = half_b * half_b - c;
+        if discriminant < 0. {
+            return vec![];
+        }
+        let start = -half_b - discriminant.sqrt();
+        let origin = origin + direction * start;
+
+        let major2 = self.major_radius * self.major_radius;
+        let minor2 = self.minor_radius * self.minor_radius;
+        let o_dot_d = origin.dot(&direction);
+        let k = origin.dot(&origin) + major2 - minor2;
+        let planar_dd = direction.x * direction.x + direction.z * direction.z;
+        let planar_od = origin.x * direction.x + origin.z * direction.z;
+        let planar_oo = origin.x * origin.x + origin.z * origin.z;
+
+        // (|p|² + R² - r²)² = 4R²(px² + pz²) with p = o + s·d and |d| = 1.
+        let roots = solve_quartic(
+            4. * o_dot_d,
+            4. * o_dot_d * o_dot_d + 2. * k - 4. * major2 * planar_dd,
+            4. * o_dot_d * k - 8. * major2 * planar_od,
+            k * k - 4. * major2 * planar_oo,
+        );
+
+        roots
+            .as_slice()
+            .iter()
+            .map(|s| (start + s) / ray_length)
+            .collect()
+    }
+
+    /// Builds the hit record for a ray parameter returned by [`Torus::intersections`].
+    fn hit_record_at(&self, ray: &Ray, t: f64) -> HitRecord<'_> {
+        let p = ray.point_at_parameter(t).0 - self.center.0;
+        let planar = Vector3::new(p.x, 0., p.z);
+        let tube_center = if planar.magnitude() > 0. {
+            planar.normalize() * self.major_radius
+        } else {
+            Vector3::new(self.major_radius, 0., 0.)
+        };
+        let outward_normal = Point64((p - tube_center).normalize());
+
+        HitRecord::new(
+            t,
+            ray,
+            outward_normal,
+            &self.material,
+            get_torus_uv(&p, self.major_radius),
+        )
+    }
+
+    pub fn is_hit_by(&self, ray: &Ray, min_value: f64, max_value: f64) -> Option<HitRecord<'_>> {
+        let t = self
+            .intersections(ray)
+            .into_iter()
+            .find(|t| min_value < *t && *t < max_value)?;
+
+        Some(self.hit_record_at(ray, t))
+    }
+
+    pub fn hit_intervals(&self, ray: &Ray) -> Vec<HitInterval<'_>> {
+        pair_intervals(
+            self.intersections(ray)
+                .into_iter()
+                .map(|t| self.hit_record_at(ray, t)),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Torus;
+    use crate::data::point64::Point64;
+    use crate::data::ray::Ray;
+    use crate::materials::Material;
+    use crate::materials::dielectric::Dielectric;
+    use approx::assert_abs_diff_eq;
+
+    fn unit_torus() -> Torus {
+        Torus {
+            center: Point64::new(0., 0., 0.),
+            major_radius: 1.,
+            minor_radius: 0.25,
+            material: Material::Dielectric(Dielectric::new(1.5)),
+        }
+    }
+
+    /// Distance from `p` to the tube's center circle, minus the tube radius.
+    fn surface_distance(torus: &Torus, p: Point64) -> f64 {
+        let planar = (p.x() * p.x() + p.z() * p.z()).sqrt() - torus.major_radius;
+        (planar * planar + p.y() * p.y()).sqrt() - torus.minor_radius
+    }
+
+    #[test]
+    fn ray_through_hole_misses() {
+        let r = Ray {
+            origin: Point64::new(0., 10., 0.),
+            direction: Point64::new(0., -1., 0.),
+            exposure_time: 0.,
+        };
+        assert!(unit_torus().is_hit_by(&r, 0.001, f64::INFINITY).is_none());
+    }
+
+    #[test]
+    fn ray_along_equator_hits_four_times() {
+        let torus = unit_torus();
+        let r = Ray {
+            origin: Point64::new(-10., 0., 0.),
+            direction: Point64::new(2., 0., 0.),
+            exposure_time: 0.,
+        };
+
+        let ts = torus.intersections(&r);
+        assert_eq!(ts.len(), 4);
+        for (t, x) in ts.iter().zip([-1.25, -0.75, 0.75, 1.25]) {
+            assert_abs_diff_eq!(*t, (x + 10.) / 2., epsilon = 1e-9);
+        }
+
+        let hr = torus.is_hit_by(&r, 0.001, f64::INFINITY).unwrap();
+        assert_abs_diff_eq!(hr.value, 4.375, epsilon = 1e-9);
+        assert!(hr.front_face);
+        assert_abs_diff_eq!(hr.normal.x(), -1., epsilon = 1e-9);
+
+        // Starting inside the tube, the first hit is the exit.
+        let hr = torus.is_hit_by(&r, 4.5, f64::INFINITY).unwrap();
+        assert_abs_diff_eq!(hr.value, 4.625, epsilon = 1e-9);
+        assert!(!hr.front_face);
+    }
+
+    #[test]
+    fn grazing_rays_land_on_surface() {
+        let torus = unit_torus();
+
+        // Rays skimming the top of the tube (y = r) from far away, at a range of heights just
+        // inside and outside the silhouette. Any reported hit must lie on the surface.
+        for i in -20..=20 {
+            let y = torus.minor_radius + i as f64 * 1e-7;
+            for &distance in &[3., 100., 10_000.] {
+                let r = Ray {
+                    origin: Point64::new(-distance, y, 0.),
+                    direction: Point64::new(1., 0., 0.),
+                    exposure_time: 0.,
+                };
+
+                match torus.is_hit_by(&r, 0.001, f64::INFINITY) {
+                    Some(hr) => {
+                        assert!(y <= torus.minor_radius + 1e-6);
+                        assert!(surface_distance(&torus, hr.location).abs() < 1e-6);
+                        assert_abs_diff_eq!(hr.normal.0.magnitude(), 1., epsilon = 1e-9);
+                    }
+                    None => assert!(y >= torus.minor_radius - 1e-6),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn grazing_outer_silhouette() {
+        let torus = unit_torus();
+        let outer = torus.major_radius + torus.minor_radius;
+
+        // Tangent to the outer equator at (0, 0, outer).
+        for i in -10..=10 {
+            let z = outer + i as f64 * 1e-8;
+            let r = Ray {
+                origin: Point64::new(-1000., 0., z),
+                direction: Point64::new(1., 0., 0.),
+                exposure_time: 0.,
+            };
+
+            if let Some(hr) = torus.is_hit_by(&r, 0.001, f64::INFINITY) {
+                assert!(surface_distance(&torus, hr.location).abs() < 1e-6);
+                assert_abs_diff_eq!(hr.location.x(), 0., epsilon = 1e-2);
+            } else {
+                assert!(z >= outer - 1e-6);
+            }
+        }
+    }
+}
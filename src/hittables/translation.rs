@@ -1,7 +1,7 @@
 use crate::data::point64::Point64;
 use crate::data::ray::Ray;
 use crate::hittables::axis_aligned_bounding_box::AxisAlignedBoundingBox;
-use crate::hittables::{HitRecord, Hittable};
+use crate::hittables::{HitInterval, HitRecord, Hittable};
 use nalgebra::Vector3;
 
 #[derive(Clone)]
@@ -24,24 +24,39 @@ impl Translation {
         }
     }
 
-    pub fn is_hit_by(&self, ray: &Ray, min_value: f64, max_value: f64) -> Option<HitRecord> {
-        let moved_ray = Ray {
+    fn moved_ray(&self, ray: &Ray) -> Ray {
+        Ray {
             origin: Point64(ray.origin.0 - self.offset),
             direction: ray.direction,
             exposure_time: ray.exposure_time,
-        };
-
-        let opt_hit_record = self.hittable.is_hit_by(&moved_ray, min_value, max_value);
-
-        match opt_hit_record {
-            Some(hr) => Some(HitRecord::new(
-                hr.value,
-                &moved_ray,
-                hr.normal,
-                hr.material,
-                (hr.u, hr.v),
-            )),
-            None => None,
         }
     }
+
+    /// Re-expresses a hit on the moved ray in world space.
+    fn to_world<'a>(ray: &Ray, hr: HitRecord<'a>) -> HitRecord<'a> {
+        let outward_normal = if hr.front_face { hr.normal } else { -hr.normal };
+        HitRecord::new(hr.value, ray, outward_normal, hr.material, (hr.u, hr.v))
+    }
+
+    pub fn is_hit_by(&self, ray: &Ray, min_value: f64, max_value: f64) -> Option<HitRecord<'_>> {
+        self.hittable
+            .is_hit_by(&self.moved_ray(ray), min_value, max_value)
+            .map(|hr| Self::to_world(ray, hr))
+    }
+
+    pub fn occluded(&self, ray: &Ray, min_value: f64, max_value: f64) -> bool {
+        self.hittable
+            .occluded(&self.moved_ray(ray), min_value, max_value)
+    }
+
+    pub fn hit_intervals(&self, ray: &Ray) -> Vec<HitInterval<'_>> {
+        self.hittable
+            .hit_intervals(&self.moved_ray(ray))
+            .into_iter()
+            .map(|interval| HitInterval {
+                enter: Self::to_world(ray, interval.enter),
+                exit: Self::to_world(ray, interval.exit),
+            })
+            .collect()
+    }
 }
@@ -1,84 +1,172 @@
 use super::{HitRecord, axis_aligned_bounding_box::AxisAlignedBoundingBox};
 use crate::{
-    data::{point64::Point64, ray::Ray, vector3::Vector},
+    data::{
+        point64::Point64,
+        ray::Ray,
+        storage::{Real, StoredVector, widen},
+        vector3::Vector,
+    },
     materials::Material,
-    util::EPSILON,
 };
+use std::sync::Arc;
 
 #[derive(Clone)]
 pub struct Triangle {
-    p1: Point64,
-    p2: Point64,
-    p3: Point64,
-    e1: Vector,
-    e2: Vector,
-    normal: Vector,
-    pub material: Material,
+    /// Stored in [`Real`] precision, like the texture coordinates and normals.
+    vertices: [StoredVector; 3],
+    normal: StoredVector,
+    /// Texture coordinates at each vertex; without them, hits report the barycentric
+    /// weights of the second and third vertices as `(u, v)`.
+    texcoords: Option<[[Real; 2]; 3]>,
+    /// Shading normals at each vertex, interpolated across the face.
+    vertex_normals: Option<[StoredVector; 3]>,
+    /// Shared by all the triangles of a mesh.
+    pub material: Arc<Material>,
 }
 
 impl Triangle {
     #[allow(dead_code)]
-    pub fn new(p1: Point64, p2: Point64, p3: Point64, material: Material) -> Self {
-        let e1 = p2.0 - p1.0;
-        let e2 = p3.0 - p1.0;
-        let normal = e2.cross(&e1).normalize();
+    pub fn new(p1: Point64, p2: Point64, p3: Point64, material: Arc<Material>) -> Self {
+        let vertices = [p1, p2, p3].map(|p| StoredVector::new(&p.0));
+        // Face the normal of the triangle as stored, which may differ slightly from the one
+        // given when storing in single precision.
+        let [p1, p2, p3] = vertices.map(|p| p.load());
+        // Counter-clockwise vertices face the viewer, as in OBJ files and `TriangleMesh`.
+        let normal = (p2 - p1).cross(&(p3 - p1)).normalize();
 
         Self {
-            p1,
-            p2,
-            p3,
-            e1,
-            e2,
-            normal,
+            vertices,
+            normal: StoredVector::new(&normal),
+            texcoords: None,
+            vertex_normals: None,
             material,
         }
     }
 
-    pub fn bounding_box(&self, _time0: f64, _time1: f64) -> Option<AxisAlignedBoundingBox> {
-        let min_x = self.p1.x().min(self.p2.x().min(self.p3.x()));
-        let min_y = self.p1.y().min(self.p2.y().min(self.p3.y()));
-        let min_z = self.p1.z().min(self.p2.z().min(self.p3.z()));
+    pub fn with_texcoords(mut self, texcoords: [(f64, f64); 3]) -> Self {
+        self.texcoords = Some(texcoords.map(|(u, v)| [u as Real, v as Real]));
+        self
+    }
+
+    pub fn with_vertex_normals(mut self, normals: [Vector; 3]) -> Self {
+        self.vertex_normals = Some(normals.map(|n| StoredVector::new(&n)));
+        self
+    }
 
-        let max_x = self.p1.x().max(self.p2.x().max(self.p3.x()));
-        let max_y = self.p1.y().max(self.p2.y().max(self.p3.y()));
-        let max_z = self.p1.z().max(self.p2.z().max(self.p3.z()));
+    pub fn vertices(&self) -> [Point64; 3] {
+        self.vertices.map(|p| Point64(p.load()))
+    }
+
+    pub fn texcoords(&self) -> Option<[(f64, f64); 3]> {
+        self.texcoords
+            .map(|uvs| uvs.map(|[u, v]| (widen(u), widen(v))))
+    }
+
+    pub fn vertex_normals(&self) -> Option<[Vector; 3]> {
+        self.vertex_normals.map(|normals| normals.map(|n| n.load()))
+    }
+
+    fn normal(&self) -> Vector {
+        self.normal.load()
+    }
+
+    pub fn bounding_box(&self, _time0: f64, _time1: f64) -> Option<AxisAlignedBoundingBox> {
+        let [p1, p2, p3] = self.vertices().map(|p| p.0);
 
         Some(AxisAlignedBoundingBox {
-            minimum: Point64::new(min_x, min_y, min_z),
-            maximum: Point64::new(max_x, max_y, max_z),
+            minimum: Point64(p1.inf(&p2).inf(&p3)),
+            maximum: Point64(p1.sup(&p2).sup(&p3)),
         })
     }
 
-    pub fn is_hit_by(&self, ray: &Ray, _min_value: f64, _max_value: f64) -> Option<HitRecord> {
-        let cross_e2 = ray.direction.0.cross(&self.e2);
-        let determinant = self.e1.dot(&cross_e2);
+    /// Watertight intersection (Woop, Benthin and Wald 2013). The vertices are moved into a
+    /// space where the ray runs along +Z from the origin, so the inside test becomes three 2D
+    /// edge functions evaluated at the origin. Those are computed identically for both
+    /// triangles sharing an edge, so a ray can never slip between them; points exactly on an
+    /// edge or vertex count as inside.
+    ///
+    /// Returns the hit distance and the barycentric weights of the second and third vertices.
+    fn intersect(&self, ray: &Ray, min_value: f64, max_value: f64) -> Option<(f64, f64, f64)> {
+        let direction = ray.direction.0;
 
-        if determinant.abs() < EPSILON {
+        // Permute axes so the direction's largest component becomes Z, keeping handedness.
+        let kz = direction.iamax();
+        let mut kx = (kz + 1) % 3;
+        let mut ky = (kx + 1) % 3;
+        if direction[kz] < 0. {
+            std::mem::swap(&mut kx, &mut ky);
+        }
+        if direction[kz] == 0. {
             return None;
         }
 
-        let f = 1.0 / determinant;
-        let p1_to_origin = ray.origin.0 - self.p1.0;
-        let u = f * p1_to_origin.dot(&cross_e2);
+        // Shear so the direction becomes (0, 0, 1).
+        let shear_x = direction[kx] / direction[kz];
+        let shear_y = direction[ky] / direction[kz];
+        let shear_z = 1. / direction[kz];
+
+        let [p1, p2, p3] = self.vertices().map(|p| p.0);
+        let a = p1 - ray.origin.0;
+        let b = p2 - ray.origin.0;
+        let c = p3 - ray.origin.0;
+        let (ax, ay) = (a[kx] - shear_x * a[kz], a[ky] - shear_y * a[kz]);
+        let (bx, by) = (b[kx] - shear_x * b[kz], b[ky] - shear_y * b[kz]);
+        let (cx, cy) = (c[kx] - shear_x * c[kz], c[ky] - shear_y * c[kz]);
+
+        // Scaled barycentric coordinates of p1, p2 and p3.
+        let edge_u = cx * by - cy * bx;
+        let edge_v = ax * cy - ay * cx;
+        let edge_w = bx * ay - by * ax;
 
-        if !(0.0..=1.0).contains(&u) {
+        if (edge_u < 0. || edge_v < 0. || edge_w < 0.)
+            && (edge_u > 0. || edge_v > 0. || edge_w > 0.)
+        {
             return None;
         }
 
-        let origin_cross_e1 = p1_to_origin.cross(&self.e1);
-        let v = f * ray.direction.0.dot(&origin_cross_e1);
+        let determinant = edge_u + edge_v + edge_w;
+        if determinant == 0. {
+            return None;
+        }
 
-        if v < 0.0 || (u + v) > 1.0 {
+        let scaled_t = shear_z * (edge_u * a[kz] + edge_v * b[kz] + edge_w * c[kz]);
+        let t = scaled_t / determinant;
+        if !(min_value..max_value).contains(&t) {
             return None;
         }
 
-        Some(HitRecord::new(
-            f * self.e2.dot(&origin_cross_e1),
-            ray,
-            Point64(self.normal),
-            self.material.clone(),
-            (u, v),
-        ))
+        Some((t, edge_v / determinant, edge_w / determinant))
+    }
+
+    pub fn occluded(&self, ray: &Ray, min_value: f64, max_value: f64) -> bool {
+        self.intersect(ray, min_value, max_value).is_some()
+    }
+
+    pub fn is_hit_by(&self, ray: &Ray, min_value: f64, max_value: f64) -> Option<HitRecord<'_>> {
+        let (t, b1, b2) = self.intersect(ray, min_value, max_value)?;
+        let b0 = 1. - b1 - b2;
+        let uv = match self.texcoords() {
+            Some([uv1, uv2, uv3]) => (
+                b0 * uv1.0 + b1 * uv2.0 + b2 * uv3.0,
+                b0 * uv1.1 + b1 * uv2.1 + b2 * uv3.1,
+            ),
+            None => (b1, b2),
+        };
+
+        // Shading normals may tilt away from the face, but must stay on its side so front
+        // and back faces are still told apart by the true geometry.
+        let face_normal = self.normal();
+        let normal = match self.vertex_normals() {
+            Some([n1, n2, n3]) => {
+                let n = (n1 * b0 + n2 * b1 + n3 * b2)
+                    .try_normalize(0.)
+                    .unwrap_or(face_normal);
+                if n.dot(&face_normal) < 0. { -n } else { n }
+            }
+            None => face_normal,
+        };
+
+        Some(HitRecord::new(t, ray, Point64(normal), &self.material, uv))
     }
 }
 
@@ -86,9 +174,15 @@ impl Triangle {
 mod test {
     use crate::{
         data::{point64::Point64, ray::Ray},
+        hittables::Hittable,
         materials::Material,
         materials::dielectric::Dielectric,
+        util::obj::{Subdivision, load_obj_triangles},
     };
+    use nalgebra::Vector3;
+    use std::collections::HashMap;
+    use std::path::Path;
+    use std::sync::Arc;
 
     use super::Triangle;
 
@@ -98,9 +192,7 @@ mod test {
             Point64::new(0., 1., 0.),
             Point64::new(-1., 0., 0.),
             Point64::new(1., 0., 0.),
-            Material::Dielectric(Dielectric {
-                index_of_refraction: 1.0,
-            }),
+            Arc::new(Material::Dielectric(Dielectric::new(1.0))),
         );
         let r = Ray {
             origin: Point64::new(0., -1., -2.),
@@ -116,9 +208,7 @@ mod test {
             Point64::new(0., 1., 0.),
             Point64::new(-1., 0., 0.),
             Point64::new(1., 0., 0.),
-            Material::Dielectric(Dielectric {
-                index_of_refraction: 1.0,
-            }),
+            Arc::new(Material::Dielectric(Dielectric::new(1.0))),
         );
 
         let r = Ray {
@@ -136,9 +226,7 @@ mod test {
             Point64::new(0., 1., 0.),
             Point64::new(-1., 0., 0.),
             Point64::new(1., 0., 0.),
-            Material::Dielectric(Dielectric {
-                index_of_refraction: 1.0,
-            }),
+            Arc::new(Material::Dielectric(Dielectric::new(1.0))),
         );
 
         let r = Ray {
@@ -150,4 +238,247 @@ mod test {
         let hr = t.is_hit_by(&r, 0.0, 100.0).unwrap();
         assert_eq!(hr.value, 2.0)
     }
+
+    #[test]
+    fn counter_clockwise_side_is_the_front() {
+        // Counter-clockwise when seen from -Z, where the ray starts.
+        let t = Triangle::new(
+            Point64::new(0., 1., 0.),
+            Point64::new(1., 0., 0.),
+            Point64::new(-1., 0., 0.),
+            Arc::new(Material::Dielectric(Dielectric::new(1.0))),
+        );
+
+        let r = Ray {
+            origin: Point64::new(0., 0.5, -2.),
+            direction: Point64::new(0., 0., 1.),
+            exposure_time: 1.0,
+        };
+
+        let hr = t.is_hit_by(&r, 0.0, 100.0).unwrap();
+        assert!(hr.front_face);
+        assert_eq!(hr.normal, Point64::new(0., 0., -1.));
+    }
+
+    #[test]
+    fn hits_outside_interval_are_ignored() {
+        let t = Triangle::new(
+            Point64::new(0., 1., 0.),
+            Point64::new(-1., 0., 0.),
+            Point64::new(1., 0., 0.),
+            Arc::new(Material::Dielectric(Dielectric::new(1.0))),
+        );
+
+        // The triangle is behind this ray.
+        let r = Ray {
+            origin: Point64::new(0., 0.5, 2.),
+            direction: Point64::new(0., 0., 1.),
+            exposure_time: 1.0,
+        };
+        assert!(t.is_hit_by(&r, 0.001, 100.0).is_none());
+
+        // In front, but beyond a closer hit found earlier.
+        let r = Ray {
+            origin: Point64::new(0., 0.5, -2.),
+            direction: Point64::new(0., 0., 1.),
+            exposure_time: 1.0,
+        };
+        assert!(t.is_hit_by(&r, 0.001, 1.5).is_none());
+        assert!(t.is_hit_by(&r, 2.5, 100.0).is_none());
+        assert!(t.is_hit_by(&r, 0.001, 2.5).is_some());
+    }
+
+    #[test]
+    fn ray_through_vertex_hits() {
+        let t = Triangle::new(
+            Point64::new(0., 1., 0.),
+            Point64::new(-1., 0., 0.),
+            Point64::new(1., 0., 0.),
+            Arc::new(Material::Dielectric(Dielectric::new(1.0))),
+        );
+
+        for origin in [
+            Point64::new(0., 1., -2.),
+            Point64::new(-1., 0., -2.),
+            Point64::new(0.5, 0., -2.),
+        ] {
+            let r = Ray {
+                origin,
+                direction: Point64::new(0., 0., 1.),
+                exposure_time: 1.0,
+            };
+            let hr = t.is_hit_by(&r, 0.0, 100.0).unwrap();
+            assert_eq!(hr.value, 2.0);
+        }
+    }
+
+    fn teapot_triangles() -> Vec<Triangle> {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("resources/teapot.obj");
+        let material = Material::Dielectric(Dielectric::new(1.0));
+
+        load_obj_triangles(
+            &path,
+            material,
+            1.,
+            Vector3::zeros(),
+            Subdivision::default(),
+        )
+        .unwrap()
+        .into_iter()
+        .map(|h| match h {
+            Hittable::Triangle(t) => t,
+            _ => unreachable!(),
+        })
+        .collect()
+    }
+
+    fn key(p: &Point64) -> [u64; 3] {
+        [p.x().to_bits(), p.y().to_bits(), p.z().to_bits()]
+    }
+
+    /// Rays aimed exactly at points on an edge shared by two teapot triangles must hit one
+    /// of them, from either side and at a range of slants.
+    #[test]
+    fn shared_teapot_edges_are_watertight() {
+        let triangles = teapot_triangles();
+
+        let mut edges: HashMap<([u64; 3], [u64; 3]), Vec<usize>> = HashMap::new();
+        for (index, t) in triangles.iter().enumerate() {
+            let [p1, p2, p3] = t.vertices();
+            for (a, b) in [(&p1, &p2), (&p2, &p3), (&p3, &p1)] {
+                let (ka, kb) = (key(a), key(b));
+                edges
+                    .entry(if ka < kb { (ka, kb) } else { (kb, ka) })
+                    .or_default()
+                    .push(index);
+            }
+        }
+
+        let mut tested = 0;
+        for pair in edges.values().filter(|tris| tris.len() == 2) {
+            let (first, second) = (&triangles[pair[0]], &triangles[pair[1]]);
+            if first.normal().iter().any(|c| c.is_nan())
+                || second.normal().iter().any(|c| c.is_nan())
+            {
+                continue;
+            }
+            // Take the shared edge in the first triangle's winding order.
+            let first_vertices = first.vertices();
+            let second_vertices = second.vertices();
+            let shared = |p: &Point64| second_vertices.iter().any(|q| key(p) == key(q));
+            let start = (0..3)
+                .find(|&i| shared(&first_vertices[i]) && shared(&first_vertices[(i + 1) % 3]))
+                .unwrap();
+            let (a, b) = (first_vertices[start].0, first_vertices[(start + 1) % 3].0);
+
+            // Orient the second normal by winding rather than by angle, so that at a sharp
+            // crease the rays come from where both faces show the same side. A consistently
+            // wound neighbour runs the shared edge the other way.
+            let reversed = (0..3).any(|i| {
+                key(&second_vertices[i]) == key(&Point64(b))
+                    && key(&second_vertices[(i + 1) % 3]) == key(&Point64(a))
+            });
+            let second_normal = if reversed {
+                second.normal()
+            } else {
+                -second.normal()
+            };
+            let normal = first.normal() + second_normal;
+            if normal.magnitude() < 1e-6 {
+                continue;
+            }
+            let normal = normal.normalize();
+            let along = (b - a).normalize();
+
+            // The teapot's coordinates are single precision, so dyadic fractions of an edge
+            // are exact in f64 and every target lies on the edge itself. Directions are
+            // snapped to 1/256ths so that backing the origin off along them is exact too.
+            for s in [0.125, 0.375, 0.5, 0.8125] {
+                let target = a + (b - a) * s;
+                for side in [1., -1.] {
+                    for slant in [0., 0.3, -0.3] {
+                        let direction =
+                            (-(normal * side) + along * slant).map(|c| (c * 256.).round() / 256.);
+                        let r = Ray {
+                            origin: Point64(target - direction * 2.),
+                            direction: Point64(direction),
+                            exposure_time: 0.,
+                        };
+
+                        let hits = [first, second]
+                            .iter()
+                            .filter(|t| t.is_hit_by(&r, 0.001, 10.).is_some())
+                            .count();
+                        assert!(hits > 0, "ray slipped through a shared edge at {target:?}");
+                    }
+                }
+            }
+
+            tested += 1;
+            if tested == 500 {
+                break;
+            }
+        }
+        assert!(tested > 100);
+    }
+
+    /// Rays through a vertex surrounded by a closed fan of triangles must hit the fan.
+    #[test]
+    fn shared_teapot_vertices_are_watertight() {
+        let triangles = teapot_triangles();
+
+        let mut fans: HashMap<[u64; 3], Vec<usize>> = HashMap::new();
+        for (index, t) in triangles.iter().enumerate() {
+            for p in &t.vertices() {
+                fans.entry(key(p)).or_default().push(index);
+            }
+        }
+
+        let mut tested = 0;
+        for (vertex_key, fan) in &fans {
+            let vertex =
+                Vector3::from_iterator(vertex_key.iter().map(|bits| f64::from_bits(*bits)));
+            let normals: Vec<Vector3<f64>> = fan.iter().map(|i| triangles[*i].normal()).collect();
+            if fan.len() < 3 || normals.iter().any(|n| n.iter().any(|c| c.is_nan())) {
+                continue;
+            }
+            // Only gently curved, consistently oriented fans, so the fan's projection along
+            // the average normal surrounds the vertex.
+            let average = normals.iter().sum::<Vector3<f64>>().normalize();
+            if normals.iter().any(|n| n.dot(&average) < 0.5) {
+                continue;
+            }
+            // Each of the vertex's edges must be shared by two fan triangles.
+            let mut edge_counts: HashMap<[u64; 3], usize> = HashMap::new();
+            for t in fan.iter().map(|i| &triangles[*i]) {
+                for p in &t.vertices() {
+                    if key(p) != *vertex_key {
+                        *edge_counts.entry(key(p)).or_default() += 1;
+                    }
+                }
+            }
+            if edge_counts.values().any(|count| *count != 2) {
+                continue;
+            }
+
+            for side in [1., -1.] {
+                let r = Ray {
+                    origin: Point64(vertex + average * side * 2.),
+                    direction: Point64(-average * side),
+                    exposure_time: 0.,
+                };
+                assert!(
+                    fan.iter()
+                        .any(|i| triangles[*i].is_hit_by(&r, 0.001, 10.).is_some()),
+                    "ray slipped through a vertex at {vertex:?}"
+                );
+            }
+
+            tested += 1;
+            if tested == 500 {
+                break;
+            }
+        }
+        assert!(tested > 100);
+    }
 }
@@ -6,9 +6,11 @@ use image::RgbImage;
 use crate::textures::noise::NoiseType::{Marble, Perlin, Turbulence};
 use crate::util::worlds::World;
 use image::DynamicImage::ImageRgb8;
+use nalgebra::{Isometry3, Translation3, UnitQuaternion, Vector3};
 use std::env;
-use util::args::parse_args;
-use util::render::render_frame;
+use std::f64::consts::TAU;
+use util::args::{ProgramOptions, parse_args};
+use util::render::{RenderMode, render_frame};
 
 mod camera;
 mod data;
@@ -19,39 +21,54 @@ mod util;
 
 fn main() -> anyhow::Result<()> {
     let args: Vec<String> = env::args().collect();
+    if args.get(1).map(String::as_str) == Some("bench") {
+        return util::bench::run_bench();
+    }
+
     let options = parse_args(&args)?;
 
+    if args.get(1).map(String::as_str) == Some("build-cache") {
+        // Loading a world fills the caches of every mesh it uses.
+        build_world(&options)?;
+        println!(
+            "Mesh caches for world {} are up to date in {}",
+            options.world_choice,
+            util::mesh_cache::cache_dir().display()
+        );
+        return Ok(());
+    }
+
     if options.help {
         println!("{}", options.help_str);
+        println!("{}", util::args::SUBCOMMANDS_HELP);
         return Ok(());
     }
 
-    let world_choice = options.world_choice;
+    let mut world = build_world(&options)?;
+    world.samples_per_pixel = options.samples_per_pixel;
 
-    let mut world = match world_choice {
-        0 => World::random_world(options.use_bvh),
-        1 => World::two_spheres(),
-        2 => World::two_perlin_spheres(Perlin),
-        3 => World::two_perlin_spheres(Turbulence),
-        4 => World::two_perlin_spheres(Marble),
-        5 => World::earth(),
-        6 => World::simple_light(),
-        7 => World::cornell_box(),
-        8 => World::final_scene(),
-        9 => World::utah_teapots(),
-        10 => World::cornell_smoke(),
-        _ => anyhow::bail!("bad world choice: {}", world_choice),
-    };
+    if options.turntable_frames > 0 {
+        return render_turntable(world, options.turntable_frames, options.render_mode);
+    }
 
-    world.samples_per_pixel = options.samples_per_pixel;
     let world = Arc::new(world);
 
     if options.interactive {
-        util::interactive::run_interactive(world).context("interactive mode failed")?;
+        util::interactive::run_interactive(world, options.render_mode)
+            .context("interactive mode failed")?;
         return Ok(());
     }
 
+    save_render(&world, "output.png", options.render_mode)?;
+
+    println!("Done!");
+
+    Ok(())
+}
+
+fn save_render(world: &Arc<World>, path: &str, mode: RenderMode) -> anyhow::Result<()> {
     let rows = render_frame(
+        mode,
         world.camera.clone(),
         world.clone(),
         world.image_width,
@@ -75,9 +92,75 @@ fn main() -> anyhow::Result<()> {
         }
     }
 
-    ImageRgb8(image).save("output.png")?;
+    ImageRgb8(image).save(path)?;
+    Ok(())
+}
+
+/// Renders `frames` images in which every instance in the world's top-level BVH makes one
+/// turn about a vertical axis through its center. Only the top level is refit per frame.
+fn render_turntable(mut world: World, frames: u32, mode: RenderMode) -> anyhow::Result<()> {
+    let tlas = world
+        .hittable
+        .top_level_bvh_mut()
+        .context("this world has no instanced objects to turn")?;
+    let start: Vec<(Isometry3<f64>, Vector3<f64>)> = tlas
+        .instances()
+        .iter()
+        .map(|instance| (*instance.transform(), instance.bounding_box().centroid().0))
+        .collect();
 
-    println!("Done!");
+    let mut world = Arc::new(world);
+    for frame in 0..frames {
+        let angle = TAU * frame as f64 / frames as f64;
+        // The previous render has released the world, so this updates it in place.
+        let tlas = Arc::make_mut(&mut world)
+            .hittable
+            .top_level_bvh_mut()
+            .unwrap();
+        for (index, (transform, center)) in start.iter().enumerate() {
+            let spin = Translation3::from(*center)
+                * UnitQuaternion::from_axis_angle(&Vector3::y_axis(), angle)
+                * Translation3::from(-center);
+            tlas.set_transform(index, spin * transform);
+        }
+        tlas.refit();
+
+        let path = format!("output_{frame:03}.png");
+        save_render(&world, &path, mode)?;
+        println!("Frame {}/{frames} written to {path}", frame + 1);
+    }
 
     Ok(())
 }
+
+fn build_world(options: &ProgramOptions) -> anyhow::Result<World> {
+    let world_choice = options.world_choice;
+
+    let world = match world_choice {
+        0 => World::random_world(options.use_bvh, &options.bvh_options),
+        1 => World::two_spheres(),
+        2 => World::two_perlin_spheres(Perlin),
+        3 => World::two_perlin_spheres(Turbulence),
+        4 => World::two_perlin_spheres(Marble),
+        5 => World::earth(),
+        6 => World::simple_light(),
+        7 => World::cornell_box(),
+        8 => World::final_scene(&options.bvh_options),
+        9 => World::utah_teapots(),
+        10 => World::cornell_smoke(),
+        11 => World::tori(),
+        12 => World::csg(),
+        13 => World::distance_fields(),
+        14 => World::terrain(),
+        15 => World::grass(),
+        16 => World::metaballs(),
+        17 => World::displacement(),
+        18 => World::conductors(),
+        19 => World::frosted_glass(),
+        20 => World::principled(),
+        21 => World::coatings(),
+        _ => anyhow::bail!("bad world choice: {}", world_choice),
+    };
+
+    Ok(world)
+}
@@ -3,21 +3,28 @@ use crate::data::point64::Point64;
 use crate::data::ray::Ray;
 use crate::hittables::HitRecord;
 
+pub mod conductor;
 pub mod dielectric;
 pub mod diffuse_light;
 pub mod isotropic;
 pub mod lambertian;
+pub mod layered;
 pub mod metal;
+pub mod microfacet;
+pub mod principled;
+pub mod rough_dielectric;
 
+pub use conductor::Conductor;
 use dielectric::Dielectric;
 use diffuse_light::DiffuseLight;
 pub use isotropic::Isotropic;
 use lambertian::Lambertian;
+pub use layered::Layered;
 use metal::Metal;
+pub use principled::Principled;
+pub use rough_dielectric::RoughDielectric;
 
 pub struct ScatterRecord {
-    #[allow(dead_code)]
-    pub hit_record: HitRecord,
     pub attenuation: Color64,
     pub scattered: Ray,
 }
@@ -29,6 +36,10 @@ pub enum Material {
     DiffuseLight(DiffuseLight),
     Isotropic(Isotropic),
     Metal(Metal),
+    Conductor(Conductor),
+    RoughDielectric(RoughDielectric),
+    Principled(Box<Principled>),
+    Layered(Box<Layered>),
 }
 
 impl Material {
@@ -39,13 +50,86 @@ impl Material {
             Material::DiffuseLight(m) => m.scatter(ray_in, hit_record),
             Material::Isotropic(m) => m.scatter(ray_in, hit_record),
             Material::Metal(m) => m.scatter(ray_in, hit_record),
+            Material::Conductor(m) => m.scatter(ray_in, hit_record),
+            Material::RoughDielectric(m) => m.scatter(ray_in, hit_record),
+            Material::Principled(m) => m.scatter(ray_in, hit_record),
+            Material::Layered(m) => m.scatter(ray_in, hit_record),
+        }
+    }
+
+    /// Position of the variant in the enum, for grouping hits by material.
+    pub fn variant_index(&self) -> usize {
+        match self {
+            Material::Lambertian(_) => 0,
+            Material::Dielectric(_) => 1,
+            Material::DiffuseLight(_) => 2,
+            Material::Isotropic(_) => 3,
+            Material::Metal(_) => 4,
+            Material::Conductor(_) => 5,
+            Material::RoughDielectric(_) => 6,
+            Material::Principled(_) => 7,
+            Material::Layered(_) => 8,
         }
     }
 
     pub fn emitted(&self, u: f64, v: f64, point: &Point64) -> Color64 {
         match self {
             Material::DiffuseLight(m) => m.emitted(u, v, point),
+            Material::Principled(m) => m.emitted(u, v, point),
+            Material::Layered(m) => m.emitted(u, v, point),
             _ => BLACK,
         }
     }
 }
+
+/// Fixtures shared by the material tests: a surface at the origin facing +y, lit by rays in
+/// the y-z plane.
+#[cfg(test)]
+pub(crate) mod test_fixtures {
+    use super::Material;
+    use crate::data::color64::Color64;
+    use crate::data::point64::Point64;
+    use crate::data::ray::Ray;
+    use crate::hittables::HitRecord;
+
+    /// A hit at the origin with the normal facing the incoming ray; `front_face` is false
+    /// when the ray arrives from inside the object.
+    pub(crate) fn hit_at_origin(material: &Material, front_face: bool) -> HitRecord<'_> {
+        HitRecord {
+            value: 1.,
+            u: 0.,
+            v: 0.,
+            location: Point64::new(0., 0., 0.),
+            normal: Point64::new(0., 1., 0.),
+            front_face,
+            outside_ior: 1.,
+            material,
+        }
+    }
+
+    /// A unit ray ending at the origin, arriving `degrees` away from the normal.
+    pub(crate) fn ray_towards_origin(degrees: f64) -> Ray {
+        let (sin, cos) = degrees.to_radians().sin_cos();
+        Ray {
+            origin: Point64::new(0., cos, -sin),
+            direction: Point64::new(0., -cos, sin),
+            exposure_time: 0.,
+        }
+    }
+
+    /// Mean attenuation over `samples` scatters, counting absorbed rays as black.
+    pub(crate) fn average_attenuation(
+        material: &Material,
+        ray: &Ray,
+        hit_record: &HitRecord,
+        samples: usize,
+    ) -> Color64 {
+        let mut total = Color64::gray(0.);
+        for _ in 0..samples {
+            if let Some(scatter) = material.scatter(ray, hit_record) {
+                total += scatter.attenuation;
+            }
+        }
+        Color64(total.0 / samples as f64)
+    }
+}
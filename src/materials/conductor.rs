@@ -0,0 +1,135 @@
+use crate::data::color64::Color64;
+use crate::data::point64::Point64;
+use crate::data::ray::Ray;
+use crate::hittables::HitRecord;
+use crate::materials::ScatterRecord;
+use crate::materials::microfacet::{
+    Ggx, ShadingFrame, fresnel_conductor, reflect, roughness_to_alpha,
+};
+
+/// Rough metal with a GGX microfacet surface and Fresnel reflectance from its complex index of
+/// refraction `eta + i k`, given per RGB channel.
+#[derive(Clone)]
+pub struct Conductor {
+    pub eta: Color64,
+    pub k: Color64,
+    pub distribution: Ggx,
+}
+
+impl Conductor {
+    pub fn new(eta: Color64, k: Color64, roughness: f64) -> Self {
+        Self::anisotropic(eta, k, roughness, roughness)
+    }
+
+    /// Different roughnesses along the surface tangent and bitangent, which stretch
+    /// highlights like on brushed metal.
+    pub fn anisotropic(eta: Color64, k: Color64, roughness_x: f64, roughness_y: f64) -> Self {
+        Self {
+            eta,
+            k,
+            distribution: Ggx::new(
+                roughness_to_alpha(roughness_x),
+                roughness_to_alpha(roughness_y),
+            ),
+        }
+    }
+
+    pub fn gold(roughness: f64) -> Self {
+        Self::new(
+            Color64::new(0.143119, 0.374957, 1.442479),
+            Color64::new(3.983160, 2.385721, 1.603215),
+            roughness,
+        )
+    }
+
+    pub fn copper(roughness: f64) -> Self {
+        Self::new(
+            Color64::new(0.200438, 0.924033, 1.102212),
+            Color64::new(3.912949, 2.452848, 2.142188),
+            roughness,
+        )
+    }
+
+    pub fn aluminum(roughness: f64) -> Self {
+        Self::new(
+            Color64::new(1.657460, 0.880369, 0.521229),
+            Color64::new(9.223869, 6.269523, 4.837001),
+            roughness,
+        )
+    }
+
+    pub fn silver(roughness: f64) -> Self {
+        Self::new(
+            Color64::new(0.155265, 0.116723, 0.138342),
+            Color64::new(4.828181, 3.122249, 2.146961),
+            roughness,
+        )
+    }
+
+    /// Reflects off a microfacet normal sampled from the visible normals, so the weight is
+    /// just Fresnel times the shadowing of the outgoing direction, `G2 / G1`.
+    pub fn scatter(&self, ray_in: &Ray, hit_record: &HitRecord) -> Option<ScatterRecord> {
+        let frame = ShadingFrame::new(&hit_record.normal.0);
+        let wi = frame.to_local(&-ray_in.direction.0.normalize());
+        if wi.z <= 0. {
+            return None;
+        }
+
+        let microfacet_normal =
+            self.distribution
+                .sample_visible_normal(&wi, rand::random(), rand::random());
+        let wo = reflect(&wi, &microfacet_normal);
+        if wo.z <= 0. {
+            return None;
+        }
+
+        // The complex index of refraction, relative to the medium the light arrives from.
+        let (eta, k) = (
+            self.eta.0 / hit_record.outside_ior,
+            self.k.0 / hit_record.outside_ior,
+        );
+        let fresnel = fresnel_conductor(wi.dot(&microfacet_normal), &eta, &k);
+        let shadowing =
+            self.distribution.masking_shadowing(&wi, &wo) / self.distribution.masking(&wi);
+
+        Some(ScatterRecord {
+            attenuation: Color64(fresnel * shadowing),
+            scattered: Ray {
+                origin: hit_record.location,
+                direction: Point64(frame.to_world(&wo)),
+                exposure_time: ray_in.exposure_time,
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Conductor;
+    use crate::data::color64::Color64;
+    use crate::materials::Material;
+    use crate::materials::test_fixtures::{average_attenuation, hit_at_origin, ray_towards_origin};
+
+    /// With eta = 1 and a huge k the Fresnel term is practically 1, so the average weight is
+    /// the energy kept by single scattering: close to 1 when smooth and lower when rough, as
+    /// single scattering ignores light bouncing between microfacets.
+    #[test]
+    fn white_furnace_loses_little_energy() {
+        let ray = ray_towards_origin(45.);
+        let mut previous = 1.;
+        for (roughness, minimum) in [(0.05, 0.99), (0.5, 0.85), (1., 0.3)] {
+            let material = Material::Conductor(Conductor::new(
+                Color64::gray(1.),
+                Color64::gray(1e4),
+                roughness,
+            ));
+            let hit_record = hit_at_origin(&material, true);
+            let average = average_attenuation(&material, &ray, &hit_record, 20_000).r();
+            assert!(
+                average <= previous + 1e-2 && average > minimum,
+                "{roughness}: {average}"
+            );
+            previous = average;
+        }
+    }
+}
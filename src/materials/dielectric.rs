@@ -16,14 +16,36 @@ fn reflectance(cosine: f64, ref_idx: f64) -> f64 {
 #[derive(Clone)]
 pub struct Dielectric {
     pub index_of_refraction: f64,
+    /// Beer–Lambert absorption coefficient per unit length inside the glass, per RGB channel.
+    pub absorption: Color64,
 }
 
 impl Dielectric {
+    /// Clear glass, which absorbs nothing.
+    pub fn new(index_of_refraction: f64) -> Self {
+        Self {
+            index_of_refraction,
+            absorption: Color64::gray(0.),
+        }
+    }
+
+    /// Tints the glass so that light keeps `color` after travelling `distance` inside it;
+    /// longer paths through thicker glass come out darker. Each channel of `color` is clamped
+    /// into (0, 1], since glass cannot add light nor absorb all of it.
+    pub fn with_transmittance(self, color: Color64, distance: f64) -> Self {
+        assert!(distance > 0., "transmittance distance must be positive");
+        Self {
+            absorption: Color64(color.0.map(|c| -c.clamp(1e-6, 1.).ln() / distance)),
+            ..self
+        }
+    }
+
     pub fn scatter(&self, ray_in: &Ray, hit_record: &HitRecord) -> Option<ScatterRecord> {
+        let relative_ior = self.index_of_refraction / hit_record.outside_ior;
         let refraction_ratio = if hit_record.front_face {
-            self.index_of_refraction.inv()
+            relative_ior.inv()
         } else {
-            self.index_of_refraction
+            relative_ior
         };
 
         let unit_direction = ray_in.direction.0.normalize();
@@ -39,9 +61,17 @@ impl Dielectric {
                 refract(&unit_direction, &hit_record.normal.0, refraction_ratio)
             };
 
+        // A ray hitting the inside of the surface has travelled through the glass since it
+        // entered, so it is absorbed along the whole segment.
+        let attenuation = if hit_record.front_face {
+            Color64::gray(1.)
+        } else {
+            let distance = hit_record.value * ray_in.direction.0.norm();
+            Color64(self.absorption.0.map(|sigma| (-sigma * distance).exp()))
+        };
+
         Some(ScatterRecord {
-            hit_record: hit_record.clone(),
-            attenuation: Color64::new(1., 1., 1.),
+            attenuation,
             scattered: Ray {
                 origin: hit_record.location,
                 direction: Point64(direction),
@@ -50,3 +80,49 @@ impl Dielectric {
         })
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::Dielectric;
+    use crate::data::color64::Color64;
+    use crate::data::point64::Point64;
+    use crate::data::ray::Ray;
+    use crate::hittables::HitRecord;
+    use crate::materials::Material;
+    use crate::materials::test_fixtures::hit_at_origin;
+    use approx::assert_abs_diff_eq;
+
+    #[test]
+    fn absorption_grows_with_the_path_inside() {
+        let green = Color64::new(0.2, 0.8, 0.3);
+        let glass = Dielectric::new(1.5).with_transmittance(green, 2.);
+        let material = Material::Dielectric(glass.clone());
+        let ray = Ray {
+            origin: Point64::new(0., 0., 0.),
+            direction: Point64::new(0., 0., 0.5),
+            exposure_time: 0.,
+        };
+        let attenuation = |distance: f64, front_face: bool| {
+            let hit_record = HitRecord {
+                value: distance / 0.5,
+                location: Point64::new(0., 0., distance),
+                normal: Point64::new(0., 0., -1.),
+                ..hit_at_origin(&material, front_face)
+            };
+            glass.scatter(&ray, &hit_record).unwrap().attenuation
+        };
+
+        assert_eq!(attenuation(2., true), Color64::gray(1.));
+        assert_abs_diff_eq!(attenuation(2., false).0, green.0, epsilon = 1e-12);
+        let thick = attenuation(4., false);
+        assert_abs_diff_eq!(thick.0, green.0.component_mul(&green.0), epsilon = 1e-12);
+    }
+
+    #[test]
+    fn transmittance_is_clamped_to_what_glass_can_do() {
+        let glass = Dielectric::new(1.5).with_transmittance(Color64::new(1.5, -0.2, 0.), 1.);
+        assert_eq!(glass.absorption.r(), 0.);
+        assert!(glass.absorption.g().is_finite() && glass.absorption.g() > 0.);
+        assert_eq!(glass.absorption.g(), glass.absorption.b());
+    }
+}
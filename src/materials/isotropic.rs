@@ -13,7 +13,6 @@ pub struct Isotropic {
 impl Isotropic {
     pub fn scatter(&self, ray_in: &Ray, hit_record: &HitRecord) -> Option<ScatterRecord> {
         Some(ScatterRecord {
-            hit_record: hit_record.clone(),
             attenuation: self
                 .albedo
                 .value(hit_record.u, hit_record.v, &hit_record.location),
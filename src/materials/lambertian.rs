@@ -15,7 +15,6 @@ impl Lambertian {
         let scatter_direction = hit_record.normal.0 + random_in_unit_sphere();
 
         Some(ScatterRecord {
-            hit_record: hit_record.clone(),
             attenuation: self
                 .albedo
                 .value(hit_record.u, hit_record.v, &hit_record.location),
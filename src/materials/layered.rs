@@ -0,0 +1,297 @@
+use crate::data::color64::Color64;
+use crate::data::point64::Point64;
+use crate::data::ray::Ray;
+use crate::data::vector3::Vector;
+use crate::hittables::HitRecord;
+use crate::materials::microfacet::{
+    Ggx, ShadingFrame, fresnel_dielectric, reflect, refract, roughness_to_alpha,
+};
+use crate::materials::{Material, ScatterRecord};
+use nalgebra::Vector3;
+use rand_distr::num_traits::Inv;
+
+/// Times light may bounce between the coating and the base before it counts as absorbed.
+const MAX_INTERNAL_BOUNCES: usize = 8;
+
+/// A dielectric coating such as lacquer or varnish over any other material. Light reflects off
+/// the coating by its Fresnel reflectance; the rest refracts in, is scattered by the base, and
+/// leaves again, possibly after bouncing back down off the underside of the coating. Every
+/// pass through the coating is tinted by its absorption.
+///
+/// The base sees light arriving from within the coating, through a hit record whose
+/// [`outside_ior`](HitRecord::outside_ior) is the coating's, so a base with its own index of
+/// refraction refracts and reflects at the coating–base interface.
+#[derive(Clone)]
+pub struct Layered {
+    pub base: Material,
+    pub index_of_refraction: f64,
+    pub distribution: Ggx,
+    /// Absorption coefficient per unit length inside the coating, per RGB channel.
+    pub absorption: Color64,
+    pub thickness: f64,
+}
+
+impl Layered {
+    /// A clear coating; see [`Layered::with_absorption`] for a tinted one.
+    pub fn new(base: Material, index_of_refraction: f64, roughness: f64) -> Self {
+        let alpha = roughness_to_alpha(roughness);
+        Self {
+            base,
+            index_of_refraction,
+            distribution: Ggx::new(alpha, alpha),
+            absorption: Color64::gray(0.),
+            thickness: 0.,
+        }
+    }
+
+    pub fn with_absorption(self, absorption: Color64, thickness: f64) -> Self {
+        Self {
+            absorption,
+            thickness,
+            ..self
+        }
+    }
+
+    /// Fraction of light kept after crossing the coating along `w`, given in the local frame.
+    fn transmittance(&self, w: &Vector) -> Vector {
+        let path_length = self.thickness / w.z.abs().max(1e-4);
+        self.absorption.0.map(|sigma| (-sigma * path_length).exp())
+    }
+
+    pub fn scatter(&self, ray_in: &Ray, hit_record: &HitRecord) -> Option<ScatterRecord> {
+        // The coating is on the outside, so the frame always points out of the object.
+        let outward = if hit_record.front_face {
+            hit_record.normal
+        } else {
+            -hit_record.normal
+        };
+        let frame = ShadingFrame::new(&outward.0);
+        // The coating relative to whatever lies outside it, usually air.
+        let eta = self.index_of_refraction / hit_record.outside_ior;
+        let under_coating = HitRecord {
+            normal: outward,
+            front_face: true,
+            outside_ior: self.index_of_refraction,
+            ..hit_record.clone()
+        };
+        let scatter = |attenuation: Vector, wo: Vector| {
+            Some(ScatterRecord {
+                attenuation: Color64(attenuation),
+                scattered: Ray {
+                    origin: hit_record.location,
+                    direction: Point64(frame.to_world(&wo)),
+                    exposure_time: ray_in.exposure_time,
+                },
+            })
+        };
+
+        if !hit_record.front_face {
+            // Light from inside meets the base first, which sees an ordinary back-face hit.
+            let from_inside = HitRecord {
+                outside_ior: self.index_of_refraction,
+                ..hit_record.clone()
+            };
+            let base_scatter = self.base.scatter(ray_in, &from_inside)?;
+            let weight = base_scatter.attenuation.0;
+            let up = frame.to_local(&base_scatter.scattered.direction.0.normalize());
+            if up.z <= 0. {
+                return scatter(weight, up);
+            }
+            let (weight, wo) =
+                self.leave_coating(ray_in, &under_coating, &frame, eta, weight, up)?;
+            return scatter(weight, wo);
+        }
+
+        let wi = frame.to_local(&-ray_in.direction.0.normalize());
+        if wi.z <= 0. {
+            return None;
+        }
+        let h = self
+            .distribution
+            .sample_visible_normal(&wi, rand::random(), rand::random());
+        if fresnel_dielectric(wi.dot(&h), eta.inv()) > rand::random() {
+            let wo = reflect(&wi, &h);
+            if wo.z <= 0. {
+                return None;
+            }
+            let shadowing =
+                self.distribution.masking_shadowing(&wi, &wo) / self.distribution.masking(&wi);
+            return scatter(Vector::repeat(shadowing), wo);
+        }
+
+        let down = refract(&wi, &h, eta.inv())?;
+        if down.z >= 0. {
+            return None;
+        }
+        let mut weight = Vector::repeat(
+            self.distribution.masking_shadowing(&wi, &down) / self.distribution.masking(&wi),
+        );
+        weight.component_mul_assign(&self.transmittance(&down));
+        let (attenuation, up) = self.scatter_off_base(ray_in, &under_coating, &frame, &down)?;
+        weight.component_mul_assign(&attenuation);
+        if up.z <= 0. {
+            // The base let the light through, as glass would.
+            return scatter(weight, up);
+        }
+        let (weight, wo) = self.leave_coating(ray_in, &under_coating, &frame, eta, weight, up)?;
+        scatter(weight, wo)
+    }
+
+    /// Scatters light heading `down` off the base, which sees it arrive from within the
+    /// coating. Returns the attenuation and the local direction the base sends it in.
+    fn scatter_off_base(
+        &self,
+        ray_in: &Ray,
+        hit_record: &HitRecord,
+        frame: &ShadingFrame,
+        down: &Vector,
+    ) -> Option<(Vector, Vector)> {
+        let ray = Ray {
+            origin: hit_record.location,
+            direction: Point64(frame.to_world(down)),
+            exposure_time: ray_in.exposure_time,
+        };
+        let base_scatter = self.base.scatter(&ray, hit_record)?;
+        let up = frame.to_local(&base_scatter.scattered.direction.0.normalize());
+        Some((base_scatter.attenuation.0, up))
+    }
+
+    /// Carries light that left the base heading `up` out through the coating, whose index of
+    /// refraction relative to the outside is `eta`, returning the weight and the local
+    /// direction it leaves in. The underside of the coating is treated as smooth, as the
+    /// roughness already blurred the way in.
+    fn leave_coating(
+        &self,
+        ray_in: &Ray,
+        hit_record: &HitRecord,
+        frame: &ShadingFrame,
+        eta: f64,
+        mut weight: Vector,
+        mut up: Vector,
+    ) -> Option<(Vector, Vector)> {
+        for _ in 0..MAX_INTERNAL_BOUNCES {
+            weight.component_mul_assign(&self.transmittance(&up));
+            if fresnel_dielectric(up.z, eta) <= rand::random() {
+                // Fresnel is 1 under total internal reflection, so refraction cannot fail.
+                return Some((weight, refract(&-up, &-Vector3::z(), eta)?));
+            }
+
+            let down = Vector3::new(up.x, up.y, -up.z);
+            weight.component_mul_assign(&self.transmittance(&down));
+            let (attenuation, next) = self.scatter_off_base(ray_in, hit_record, frame, &down)?;
+            weight.component_mul_assign(&attenuation);
+            if next.z <= 0. {
+                return Some((weight, next));
+            }
+            up = next;
+        }
+        None
+    }
+
+    pub fn emitted(&self, u: f64, v: f64, point: &Point64) -> Color64 {
+        self.base.emitted(u, v, point)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Layered;
+    use crate::data::color64::Color64;
+    use crate::materials::Material;
+    use crate::materials::dielectric::Dielectric;
+    use crate::materials::lambertian::Lambertian;
+    use crate::materials::test_fixtures::{average_attenuation, hit_at_origin, ray_towards_origin};
+    use crate::textures::Texture;
+
+    fn average_weight(layered: Layered, samples: usize) -> Color64 {
+        let material = Material::Layered(Box::new(layered));
+        let hit_record = hit_at_origin(&material, true);
+        average_attenuation(&material, &ray_towards_origin(45.), &hit_record, samples)
+    }
+
+    #[test]
+    fn clear_coat_over_white_keeps_energy_and_absorption_tints_it() {
+        let white = Material::Lambertian(Lambertian {
+            albedo: Texture::solid(Color64::gray(1.)),
+        });
+
+        for roughness in [0., 0.3] {
+            let clear = average_weight(Layered::new(white.clone(), 1.5, roughness), 20_000);
+            // Only light trapped past the bounce limit is lost.
+            assert!(clear.r() <= 1.01 && clear.r() > 0.95, "{clear:?}");
+        }
+
+        let amber = average_weight(
+            Layered::new(white, 1.5, 0.).with_absorption(Color64::new(0.1, 1., 4.), 0.5),
+            20_000,
+        );
+        assert!(amber.r() > amber.g() && amber.g() > amber.b(), "{amber:?}");
+        // Coating reflections are untinted, so even blue keeps a few percent.
+        assert!(amber.b() > 0.03, "{amber:?}");
+    }
+
+    /// Light inside coated glass crosses the glass before the coating, so past the critical
+    /// angle of the glass none of it escapes, and what does escape leaves outwards.
+    #[test]
+    fn light_from_inside_crosses_the_base_first() {
+        let material = Material::Layered(Box::new(Layered::new(
+            Material::Dielectric(Dielectric::new(1.5)),
+            1.5,
+            0.,
+        )));
+        let hit_record = hit_at_origin(&material, false);
+
+        let samples = 2_000;
+        for (degrees, escapes) in [(60., false), (0., true)] {
+            let ray = ray_towards_origin(degrees);
+            let mut escaped = 0;
+            for _ in 0..samples {
+                let scatter = material.scatter(&ray, &hit_record).unwrap();
+                assert!(scatter.attenuation.r() > 0.999, "{:?}", scatter.attenuation);
+                // The normal faces the inside, so leaving the object means heading down.
+                if scatter.scattered.direction.y() < 0. {
+                    escaped += 1;
+                }
+            }
+            if escapes {
+                assert!(escaped > samples * 3 / 4, "{degrees}°: {escaped} escaped");
+            } else {
+                assert_eq!(escaped, 0, "{degrees}°");
+            }
+        }
+    }
+
+    /// Under a smooth coating, light crossing into a glass base bends by the ratio of the
+    /// coating's index of refraction to the glass's, not the air's.
+    #[test]
+    fn glass_bases_refract_relative_to_the_coating() {
+        let (coat_ior, base_ior) = (1.3, 1.5);
+        let material = Material::Layered(Box::new(Layered::new(
+            Material::Dielectric(Dielectric::new(base_ior)),
+            coat_ior,
+            0.,
+        )));
+        let hit_record = hit_at_origin(&material, true);
+        let ray = ray_towards_origin(60.);
+
+        // Snell's law at the air–coating interface, then at the coating–glass one.
+        let sin_coat = 60_f64.to_radians().sin() / coat_ior;
+        let sin_base = sin_coat * coat_ior / base_ior;
+
+        let samples = 20_000;
+        let mut transmitted = 0;
+        let mut strays = 0;
+        for _ in 0..samples {
+            let scatter = material.scatter(&ray, &hit_record).unwrap();
+            let direction = scatter.scattered.direction.0.normalize();
+            if direction.y < 0. {
+                transmitted += 1;
+                if (direction.z - sin_base).abs() > 1e-2 {
+                    strays += 1;
+                }
+            }
+        }
+        assert!(transmitted > samples / 2, "{transmitted} transmitted");
+        assert!(strays < samples / 500, "{strays} strays");
+    }
+}
@@ -17,7 +17,6 @@ impl Metal {
 
         if reflected.dot(&hit_record.normal.0) > 0. {
             Some(ScatterRecord {
-                hit_record: hit_record.clone(),
                 attenuation: self.albedo,
                 scattered: Ray {
                     origin: hit_record.location,
@@ -0,0 +1,251 @@
+//! GGX (Trowbridge–Reitz) microfacet model shared by the rough materials.
+//!
+//! Directions are expressed in a shading frame whose z axis is the surface normal; see
+//! [`ShadingFrame`]. `alpha_x` and `alpha_y` are the roughnesses along its x and y axes.
+
+use crate::data::vector3::Vector;
+use nalgebra::Vector3;
+use std::f64::consts::{PI, TAU};
+
+/// Smallest roughness used, since a perfectly smooth GGX lobe is a delta function.
+const MIN_ALPHA: f64 = 1e-4;
+
+/// Orthonormal frame around a shading normal. The tangent is chosen continuously from the
+/// normal alone (Duff et al. 2017), which orients anisotropic highlights consistently over
+/// a smooth surface.
+pub struct ShadingFrame {
+    tangent: Vector,
+    bitangent: Vector,
+    normal: Vector,
+}
+
+impl ShadingFrame {
+    pub fn new(normal: &Vector) -> Self {
+        let sign = 1_f64.copysign(normal.z);
+        let a = -1. / (sign + normal.z);
+        let b = normal.x * normal.y * a;
+        Self {
+            tangent: Vector3::new(
+                1. + sign * normal.x * normal.x * a,
+                sign * b,
+                -sign * normal.x,
+            ),
+            bitangent: Vector3::new(b, sign + normal.y * normal.y * a, -normal.y),
+            normal: *normal,
+        }
+    }
+
+    pub fn to_local(&self, v: &Vector) -> Vector {
+        Vector3::new(
+            v.dot(&self.tangent),
+            v.dot(&self.bitangent),
+            v.dot(&self.normal),
+        )
+    }
+
+    pub fn to_world(&self, v: &Vector) -> Vector {
+        self.tangent * v.x + self.bitangent * v.y + self.normal * v.z
+    }
+}
+
+/// Maps a perceptual roughness in `[0, 1]` to GGX alpha, which is its square.
+pub fn roughness_to_alpha(roughness: f64) -> f64 {
+    (roughness * roughness).max(MIN_ALPHA)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Ggx {
+    pub alpha_x: f64,
+    pub alpha_y: f64,
+}
+
+impl Ggx {
+    pub fn new(alpha_x: f64, alpha_y: f64) -> Self {
+        Self {
+            alpha_x: alpha_x.max(MIN_ALPHA),
+            alpha_y: alpha_y.max(MIN_ALPHA),
+        }
+    }
+
+    /// Density of microfacet normals `h`, normalized so that `∫ D(h) h.z dh = 1`. Sampling
+    /// visible normals cancels it out of the weights, so only the tests evaluate it.
+    #[allow(dead_code)]
+    pub fn distribution(&self, h: &Vector) -> f64 {
+        if h.z <= 0. {
+            return 0.;
+        }
+        let x = h.x / self.alpha_x;
+        let y = h.y / self.alpha_y;
+        let denominator = x * x + y * y + h.z * h.z;
+        1. / (PI * self.alpha_x * self.alpha_y * denominator * denominator)
+    }
+
+    /// Smith's auxiliary function Λ for direction `w`.
+    pub fn lambda(&self, w: &Vector) -> f64 {
+        let tan2 = ((self.alpha_x * w.x).powi(2) + (self.alpha_y * w.y).powi(2)) / (w.z * w.z);
+        if !tan2.is_finite() {
+            return f64::INFINITY;
+        }
+        0.5 * ((1. + tan2).sqrt() - 1.)
+    }
+
+    /// Fraction of microfacets visible from `w`.
+    pub fn masking(&self, w: &Vector) -> f64 {
+        1. / (1. + self.lambda(w))
+    }
+
+    /// Height-correlated fraction of microfacets visible from both `wi` and `wo`.
+    pub fn masking_shadowing(&self, wi: &Vector, wo: &Vector) -> f64 {
+        1. / (1. + self.lambda(wi) + self.lambda(wo))
+    }
+
+    /// Samples a microfacet normal from those visible from `wi` (Heitz 2018), given two
+    /// uniform numbers in `[0, 1)`. `wi` must lie above the surface.
+    pub fn sample_visible_normal(&self, wi: &Vector, u1: f64, u2: f64) -> Vector {
+        // Stretch to the hemisphere configuration, where the visible normals of a unit
+        // roughness surface are easy to sample.
+        let v = Vector3::new(self.alpha_x * wi.x, self.alpha_y * wi.y, wi.z).normalize();
+
+        let length_squared = v.x * v.x + v.y * v.y;
+        let t1 = if length_squared > 0. {
+            Vector3::new(-v.y, v.x, 0.) / length_squared.sqrt()
+        } else {
+            Vector3::x()
+        };
+        let t2 = v.cross(&t1);
+
+        let r = u1.sqrt();
+        let phi = TAU * u2;
+        let p1 = r * phi.cos();
+        let s = 0.5 * (1. + v.z);
+        let p2 = (1. - s) * (1. - p1 * p1).sqrt() + s * r * phi.sin();
+        let n = t1 * p1 + t2 * p2 + v * (1. - p1 * p1 - p2 * p2).max(0.).sqrt();
+
+        Vector3::new(self.alpha_x * n.x, self.alpha_y * n.y, n.z.max(0.)).normalize()
+    }
+}
+
+/// Mirrors `wi` about the microfacet normal `h`.
+pub fn reflect(wi: &Vector, h: &Vector) -> Vector {
+    2. * wi.dot(h) * h - wi
+}
+
+/// Refracts `wi` through the microfacet normal `h` into the other side of the surface, or
+/// `None` under total internal reflection.
+pub fn refract(wi: &Vector, h: &Vector, etai_over_etat: f64) -> Option<Vector> {
+    let cos_i = wi.dot(h);
+    let sin2_t = etai_over_etat * etai_over_etat * (1. - cos_i * cos_i);
+    if sin2_t >= 1. {
+        return None;
+    }
+    let cos_t = (1. - sin2_t).sqrt();
+    Some((etai_over_etat * cos_i - cos_t) * h - etai_over_etat * wi)
+}
+
+/// Unpolarized Fresnel reflectance at the boundary between two dielectrics, where
+/// `etai_over_etat` is the ratio of the indices of refraction on the incident and transmitted
+/// sides. Returns 1 under total internal reflection.
+pub fn fresnel_dielectric(cos_theta: f64, etai_over_etat: f64) -> f64 {
+    let cos_i = cos_theta.clamp(0., 1.);
+    let sin2_t = etai_over_etat * etai_over_etat * (1. - cos_i * cos_i);
+    if sin2_t >= 1. {
+        return 1.;
+    }
+    let cos_t = (1. - sin2_t).sqrt();
+
+    let rs = (etai_over_etat * cos_i - cos_t) / (etai_over_etat * cos_i + cos_t);
+    let rp = (cos_i - etai_over_etat * cos_t) / (cos_i + etai_over_etat * cos_t);
+    0.5 * (rs * rs + rp * rp)
+}
+
+/// Unpolarized Fresnel reflectance from air onto a conductor with complex index of
+/// refraction `eta + i k`, per channel, at incidence cosine `cos_theta`.
+pub fn fresnel_conductor(cos_theta: f64, eta: &Vector, k: &Vector) -> Vector {
+    let cos2 = cos_theta.clamp(0., 1.).powi(2);
+    let sin2 = 1. - cos2;
+
+    Vector3::from_fn(|channel, _| {
+        let (eta, k) = (eta[channel], k[channel]);
+        let t0 = eta * eta - k * k - sin2;
+        let a2_plus_b2 = (t0 * t0 + 4. * eta * eta * k * k).sqrt();
+        let a = (0.5 * (a2_plus_b2 + t0)).max(0.).sqrt();
+
+        let t1 = a2_plus_b2 + cos2;
+        let t2 = 2. * cos_theta * a;
+        let rs = (t1 - t2) / (t1 + t2);
+
+        let t3 = cos2 * a2_plus_b2 + sin2 * sin2;
+        let t4 = t2 * sin2;
+        let rp = rs * (t3 - t4) / (t3 + t4);
+
+        0.5 * (rs + rp)
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Ggx, ShadingFrame, fresnel_conductor, fresnel_dielectric};
+    use crate::data::vector3::random_in_unit_sphere;
+    use approx::assert_abs_diff_eq;
+    use nalgebra::Vector3;
+    use std::f64::consts::TAU;
+
+    #[test]
+    fn distribution_projects_to_unit_area() {
+        // Integrate D(h) h.z over the hemisphere with uniform samples (pdf 1 / 2π).
+        for ggx in [Ggx::new(0.3, 0.3), Ggx::new(0.6, 0.15)] {
+            let samples = 400_000;
+            let total: f64 = (0..samples)
+                .map(|_| {
+                    let h = random_in_unit_sphere().normalize();
+                    let h = Vector3::new(h.x, h.y, h.z.abs());
+                    ggx.distribution(&h) * h.z * TAU
+                })
+                .sum();
+            assert_abs_diff_eq!(total / samples as f64, 1., epsilon = 0.03);
+        }
+    }
+
+    #[test]
+    fn visible_normals_face_the_viewer() {
+        let ggx = Ggx::new(0.5, 0.2);
+        let frame = ShadingFrame::new(&Vector3::new(0.3, 0.8, -0.52).normalize());
+        let wi = frame.to_local(&Vector3::new(0., 1., 0.));
+        assert!(wi.z > 0.);
+
+        let mut reflected_up = 0;
+        for _ in 0..10_000 {
+            let h = ggx.sample_visible_normal(&wi, rand::random(), rand::random());
+            assert_abs_diff_eq!(h.norm(), 1., epsilon = 1e-9);
+            assert!(h.z >= 0. && wi.dot(&h) >= 0.);
+            let wo = 2. * wi.dot(&h) * h - wi;
+            reflected_up += (wo.z > 0.) as usize;
+        }
+        assert!(reflected_up > 8_000);
+    }
+
+    #[test]
+    fn conductor_fresnel_matches_normal_incidence_formula() {
+        let eta = Vector3::new(0.2, 0.9, 1.1);
+        let k = Vector3::new(3.9, 2.5, 2.1);
+        let at_normal = fresnel_conductor(1., &eta, &k);
+        for channel in 0..3 {
+            let (n, k) = (eta[channel], k[channel]);
+            let expected = ((n - 1.).powi(2) + k * k) / ((n + 1.).powi(2) + k * k);
+            assert_abs_diff_eq!(at_normal[channel], expected, epsilon = 1e-12);
+        }
+        // Every conductor turns into a perfect mirror at grazing incidence.
+        assert_abs_diff_eq!(fresnel_conductor(0., &eta, &k), Vector3::repeat(1.));
+    }
+
+    #[test]
+    fn dielectric_fresnel_limits() {
+        let at_normal = ((1.5_f64 - 1.) / (1.5 + 1.)).powi(2);
+        assert_abs_diff_eq!(fresnel_dielectric(1., 1. / 1.5), at_normal, epsilon = 1e-12);
+        assert_abs_diff_eq!(fresnel_dielectric(1., 1.5), at_normal, epsilon = 1e-12);
+        assert_abs_diff_eq!(fresnel_dielectric(0., 1. / 1.5), 1.);
+        // Past the critical angle of about 41.8° inside glass, everything reflects.
+        assert_eq!(fresnel_dielectric(0.7, 1.5), 1.);
+        assert!(fresnel_dielectric(0.8, 1.5) < 1.);
+    }
+}
@@ -0,0 +1,271 @@
+use crate::data::color64::{BLACK, Color64};
+use crate::data::point64::Point64;
+use crate::data::ray::Ray;
+use crate::data::vector3::{Vector, random_in_unit_sphere};
+use crate::hittables::HitRecord;
+use crate::materials::ScatterRecord;
+use crate::materials::microfacet::{
+    Ggx, ShadingFrame, fresnel_dielectric, reflect, refract, roughness_to_alpha,
+};
+use crate::textures::Texture;
+use nalgebra::Vector3;
+use rand_distr::num_traits::Inv;
+use std::f64::consts::PI;
+
+/// Index of refraction of the clear coat, a typical lacquer.
+const CLEARCOAT_IOR: f64 = 1.5;
+
+/// Disney-style "uber" material blending diffuse, metal, glass, sheen and clear coat lobes
+/// from a handful of artist-friendly parameters. Scalar parameters are read from the red
+/// channel of their texture, so grayscale maps work as they are.
+///
+/// One lobe is sampled per scatter: the clear coat with probability equal to its Fresnel
+/// reflectance, then metal, glass or the opaque dielectric base by their blend weights, and
+/// for the base either its specular reflection or the diffuse layer underneath by Fresnel.
+#[derive(Clone)]
+pub struct Principled {
+    pub base_color: Texture,
+    /// Blend from dielectric (0) to metal (1), where `base_color` tints the reflection.
+    pub metallic: Texture,
+    pub roughness: Texture,
+    /// Normal-incidence reflectance of the dielectric base, scaled so that 0.5 is 4%.
+    pub specular: Texture,
+    /// Color of the fabric-like rim over the diffuse base at grazing angles.
+    pub sheen: Texture,
+    /// Strength of a white coat on top of every other lobe.
+    pub clearcoat: Texture,
+    pub clearcoat_roughness: Texture,
+    /// Blend of the dielectric base from opaque (0) to glass tinted by `base_color` (1).
+    pub transmission: Texture,
+    pub index_of_refraction: f64,
+    pub emission: Texture,
+}
+
+impl Default for Principled {
+    fn default() -> Self {
+        Self {
+            base_color: Texture::solid(Color64::gray(0.8)),
+            metallic: constant(0.),
+            roughness: constant(0.5),
+            specular: constant(0.5),
+            sheen: Texture::solid(BLACK),
+            clearcoat: constant(0.),
+            clearcoat_roughness: constant(0.03),
+            transmission: constant(0.),
+            index_of_refraction: 1.5,
+            emission: Texture::solid(BLACK),
+        }
+    }
+}
+
+fn constant(value: f64) -> Texture {
+    Texture::solid(Color64::gray(value))
+}
+
+fn scalar(texture: &Texture, hit_record: &HitRecord) -> f64 {
+    texture
+        .value(hit_record.u, hit_record.v, &hit_record.location)
+        .r()
+        .clamp(0., 1.)
+}
+
+fn schlick_weight(cos_theta: f64) -> f64 {
+    (1. - cos_theta.clamp(0., 1.)).powi(5)
+}
+
+/// Weight of a direction `wo` sampled from the visible normals of `distribution`.
+fn microfacet_weight(distribution: &Ggx, wi: &Vector, wo: &Vector) -> f64 {
+    distribution.masking_shadowing(wi, wo) / distribution.masking(wi)
+}
+
+impl Principled {
+    pub fn scatter(&self, ray_in: &Ray, hit_record: &HitRecord) -> Option<ScatterRecord> {
+        let frame = ShadingFrame::new(&hit_record.normal.0);
+        let wi = frame.to_local(&-ray_in.direction.0.normalize());
+        if wi.z <= 0. {
+            return None;
+        }
+        let scatter = |attenuation: Vector, wo: Vector| {
+            Some(ScatterRecord {
+                attenuation: Color64(attenuation),
+                scattered: Ray {
+                    origin: hit_record.location,
+                    direction: Point64(frame.to_world(&wo)),
+                    exposure_time: ray_in.exposure_time,
+                },
+            })
+        };
+
+        // The coat lies on the outside, so light leaving a transmissive object passes under it.
+        let clearcoat = scalar(&self.clearcoat, hit_record);
+        if clearcoat > 0. && hit_record.front_face {
+            let alpha = roughness_to_alpha(scalar(&self.clearcoat_roughness, hit_record));
+            let coat = Ggx::new(alpha, alpha);
+            let h = coat.sample_visible_normal(&wi, rand::random(), rand::random());
+            let reflectance =
+                clearcoat * fresnel_dielectric(wi.dot(&h), hit_record.outside_ior / CLEARCOAT_IOR);
+            if reflectance > rand::random() {
+                let wo = reflect(&wi, &h);
+                if wo.z <= 0. {
+                    return None;
+                }
+                return scatter(Vector::repeat(microfacet_weight(&coat, &wi, &wo)), wo);
+            }
+        }
+
+        let base_color = self
+            .base_color
+            .value(hit_record.u, hit_record.v, &hit_record.location)
+            .0;
+        let metallic = scalar(&self.metallic, hit_record);
+        let transmission = scalar(&self.transmission, hit_record);
+        let alpha = roughness_to_alpha(scalar(&self.roughness, hit_record));
+        let distribution = Ggx::new(alpha, alpha);
+        let h = distribution.sample_visible_normal(&wi, rand::random(), rand::random());
+        let cos_i = wi.dot(&h);
+
+        let lobe: f64 = rand::random();
+        if lobe < metallic {
+            let wo = reflect(&wi, &h);
+            if wo.z <= 0. {
+                return None;
+            }
+            let fresnel = base_color.map(|f0| f0 + (1. - f0) * schlick_weight(cos_i));
+            return scatter(fresnel * microfacet_weight(&distribution, &wi, &wo), wo);
+        }
+
+        if lobe < metallic + (1. - metallic) * transmission {
+            let relative_ior = self.index_of_refraction / hit_record.outside_ior;
+            let refraction_ratio = if hit_record.front_face {
+                relative_ior.inv()
+            } else {
+                relative_ior
+            };
+            return if fresnel_dielectric(cos_i, refraction_ratio) > rand::random() {
+                let wo = reflect(&wi, &h);
+                if wo.z <= 0. {
+                    return None;
+                }
+                scatter(
+                    Vector::repeat(microfacet_weight(&distribution, &wi, &wo)),
+                    wo,
+                )
+            } else {
+                let wo = refract(&wi, &h, refraction_ratio)?;
+                if wo.z >= 0. {
+                    return None;
+                }
+                scatter(base_color * microfacet_weight(&distribution, &wi, &wo), wo)
+            };
+        }
+
+        let f0 = 0.08 * scalar(&self.specular, hit_record);
+        if f0 + (1. - f0) * schlick_weight(cos_i) > rand::random() {
+            let wo = reflect(&wi, &h);
+            if wo.z <= 0. {
+                return None;
+            }
+            return scatter(
+                Vector::repeat(microfacet_weight(&distribution, &wi, &wo)),
+                wo,
+            );
+        }
+
+        // Cosine-weighted like `Lambertian`, so the diffuse weight is just the base color.
+        let mut wo = Vector3::z() + random_in_unit_sphere();
+        if wo.z <= 1e-8 {
+            wo = Vector3::z();
+        }
+        let wo = wo.normalize();
+        let cos_d = wo.dot(&(wi + wo).normalize());
+        let sheen = self
+            .sheen
+            .value(hit_record.u, hit_record.v, &hit_record.location)
+            .0;
+        scatter(base_color + sheen * PI * schlick_weight(cos_d), wo)
+    }
+
+    pub fn emitted(&self, u: f64, v: f64, point: &Point64) -> Color64 {
+        self.emission.value(u, v, point)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Principled, constant};
+    use crate::data::color64::Color64;
+    use crate::materials::Material;
+    use crate::materials::microfacet::fresnel_dielectric;
+    use crate::materials::test_fixtures::{average_attenuation, hit_at_origin, ray_towards_origin};
+    use crate::textures::Texture;
+    use approx::assert_abs_diff_eq;
+
+    fn average_weight(principled: Principled, samples: usize) -> Color64 {
+        let material = Material::Principled(Box::new(principled));
+        let hit_record = hit_at_origin(&material, true);
+        average_attenuation(&material, &ray_towards_origin(45.), &hit_record, samples)
+    }
+
+    /// A white surface without sheen reflects at most what arrives, whichever lobes mix.
+    #[test]
+    fn white_surfaces_conserve_energy() {
+        for (metallic, transmission, clearcoat) in
+            [(0., 0., 0.), (1., 0., 0.), (0., 1., 0.), (0.5, 0.5, 1.)]
+        {
+            let average = average_weight(
+                Principled {
+                    base_color: Texture::solid(Color64::gray(1.)),
+                    metallic: constant(metallic),
+                    roughness: constant(0.3),
+                    transmission: constant(transmission),
+                    clearcoat: constant(clearcoat),
+                    ..Default::default()
+                },
+                20_000,
+            );
+            assert!(average.r() <= 1.01 && average.r() > 0.9, "{average:?}");
+        }
+    }
+
+    #[test]
+    fn smooth_metal_reflects_its_base_color() {
+        let gold = Color64::new(1., 0.78, 0.34);
+        let average = average_weight(
+            Principled {
+                base_color: Texture::solid(gold),
+                metallic: constant(1.),
+                roughness: constant(0.),
+                ..Default::default()
+            },
+            1_000,
+        );
+        // At 45° Schlick's approximation has barely moved away from the base color.
+        assert_abs_diff_eq!(average.0, gold.0, epsilon = 0.01);
+    }
+
+    #[test]
+    fn light_leaving_through_the_back_face_skips_the_coat() {
+        let material = Material::Principled(Box::new(Principled {
+            roughness: constant(0.),
+            transmission: constant(1.),
+            clearcoat: constant(1.),
+            ..Default::default()
+        }));
+        let hit_record = hit_at_origin(&material, false);
+        let ray = ray_towards_origin(0.);
+
+        let samples = 20_000;
+        let reflections = (0..samples)
+            .filter(|_| {
+                let scatter = material.scatter(&ray, &hit_record).unwrap();
+                scatter.scattered.direction.y() > 0.
+            })
+            .count();
+        // Only the glass itself reflects, from its inside.
+        assert_abs_diff_eq!(
+            reflections as f64 / samples as f64,
+            fresnel_dielectric(1., 1.5),
+            epsilon = 0.01
+        );
+    }
+}
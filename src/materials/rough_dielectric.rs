@@ -0,0 +1,126 @@
+use crate::data::color64::Color64;
+use crate::data::point64::Point64;
+use crate::data::ray::Ray;
+use crate::hittables::HitRecord;
+use crate::materials::ScatterRecord;
+use crate::materials::microfacet::{
+    Ggx, ShadingFrame, fresnel_dielectric, reflect, refract, roughness_to_alpha,
+};
+use rand_distr::num_traits::Inv;
+
+/// Frosted glass: a GGX microfacet surface that reflects or transmits through each sampled
+/// microfacet in proportion to the exact dielectric Fresnel term. At zero roughness it behaves
+/// like [`Dielectric`](crate::materials::dielectric::Dielectric).
+#[derive(Clone)]
+pub struct RoughDielectric {
+    pub index_of_refraction: f64,
+    pub distribution: Ggx,
+}
+
+impl RoughDielectric {
+    pub fn new(index_of_refraction: f64, roughness: f64) -> Self {
+        let alpha = roughness_to_alpha(roughness);
+        Self {
+            index_of_refraction,
+            distribution: Ggx::new(alpha, alpha),
+        }
+    }
+
+    /// Samples a visible microfacet normal, then picks reflection or refraction with
+    /// probability equal to its Fresnel reflectance. Fresnel and the microfacet density
+    /// cancel against the pdf for both events, leaving the weight `G2 / G1`.
+    pub fn scatter(&self, ray_in: &Ray, hit_record: &HitRecord) -> Option<ScatterRecord> {
+        let relative_ior = self.index_of_refraction / hit_record.outside_ior;
+        let refraction_ratio = if hit_record.front_face {
+            relative_ior.inv()
+        } else {
+            relative_ior
+        };
+
+        let frame = ShadingFrame::new(&hit_record.normal.0);
+        let wi = frame.to_local(&-ray_in.direction.0.normalize());
+        if wi.z <= 0. {
+            return None;
+        }
+
+        let microfacet_normal =
+            self.distribution
+                .sample_visible_normal(&wi, rand::random(), rand::random());
+        let reflectance = fresnel_dielectric(wi.dot(&microfacet_normal), refraction_ratio);
+
+        let wo = if reflectance > rand::random() {
+            let reflected = reflect(&wi, &microfacet_normal);
+            if reflected.z <= 0. {
+                return None;
+            }
+            reflected
+        } else {
+            // Fresnel is 1 under total internal reflection, so refraction cannot fail here.
+            let refracted = refract(&wi, &microfacet_normal, refraction_ratio)?;
+            if refracted.z >= 0. {
+                return None;
+            }
+            refracted
+        };
+
+        let shadowing =
+            self.distribution.masking_shadowing(&wi, &wo) / self.distribution.masking(&wi);
+
+        Some(ScatterRecord {
+            attenuation: Color64::gray(shadowing),
+            scattered: Ray {
+                origin: hit_record.location,
+                direction: Point64(frame.to_world(&wo)),
+                exposure_time: ray_in.exposure_time,
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::RoughDielectric;
+    use crate::materials::Material;
+    use crate::materials::microfacet::fresnel_dielectric;
+    use crate::materials::test_fixtures::{hit_at_origin, ray_towards_origin};
+    use approx::assert_abs_diff_eq;
+    use nalgebra::Vector3;
+
+    #[test]
+    fn smooth_limit_matches_snell_and_fresnel() {
+        let glass = RoughDielectric::new(1.5, 0.);
+        let material = Material::RoughDielectric(glass.clone());
+        let hit_record = hit_at_origin(&material, true);
+        let ray = ray_towards_origin(50.);
+        let (sin_i, cos_i) = 50_f64.to_radians().sin_cos();
+
+        let sin_t = sin_i / 1.5;
+        let mirrored = Vector3::new(0., cos_i, sin_i);
+        let refracted = Vector3::new(0., -(1. - sin_t * sin_t).sqrt(), sin_t);
+
+        let samples = 20_000;
+        let mut reflections = 0;
+        // GGX has long tails, so even the smallest roughness strays now and then.
+        let mut strays = 0;
+        for _ in 0..samples {
+            let scatter = glass.scatter(&ray, &hit_record).unwrap();
+            assert_abs_diff_eq!(scatter.attenuation.r(), 1., epsilon = 1e-3);
+            let direction = scatter.scattered.direction.0.normalize();
+            let ideal = if direction.y > 0. {
+                reflections += 1;
+                mirrored
+            } else {
+                refracted
+            };
+            if (direction - ideal).norm() > 1e-2 {
+                strays += 1;
+            }
+        }
+        assert!(strays < samples / 500, "{strays} strays");
+        assert_abs_diff_eq!(
+            reflections as f64 / samples as f64,
+            fresnel_dielectric(cos_i, 1. / 1.5),
+            epsilon = 0.01
+        );
+    }
+}
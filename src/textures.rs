@@ -22,6 +22,13 @@ pub enum Texture {
     },
     Noise(Box<Noise>),
     Image(ImageTexture),
+    /// Squares laid out in texture space rather than 3D space, `scale` per unit of `u` and
+    /// `v`, so they follow the surface's parameterization.
+    UvChecker {
+        odd: Box<Texture>,
+        even: Box<Texture>,
+        scale: f64,
+    },
 }
 
 impl Texture {
@@ -44,6 +51,38 @@ impl Texture {
             }
             Texture::Noise(n) => n.value(u, v, point),
             Texture::Image(img) => img.value(u, v, point),
+            Texture::UvChecker { odd, even, scale } => {
+                let cell = (u * scale).floor() + (v * scale).floor();
+                if cell.rem_euclid(2.) == 1. {
+                    odd.value(u, v, point)
+                } else {
+                    even.value(u, v, point)
+                }
+            }
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::Texture;
+    use crate::data::color64::Color64;
+    use crate::data::point64::Point64;
+
+    #[test]
+    fn uv_checker_tiles_in_texture_space() {
+        let checker = Texture::UvChecker {
+            odd: Box::new(Texture::solid(Color64::gray(0.))),
+            even: Box::new(Texture::solid(Color64::gray(1.))),
+            scale: 2.,
+        };
+        let at = |u: f64, v: f64| checker.value(u, v, &Point64::default()).r();
+
+        assert_eq!(at(0.1, 0.1), 1.);
+        assert_eq!(at(0.6, 0.1), 0.);
+        assert_eq!(at(0.6, 0.6), 1.);
+        // Planar coordinates run negative too.
+        assert_eq!(at(-0.1, 0.1), 0.);
+        assert_eq!(at(-0.1, -0.1), 1.);
+    }
+}
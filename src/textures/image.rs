@@ -3,10 +3,12 @@ use crate::data::point64::Point64;
 use image::{DynamicImage, GenericImageView, ImageReader};
 use nalgebra::Vector3;
 use std::path::Path;
+use std::sync::Arc;
 
+/// Cloning shares the decoded image rather than copying it.
 #[derive(Clone)]
 pub struct ImageTexture {
-    image: Option<DynamicImage>,
+    image: Option<Arc<DynamicImage>>,
 }
 
 impl ImageTexture {
@@ -18,7 +20,9 @@ impl ImageTexture {
                 let img_decoded = image_file.decode();
 
                 match img_decoded {
-                    Ok(image) => ImageTexture { image: Some(image) },
+                    Ok(image) => ImageTexture {
+                        image: Some(Arc::new(image)),
+                    },
                     Err(e) => {
                         eprintln!("cold not decode image: {}", e);
                         ImageTexture { image: None }
@@ -1,8 +1,12 @@
 pub mod args;
+pub mod bench;
 pub mod interactive;
+pub mod mesh;
+pub mod mesh_cache;
 pub mod obj;
 mod ppm;
 pub mod render;
+pub mod strands;
 pub mod worlds;
 
 pub const EPSILON: f64 = 1e-8;
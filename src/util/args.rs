@@ -1,11 +1,21 @@
+use crate::hittables::bounded_volume_hierarchy::{BvhBuildOptions, BvhLayout, BvhStrategy};
+use crate::util::render::RenderMode;
 use args::Args;
 use getopts::Occur;
 
+pub const SUBCOMMANDS_HELP: &str = "Subcommands:
+    bench               Compare BVH builders and layouts on the Utah teapot
+    build-cache -w N    Build the on-disk mesh and BVH caches used by world N
+                        (stored in $RAY_TRACER_CACHE_DIR when set)";
+
 pub struct ProgramOptions {
     pub use_bvh: bool,
+    pub bvh_options: BvhBuildOptions,
     pub world_choice: u8,
     pub samples_per_pixel: u32,
     pub interactive: bool,
+    pub turntable_frames: u32,
+    pub render_mode: RenderMode,
     pub help: bool,
     pub help_str: String,
 }
@@ -28,8 +38,8 @@ pub fn parse_args(input: &[String]) -> anyhow::Result<ProgramOptions> {
     args.option(
         "w",
         "world_choice",
-        "Select world (0–10; 9 = Utah teapots OBJ, 10 = Cornell smoke)",
-        "[0-10]",
+        "Select world (0–21; 9 = Utah teapots OBJ, 10 = Cornell smoke, 11 = tori, 12 = CSG, 13 = SDFs, 14 = terrain, 15 = grass and fur, 16 = metaballs, 17 = displacement, 18 = GGX conductors, 19 = frosted glass, 20 = principled material, 21 = coatings)",
+        "[0-21]",
         Occur::Optional,
         Some(String::from("0")),
     );
@@ -42,15 +52,84 @@ pub fn parse_args(input: &[String]) -> anyhow::Result<ProgramOptions> {
         Some(String::from("100")),
     );
 
+    args.option(
+        "t",
+        "turntable",
+        "Render N frames (output_000.png, ...) turning each instanced object once; world 9 only",
+        "N",
+        Occur::Optional,
+        Some(String::from("0")),
+    );
+    args.option(
+        "",
+        "render_mode",
+        "recursive (one path at a time) or wavefront (all paths of a task bounce by bounce, sorted by ray direction and grouped by material)",
+        "[recursive|wavefront]",
+        Occur::Optional,
+        Some(RenderMode::default().to_string()),
+    );
+    args.option(
+        "b",
+        "bvh_builder",
+        "BVH builder for worlds 0 and 8: sah (surface area heuristic) or median (random axis median split)",
+        "[sah|median]",
+        Occur::Optional,
+        Some(String::from("sah")),
+    );
+    args.option(
+        "",
+        "bvh_layout",
+        "BVH storage for worlds 0 and 8: linear (flat node array) or tree (boxed nodes)",
+        "[linear|tree]",
+        Occur::Optional,
+        Some(String::from("linear")),
+    );
+    args.option(
+        "",
+        "bvh_leaf_size",
+        "Most primitives per BVH leaf with the sah builder",
+        "N",
+        Occur::Optional,
+        Some(BvhBuildOptions::default().max_leaf_size.to_string()),
+    );
+
+    args.option(
+        "",
+        "bvh_threads",
+        "Threads used to build BVHs for worlds 0 and 8 (default: all CPUs)",
+        "N",
+        Occur::Optional,
+        Some(BvhBuildOptions::default().threads.to_string()),
+    );
+    args.option(
+        "",
+        "bvh_seed",
+        "Seed for the median builder's split axes; equal seeds give equal trees",
+        "N",
+        Occur::Optional,
+        Some(String::from("0")),
+    );
+
     args.parse(input)?;
 
     let samples_per_pixel: u32 = args.value_of("samples_per_pixel")?;
+    let bvh_options = BvhBuildOptions {
+        strategy: args.value_of::<BvhStrategy>("bvh_builder")?,
+        layout: args.value_of::<BvhLayout>("bvh_layout")?,
+        max_leaf_size: args.value_of("bvh_leaf_size")?,
+        threads: args.value_of("bvh_threads")?,
+        seed: args.value_of("bvh_seed")?,
+        ..BvhBuildOptions::default()
+    };
 
     Ok(ProgramOptions {
         use_bvh: !args.value_of("flat")?,
+        bvh_options,
         world_choice: args.value_of("world_choice")?,
         samples_per_pixel,
         interactive: args.value_of("interactive")?,
+        turntable_frames: args.value_of("turntable")?,
+        render_mode: args.value_of::<RenderMode>("render_mode")?,
         help: args.value_of("help")?,
         help_str: args.full_usage(),
     })
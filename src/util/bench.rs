@@ -0,0 +1,277 @@
+use crate::camera::Camera;
+use crate::data::color64::Color64;
+use crate::data::point64::Point64;
+use crate::data::ray::Ray;
+use crate::data::ray_packet::{PACKET_WIDTH, RayPacket};
+use crate::data::storage::Real;
+use crate::data::vector3::random_in_unit_sphere;
+use crate::hittables::Hittable;
+use crate::hittables::bounded_volume_hierarchy::{
+    BoundedVolumeHierarchy, BvhBuildOptions, BvhLayout, BvhStrategy,
+};
+use crate::hittables::hittable_vec::HittableVec;
+use crate::hittables::plane::Plane;
+use crate::materials::Material;
+use crate::materials::lambertian::Lambertian;
+use crate::textures::Texture;
+use crate::util::obj::{Subdivision, load_obj_triangles};
+use crate::util::render::{RenderMode, render_rows};
+use nalgebra::Vector3;
+use std::array;
+use std::path::Path;
+use std::time::Instant;
+
+const PRIMARY_RAYS_PER_SIDE: u32 = 400;
+const RANDOM_RAYS: usize = 160_000;
+const MIB: f64 = (1 << 20) as f64;
+const RENDER_SIDE: u32 = 200;
+const RENDER_SAMPLES: u32 = 8;
+const RENDER_MAX_DEPTH: i32 = 50;
+
+/// `ray-tracer bench`: builds a hierarchy over the subdivided Utah teapot with each builder
+/// and layout, then times closest-hit queries for a grid of camera rays and for incoherent
+/// rays between random points around the teapot, and occlusion queries along the latter.
+/// Every configuration must find the same hits as the first, and the flattened layout also
+/// traces the camera rays in packets. Finally the teapot is rendered in both render modes.
+/// Build with the `f32-geometry` feature to compare memory and speed with single-precision
+/// storage.
+pub fn run_bench() -> anyhow::Result<()> {
+    let material = Material::Lambertian(Lambertian {
+        albedo: Texture::solid(Color64::gray(0.5)),
+    });
+    let triangles = load_obj_triangles(
+        Path::new("resources/teapot.obj"),
+        material,
+        1.,
+        Vector3::zeros(),
+        Subdivision {
+            levels: 2,
+            crease_angle: Some(60.),
+        },
+    )?;
+
+    let bounds = HittableVec {
+        hittables: triangles.clone(),
+    }
+    .bounding_box(0., 1.)
+    .expect("teapot has bounds");
+    let center = bounds.centroid();
+    let radius = (bounds.maximum.0 - bounds.minimum.0).norm();
+
+    let camera = Camera::new(
+        center + Point64::new(0.3, 0.4, 1.) * radius,
+        center,
+        Vector3::y(),
+        35.,
+        1.,
+        0.,
+        1.,
+        0.0..1.0,
+    );
+    let side = (PRIMARY_RAYS_PER_SIDE - 1) as f64;
+    let primary: Vec<Ray> = (0..PRIMARY_RAYS_PER_SIDE)
+        .flat_map(|y| (0..PRIMARY_RAYS_PER_SIDE).map(move |x| (x as f64 / side, y as f64 / side)))
+        .map(|(s, t)| camera.get_ray(s, t))
+        .collect();
+    let random: Vec<Ray> = (0..RANDOM_RAYS)
+        .map(|_| {
+            let origin = center + Point64(random_in_unit_sphere()) * radius;
+            let target = center + Point64(random_in_unit_sphere()) * (0.5 * radius);
+            Ray {
+                origin,
+                direction: target - origin,
+                exposure_time: 0.,
+            }
+        })
+        .collect();
+
+    println!(
+        "{} triangles, {}-bit geometry",
+        triangles.len(),
+        8 * size_of::<Real>()
+    );
+
+    let configurations = [
+        ("median, tree", BvhStrategy::RandomMedian, BvhLayout::Tree),
+        ("SAH, tree", BvhStrategy::Sah, BvhLayout::Tree),
+        ("SAH, linear", BvhStrategy::Sah, BvhLayout::Linear),
+    ];
+    let mut reference: Option<Vec<Option<f64>>> = None;
+
+    for (name, strategy, layout) in configurations {
+        let options = BvhBuildOptions {
+            strategy,
+            layout,
+            ..BvhBuildOptions::default()
+        };
+        let (bvh, stats) = BoundedVolumeHierarchy::build(&triangles, 0., 1., &options);
+        println!("{name}: {stats}");
+
+        let mut hits = vec![];
+        for (kind, rays) in [("primary", &primary), ("random", &random)] {
+            let (seconds, mut kind_hits) = trace(&bvh, rays);
+            println!(
+                "  {kind}: {:.2} Mrays/s ({} of {} hit)",
+                rays.len() as f64 / seconds / 1e6,
+                kind_hits.iter().flatten().count(),
+                rays.len(),
+            );
+            hits.append(&mut kind_hits);
+        }
+
+        // The random rays end at their targets, so as shadow rays they ask whether the
+        // segment between two points is blocked.
+        let start = Instant::now();
+        let blocked: Vec<bool> = random
+            .iter()
+            .map(|ray| bvh.occluded(ray, 0.001, 1.))
+            .collect();
+        let seconds = start.elapsed().as_secs_f64();
+        println!(
+            "  shadow: {:.2} Mrays/s ({} of {} blocked)",
+            random.len() as f64 / seconds / 1e6,
+            blocked.iter().filter(|&&b| b).count(),
+            random.len(),
+        );
+        let disagreements = random
+            .iter()
+            .zip(&blocked)
+            .filter(|(ray, blocked)| bvh.is_hit_by(ray, 0.001, 1.).is_some() != **blocked)
+            .count();
+        if disagreements > 0 {
+            anyhow::bail!("{name}: occlusion disagrees with closest hits on {disagreements} rays");
+        }
+
+        if let Hittable::LinearBvh(linear) = &bvh {
+            let node_bytes = size_of_val(linear.nodes());
+            let primitive_bytes = size_of_val(linear.primitives());
+            println!(
+                "  memory: {:.1} MiB ({:.1} MiB nodes, {:.1} MiB primitives)",
+                (node_bytes + primitive_bytes) as f64 / MIB,
+                node_bytes as f64 / MIB,
+                primitive_bytes as f64 / MIB,
+            );
+        }
+
+        if layout == BvhLayout::Linear {
+            let (seconds, packet_hits) = trace_packets(&bvh, &primary);
+            println!(
+                "  primary, {PACKET_WIDTH}-wide packets: {:.2} Mrays/s",
+                primary.len() as f64 / seconds / 1e6,
+            );
+            if packet_hits[..] != hits[..primary.len()] {
+                anyhow::bail!("{name} finds different hits for packets and single rays");
+            }
+        }
+
+        match &reference {
+            None => reference = Some(hits),
+            Some(expected) => {
+                let mismatches = expected
+                    .iter()
+                    .zip(&hits)
+                    .filter(|(a, b)| match (a, b) {
+                        (Some(a), Some(b)) => (a - b).abs() > 1e-9 * a.abs().max(1.),
+                        (a, b) => a.is_some() != b.is_some(),
+                    })
+                    .count();
+                if mismatches > 0 {
+                    anyhow::bail!(
+                        "{name} disagrees with {} on {mismatches} rays",
+                        configurations[0].0
+                    );
+                }
+            }
+        }
+    }
+
+    bench_render_modes(&triangles, &camera, bounds.minimum.y())
+}
+
+/// Renders the teapot standing on a plane with each [`RenderMode`], printing the time taken
+/// and the mean pixel value, which should agree to within noise.
+fn bench_render_modes(triangles: &[Hittable], camera: &Camera, ground: f64) -> anyhow::Result<()> {
+    let world = Hittable::HittableVec(HittableVec {
+        hittables: vec![
+            BoundedVolumeHierarchy::create_bvh(triangles, 0., 1.),
+            Hittable::Plane(Plane::horizontal(
+                ground,
+                Material::Lambertian(Lambertian {
+                    albedo: Texture::solid(Color64::gray(0.7)),
+                }),
+            )),
+        ],
+    });
+    let background = Color64::new(0.7, 0.8, 1.);
+
+    for mode in [RenderMode::Recursive, RenderMode::Wavefront] {
+        let start = Instant::now();
+        let rows = render_rows(
+            mode,
+            camera,
+            &world,
+            &background,
+            0..RENDER_SIDE,
+            RENDER_SIDE,
+            RENDER_SIDE,
+            RENDER_MAX_DEPTH,
+            RENDER_SAMPLES,
+            &mut rand::rng(),
+        );
+        let seconds = start.elapsed().as_secs_f64();
+        let samples = (RENDER_SIDE * RENDER_SIDE * RENDER_SAMPLES) as f64;
+        let mean = rows
+            .iter()
+            .flatten()
+            .fold(Vector3::zeros(), |sum, c| sum + c.0)
+            / samples;
+        println!(
+            "{mode} render: {:.2} Msamples/s, mean color ({:.4}, {:.4}, {:.4})",
+            samples / seconds / 1e6,
+            mean.x,
+            mean.y,
+            mean.z,
+        );
+    }
+
+    Ok(())
+}
+
+/// Seconds taken to find the closest hit of every ray, and the hit distances.
+fn trace(hittable: &Hittable, rays: &[Ray]) -> (f64, Vec<Option<f64>>) {
+    let start = Instant::now();
+    let hits = rays
+        .iter()
+        .map(|ray| {
+            hittable
+                .is_hit_by(ray, 0.001, f64::INFINITY)
+                .map(|hr| hr.value)
+        })
+        .collect();
+    (start.elapsed().as_secs_f64(), hits)
+}
+
+/// [`trace`] with the rays taken [`PACKET_WIDTH`] at a time, dropping any remainder.
+fn trace_packets(hittable: &Hittable, rays: &[Ray]) -> (f64, Vec<Option<f64>>) {
+    let packets: Vec<RayPacket> = rays
+        .chunks_exact(PACKET_WIDTH)
+        .map(|chunk| {
+            RayPacket::new(array::from_fn(|lane| Ray {
+                origin: chunk[lane].origin,
+                direction: chunk[lane].direction,
+                exposure_time: chunk[lane].exposure_time,
+            }))
+        })
+        .collect();
+
+    let start = Instant::now();
+    let hits = packets
+        .iter()
+        .flat_map(|packet| {
+            hittable
+                .packet_hits(packet, 0.001, [f64::INFINITY; PACKET_WIDTH])
+                .map(|hit| hit.map(|hr| hr.value))
+        })
+        .collect();
+    (start.elapsed().as_secs_f64(), hits)
+}
@@ -8,7 +8,7 @@ use nalgebra::{Rotation3, Unit, Vector3};
 use crate::camera::Camera;
 use crate::data::color64::Color64;
 use crate::data::point64::Point64;
-use crate::util::render::render_frame;
+use crate::util::render::{RenderMode, render_frame};
 use crate::util::worlds::World;
 
 const PITCH_LIMIT: f64 = 1.553;
@@ -23,7 +23,7 @@ const GROUND_MARGIN: f64 = 0.1;
 
 /// Returns the minimum allowed pitch so the camera stays above `world.ground_y`.
 fn pitch_floor(world: &World, orbit: &OrbitState) -> f64 {
-    match world.ground_y {
+    match world.ground_y() {
         Some(gy) => {
             let target_y = orbit.effective_target(world).y();
             let ratio = (gy + GROUND_MARGIN - target_y) / orbit.distance;
@@ -134,7 +134,7 @@ fn tonemap_to_display(
     }
 }
 
-fn render_thread(world: Arc<World>, shared: Arc<SharedRender>) {
+fn render_thread(world: Arc<World>, shared: Arc<SharedRender>, mode: RenderMode) {
     let display_w = world.image_width as usize;
     let render_w = world.image_width / RENDER_SCALE;
     let render_h = world.image_height / RENDER_SCALE;
@@ -165,6 +165,7 @@ fn render_thread(world: Arc<World>, shared: Arc<SharedRender>) {
             let cancel = Some((shared.generation.clone(), view_gen));
 
             match render_frame(
+                mode,
                 camera,
                 world.clone(),
                 render_w,
@@ -201,7 +202,7 @@ fn render_thread(world: Arc<World>, shared: Arc<SharedRender>) {
 /// Opens a window, runs progressive path tracing with the current `samples_per_pixel` as the
 /// target count per pixel (restarts accumulation when the view changes).  
 /// **LMB drag:** yaw / pitch. **RMB drag:** roll. **Mouse wheel:** zoom (orbit distance). **Esc:** close.
-pub fn run_interactive(world: Arc<World>) -> anyhow::Result<()> {
+pub fn run_interactive(world: Arc<World>, mode: RenderMode) -> anyhow::Result<()> {
     let w = world.image_width as usize;
     let h = world.image_height as usize;
     let len = w * h;
@@ -216,7 +217,7 @@ pub fn run_interactive(world: Arc<World>) -> anyhow::Result<()> {
 
     let world_render = world.clone();
     let shared_render = shared.clone();
-    std::thread::spawn(move || render_thread(world_render, shared_render));
+    std::thread::spawn(move || render_thread(world_render, shared_render, mode));
 
     let mut window = Window::new(
         "ray-tracer (LMB orbit, MMB pan, RMB roll, wheel zoom)",
@@ -306,7 +307,7 @@ pub fn run_interactive(world: Arc<World>) -> anyhow::Result<()> {
                                 / world.image_height as f64;
                         o.target_offset += -dx * pan_scale * right_vec + dy * pan_scale * up_vec;
                         // Keep the effective target above the ground plane.
-                        if let Some(gy) = world.ground_y {
+                        if let Some(gy) = world.ground_y() {
                             let target_y = world.camera_target.y() + o.target_offset.y;
                             if target_y < gy {
                                 o.target_offset.y += gy - target_y;
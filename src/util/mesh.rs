@@ -0,0 +1,660 @@
+//! Indexed triangle meshes: Loop subdivision, refinement and displacement.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use nalgebra::Vector3;
+
+use crate::data::point64::Point64;
+use crate::hittables::Hittable;
+use crate::hittables::quad::Quad;
+use crate::hittables::triangle::Triangle;
+use crate::materials::Material;
+use crate::textures::Texture;
+
+/// Triangles sharing vertices by index, so subdivision can see the mesh's connectivity.
+#[derive(Clone, Debug, Default)]
+pub struct TriangleMesh {
+    pub positions: Vec<Vector3<f64>>,
+    pub faces: Vec<[usize; 3]>,
+    /// Texture coordinates per face corner, so UV seams survive subdivision.
+    pub texcoords: Option<Vec<[(f64, f64); 3]>>,
+    /// Per-vertex shading normals; faces are shaded flat without them.
+    pub normals: Option<Vec<Vector3<f64>>>,
+    /// Edges (as sorted vertex pairs) kept sharp by subdivision. Boundary edges are always
+    /// treated as sharp and need not be listed.
+    pub creases: HashSet<(usize, usize)>,
+}
+
+fn edge_key(a: usize, b: usize) -> (usize, usize) {
+    (a.min(b), a.max(b))
+}
+
+fn face_edges(face: [usize; 3]) -> impl Iterator<Item = (usize, usize)> {
+    (0..3).map(move |k| edge_key(face[k], face[(k + 1) % 3]))
+}
+
+fn midpoint(a: (f64, f64), b: (f64, f64)) -> (f64, f64) {
+    (0.5 * (a.0 + b.0), 0.5 * (a.1 + b.1))
+}
+
+/// Adjacency of one edge: the vertex opposite it in each face that uses it, and the index of
+/// the vertex inserted at its midpoint.
+struct EdgeInfo {
+    opposite: Vec<usize>,
+    midpoint: usize,
+}
+
+impl TriangleMesh {
+    fn face_normal(&self, face: &[usize; 3]) -> Vector3<f64> {
+        let [a, b, c] = face.map(|i| self.positions[i]);
+        (b - a).cross(&(c - a))
+    }
+
+    /// Marks every edge whose two faces meet at more than `degrees` as a crease.
+    pub fn mark_creases_by_angle(&mut self, degrees: f64) {
+        let cos_limit = degrees.to_radians().cos();
+        let mut edge_faces: HashMap<(usize, usize), Vec<usize>> = HashMap::new();
+        for (index, face) in self.faces.iter().enumerate() {
+            for k in 0..3 {
+                edge_faces
+                    .entry(edge_key(face[k], face[(k + 1) % 3]))
+                    .or_default()
+                    .push(index);
+            }
+        }
+
+        for (edge, faces) in edge_faces {
+            if let [f0, f1] = faces[..] {
+                let n0 = self.face_normal(&self.faces[f0]);
+                let n1 = self.face_normal(&self.faces[f1]);
+                if n0.norm() > 0.
+                    && n1.norm() > 0.
+                    && n0.normalize().dot(&n1.normalize()) < cos_limit
+                {
+                    self.creases.insert(edge);
+                }
+            }
+        }
+    }
+
+    /// One step of Loop subdivision: every triangle is split in four, with new and old
+    /// vertices repositioned by Loop's smoothing masks. Boundary and crease edges use the
+    /// cubic B-spline rules, so they stay curves of their own and flat regions stay flat;
+    /// vertices where more than two sharp edges meet, or that belong to a single face, are
+    /// corners and do not move.
+    ///
+    /// Vertex normals are dropped, since the surface changes shape.
+    pub fn subdivided(&self) -> TriangleMesh {
+        let vertex_count = self.positions.len();
+
+        let mut edges: HashMap<(usize, usize), EdgeInfo> = HashMap::new();
+        let mut edge_order = vec![];
+        for face in &self.faces {
+            for k in 0..3 {
+                let key = edge_key(face[k], face[(k + 1) % 3]);
+                let info = edges.entry(key).or_insert_with(|| {
+                    edge_order.push(key);
+                    EdgeInfo {
+                        opposite: vec![],
+                        midpoint: vertex_count + edge_order.len() - 1,
+                    }
+                });
+                info.opposite.push(face[(k + 2) % 3]);
+            }
+        }
+        let is_sharp = |key: &(usize, usize), info: &EdgeInfo| {
+            info.opposite.len() != 2 || self.creases.contains(key)
+        };
+
+        let mut neighbors = vec![vec![]; vertex_count];
+        let mut sharp_neighbors = vec![vec![]; vertex_count];
+        let mut face_counts = vec![0; vertex_count];
+        for key in &edge_order {
+            let (a, b) = *key;
+            neighbors[a].push(b);
+            neighbors[b].push(a);
+            if is_sharp(key, &edges[key]) {
+                sharp_neighbors[a].push(b);
+                sharp_neighbors[b].push(a);
+            }
+        }
+        for face in &self.faces {
+            for &v in face {
+                face_counts[v] += 1;
+            }
+        }
+
+        let mut positions: Vec<Vector3<f64>> = (0..vertex_count)
+            .map(|v| {
+                let p = self.positions[v];
+                match sharp_neighbors[v][..] {
+                    [s0, s1] if face_counts[v] > 1 => {
+                        p * 0.75 + (self.positions[s0] + self.positions[s1]) * 0.125
+                    }
+                    [] | [_] if !neighbors[v].is_empty() => {
+                        let n = neighbors[v].len() as f64;
+                        let beta = if neighbors[v].len() == 3 {
+                            3. / 16.
+                        } else {
+                            3. / (8. * n)
+                        };
+                        let sum: Vector3<f64> =
+                            neighbors[v].iter().map(|&u| self.positions[u]).sum();
+                        p * (1. - n * beta) + sum * beta
+                    }
+                    _ => p,
+                }
+            })
+            .collect();
+
+        positions.extend(edge_order.iter().map(|key| {
+            let info = &edges[key];
+            let (a, b) = (self.positions[key.0], self.positions[key.1]);
+            if is_sharp(key, info) {
+                (a + b) * 0.5
+            } else {
+                let (c, d) = (
+                    self.positions[info.opposite[0]],
+                    self.positions[info.opposite[1]],
+                );
+                (a + b) * 0.375 + (c + d) * 0.125
+            }
+        }));
+
+        let mut faces = Vec::with_capacity(self.faces.len() * 4);
+        let mut texcoords = self
+            .texcoords
+            .as_ref()
+            .map(|_| Vec::with_capacity(self.faces.len() * 4));
+        for (index, &[a, b, c]) in self.faces.iter().enumerate() {
+            let ab = edges[&edge_key(a, b)].midpoint;
+            let bc = edges[&edge_key(b, c)].midpoint;
+            let ca = edges[&edge_key(c, a)].midpoint;
+            faces.extend([[a, ab, ca], [ab, b, bc], [ca, bc, c], [ab, bc, ca]]);
+
+            if let (Some(out), Some(uvs)) = (texcoords.as_mut(), self.texcoords.as_ref()) {
+                let [ta, tb, tc] = uvs[index];
+                let (tab, tbc, tca) = (midpoint(ta, tb), midpoint(tb, tc), midpoint(tc, ta));
+                out.extend([
+                    [ta, tab, tca],
+                    [tab, tb, tbc],
+                    [tca, tbc, tc],
+                    [tab, tbc, tca],
+                ]);
+            }
+        }
+
+        let creases = self
+            .creases
+            .iter()
+            .filter_map(|key| {
+                let m = edges.get(key)?.midpoint;
+                Some([edge_key(key.0, m), edge_key(m, key.1)])
+            })
+            .flatten()
+            .collect();
+
+        TriangleMesh {
+            positions,
+            faces,
+            texcoords,
+            normals: None,
+            creases,
+        }
+    }
+
+    /// Applies [`TriangleMesh::subdivided`] `levels` times.
+    pub fn subdivided_times(&self, levels: u32) -> TriangleMesh {
+        (0..levels).fold(self.clone(), |mesh, _| mesh.subdivided())
+    }
+
+    /// Two triangles covering `quad`, with texture coordinates matching the quad's own.
+    pub fn from_quad(quad: &Quad) -> TriangleMesh {
+        let (q, u, v) = (quad.q.0, quad.u.0, quad.v.0);
+
+        TriangleMesh {
+            positions: vec![q, q + u, q + v, q + u + v],
+            faces: vec![[0, 1, 3], [0, 3, 2]],
+            texcoords: Some(vec![
+                [(0., 0.), (1., 0.), (1., 1.)],
+                [(0., 0.), (1., 1.), (0., 1.)],
+            ]),
+            ..Default::default()
+        }
+    }
+
+    fn longest_edge(&self) -> f64 {
+        self.faces
+            .iter()
+            .flat_map(|face| {
+                (0..3).map(|k| (self.positions[face[k]] - self.positions[face[(k + 1) % 3]]).norm())
+            })
+            .fold(0., f64::max)
+    }
+
+    /// Bisects triangles, without changing the surface, until no edge is longer than
+    /// `max_edge_length`. Faces whose edges are already short enough are left alone.
+    ///
+    /// Each split face is bisected across its longest edge, so triangles do not grow thinner
+    /// as they are refined. A face whose neighbour splits a shared edge splits it as well,
+    /// which keeps the mesh free of T-junctions.
+    ///
+    /// Vertex normals are dropped once any face is split.
+    pub fn refined(&self, max_edge_length: f64) -> TriangleMesh {
+        assert!(max_edge_length > 0., "edge length must be positive");
+
+        let mut mesh = self.clone();
+        while mesh.longest_edge() > max_edge_length {
+            mesh = mesh.bisected(max_edge_length);
+        }
+        mesh
+    }
+
+    /// Index `k` of the face's longest edge, from corner `k` to corner `k + 1`.
+    fn longest_edge_of(&self, face: &[usize; 3]) -> usize {
+        let length =
+            |k: usize| (self.positions[face[k]] - self.positions[face[(k + 1) % 3]]).norm();
+        (0..3).fold(0, |longest, k| {
+            if length(k) > length(longest) {
+                k
+            } else {
+                longest
+            }
+        })
+    }
+
+    /// One round of [`TriangleMesh::refined`]: splits every edge longer than
+    /// `max_edge_length`, along with whatever longest edges that forces.
+    fn bisected(&self, max_edge_length: f64) -> TriangleMesh {
+        let edge_length = |(a, b): (usize, usize)| (self.positions[a] - self.positions[b]).norm();
+        let mut marked: HashSet<(usize, usize)> = self
+            .faces
+            .iter()
+            .flat_map(|face| face_edges(*face))
+            .filter(|&edge| edge_length(edge) > max_edge_length)
+            .collect();
+        // A face split at any edge is first bisected across its longest one, which its
+        // neighbour over that edge must then split too.
+        loop {
+            let forced: Vec<(usize, usize)> = self
+                .faces
+                .iter()
+                .filter(|face| face_edges(**face).any(|edge| marked.contains(&edge)))
+                .map(|face| {
+                    let k = self.longest_edge_of(face);
+                    edge_key(face[k], face[(k + 1) % 3])
+                })
+                .filter(|edge| !marked.contains(edge))
+                .collect();
+            if forced.is_empty() {
+                break;
+            }
+            marked.extend(forced);
+        }
+
+        let mut positions = self.positions.clone();
+        let mut midpoints: HashMap<(usize, usize), usize> = HashMap::new();
+        for face in &self.faces {
+            for edge in face_edges(*face) {
+                if marked.contains(&edge) && !midpoints.contains_key(&edge) {
+                    midpoints.insert(edge, positions.len());
+                    positions.push((self.positions[edge.0] + self.positions[edge.1]) * 0.5);
+                }
+            }
+        }
+        let split_at = |a: usize, b: usize| midpoints.get(&edge_key(a, b)).copied();
+
+        let mut faces = Vec::with_capacity(self.faces.len() * 2);
+        let mut texcoords = self
+            .texcoords
+            .as_ref()
+            .map(|_| Vec::with_capacity(self.faces.len() * 2));
+        for (index, face) in self.faces.iter().enumerate() {
+            let corner_uvs = self.texcoords.as_ref().map(|uvs| uvs[index]);
+            let k = self.longest_edge_of(face);
+            let (a, b, c) = (face[k], face[(k + 1) % 3], face[(k + 2) % 3]);
+            let Some(m) = split_at(a, b) else {
+                faces.push(*face);
+                if let (Some(out), Some(uvs)) = (texcoords.as_mut(), corner_uvs) {
+                    out.push(uvs);
+                }
+                continue;
+            };
+
+            // Bisect across the longest edge a-b, then split each half at its outer edge if
+            // that is marked too. Corners keep their winding order.
+            let mut pieces = vec![];
+            match split_at(c, a) {
+                Some(n) => pieces.extend([[a, m, n], [m, c, n]]),
+                None => pieces.push([a, m, c]),
+            }
+            match split_at(b, c) {
+                Some(n) => pieces.extend([[m, b, n], [m, n, c]]),
+                None => pieces.push([m, b, c]),
+            }
+            faces.extend(&pieces);
+
+            if let (Some(out), Some(uvs)) = (texcoords.as_mut(), corner_uvs) {
+                let mut uv_of: HashMap<usize, (f64, f64)> = HashMap::new();
+                let (ta, tb, tc) = (uvs[k], uvs[(k + 1) % 3], uvs[(k + 2) % 3]);
+                uv_of.extend([(a, ta), (b, tb), (c, tc), (m, midpoint(ta, tb))]);
+                if let Some(n) = split_at(c, a) {
+                    uv_of.insert(n, midpoint(tc, ta));
+                }
+                if let Some(n) = split_at(b, c) {
+                    uv_of.insert(n, midpoint(tb, tc));
+                }
+                out.extend(pieces.iter().map(|piece| piece.map(|v| uv_of[&v])));
+            }
+        }
+
+        let creases = self
+            .creases
+            .iter()
+            .flat_map(|&(a, b)| match midpoints.get(&(a, b)) {
+                Some(&m) => vec![edge_key(a, m), edge_key(m, b)],
+                None => vec![(a, b)],
+            })
+            .collect();
+
+        TriangleMesh {
+            positions,
+            faces,
+            texcoords,
+            normals: None,
+            creases,
+        }
+    }
+
+    /// Area-weighted average of the normals of the faces around each vertex.
+    pub fn vertex_normals(&self) -> Vec<Vector3<f64>> {
+        let mut normals = vec![Vector3::zeros(); self.positions.len()];
+        for face in &self.faces {
+            let normal = self.face_normal(face);
+            for &v in face {
+                normals[v] += normal;
+            }
+        }
+
+        normals
+            .into_iter()
+            .map(|n| n.try_normalize(0.).unwrap_or_else(Vector3::y))
+            .collect()
+    }
+
+    /// Moves every vertex along its normal by `scale` times the texture's brightness there,
+    /// then rebuilds smooth normals for the new shape. Refine the mesh first so there are
+    /// enough vertices to carry the texture's detail.
+    ///
+    /// Vertices take the texture coordinates of the first face corner that uses them, so an
+    /// image that does not match across a UV seam will open a crack along it.
+    pub fn displaced(&self, texture: &Texture, scale: f64) -> TriangleMesh {
+        let mut uvs = vec![None; self.positions.len()];
+        if let Some(texcoords) = &self.texcoords {
+            for (face, corners) in self.faces.iter().zip(texcoords) {
+                for (&v, &uv) in face.iter().zip(corners) {
+                    uvs[v].get_or_insert(uv);
+                }
+            }
+        }
+
+        let positions = self
+            .positions
+            .iter()
+            .zip(self.vertex_normals())
+            .zip(uvs)
+            .map(|((p, normal), uv)| {
+                let (u, v) = uv.unwrap_or((0., 0.));
+                let color = texture.value(u, v, &Point64(*p));
+                let height = (color.r() + color.g() + color.b()) / 3.;
+                p + normal * (height * scale)
+            })
+            .collect();
+
+        let mut mesh = TriangleMesh {
+            positions,
+            ..self.clone()
+        };
+        mesh.normals = Some(mesh.vertex_normals());
+        mesh
+    }
+
+    /// One [`Triangle`] per face, carrying the mesh's texture coordinates and vertex normals
+    /// if it has them. The triangles share one copy of `material`.
+    pub fn triangles(&self, material: impl Into<Arc<Material>>) -> Vec<Hittable> {
+        let material = material.into();
+        self.faces
+            .iter()
+            .enumerate()
+            .map(|(index, face)| {
+                let [p1, p2, p3] = face.map(|i| Point64(self.positions[i]));
+                let mut triangle = Triangle::new(p1, p2, p3, Arc::clone(&material));
+                if let Some(texcoords) = &self.texcoords {
+                    triangle = triangle.with_texcoords(texcoords[index]);
+                }
+                if let Some(normals) = &self.normals {
+                    triangle = triangle.with_vertex_normals(face.map(|i| normals[i]));
+                }
+                Hittable::Triangle(triangle)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{TriangleMesh, edge_key};
+    use crate::data::color64::Color64;
+    use crate::data::point64::Point64;
+    use crate::data::ray::Ray;
+    use crate::hittables::Hittable;
+    use crate::hittables::quad::Quad;
+    use crate::materials::Material;
+    use crate::materials::lambertian::Lambertian;
+    use crate::textures::Texture;
+    use crate::textures::noise::{Noise, NoiseType};
+    use crate::textures::perlin::PerlinGenerator;
+    use approx::assert_abs_diff_eq;
+    use nalgebra::Vector3;
+    use std::collections::HashMap;
+
+    fn tetrahedron() -> TriangleMesh {
+        TriangleMesh {
+            positions: vec![
+                Vector3::new(1., 1., 1.),
+                Vector3::new(1., -1., -1.),
+                Vector3::new(-1., 1., -1.),
+                Vector3::new(-1., -1., 1.),
+            ],
+            faces: vec![[0, 1, 2], [0, 3, 1], [0, 2, 3], [1, 3, 2]],
+            ..Default::default()
+        }
+    }
+
+    /// A unit square in the XZ plane made of a 2×2 grid of quads, each split in two, with
+    /// texture coordinates equal to (x, z).
+    fn flat_grid() -> TriangleMesh {
+        let positions: Vec<Vector3<f64>> = (0..3)
+            .flat_map(|j| (0..3).map(move |i| Vector3::new(i as f64 * 0.5, 0., j as f64 * 0.5)))
+            .collect();
+        let mut faces = vec![];
+        for j in 0..2 {
+            for i in 0..2 {
+                let v = j * 3 + i;
+                faces.push([v, v + 3, v + 1]);
+                faces.push([v + 1, v + 3, v + 4]);
+            }
+        }
+        let texcoords = faces
+            .iter()
+            .map(|face: &[usize; 3]| face.map(|v| (positions[v].x, positions[v].z)))
+            .collect();
+
+        TriangleMesh {
+            positions,
+            faces,
+            texcoords: Some(texcoords),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn closed_mesh_shrinks_inside_its_hull() {
+        let mesh = tetrahedron().subdivided_times(2);
+        assert_eq!(mesh.faces.len(), 4 * 16);
+        // V - E + F = 2 for a closed genus-0 mesh: E = 3F/2.
+        assert_eq!(mesh.positions.len() as i64 - 96 + 64, 2);
+
+        for p in &mesh.positions {
+            // Inside the original tetrahedron: |x| + |y| + |z| ≤ 3 bounds it loosely, and the
+            // smoothed surface pulls the corners in.
+            assert!(p.abs().sum() < 3.);
+        }
+    }
+
+    #[test]
+    fn boundaries_stay_on_the_outline_and_uvs_follow() {
+        let mesh = flat_grid().subdivided_times(2);
+        let texcoords = mesh.texcoords.as_ref().unwrap();
+        assert_eq!(texcoords.len(), mesh.faces.len());
+
+        for p in &mesh.positions {
+            assert_abs_diff_eq!(p.y, 0.);
+            assert!((-1e-12..=1. + 1e-12).contains(&p.x));
+            assert!((-1e-12..=1. + 1e-12).contains(&p.z));
+        }
+
+        // Corners of the square belong to a single face and must not move.
+        assert_eq!(mesh.positions[0], Vector3::new(0., 0., 0.));
+        assert_eq!(mesh.positions[8], Vector3::new(1., 0., 1.));
+
+        // Texture coordinates are interpolated, not smoothed: every face corner's UV is a
+        // dyadic blend of the original corners and stays inside the unit square.
+        for face_uvs in texcoords {
+            for (u, v) in face_uvs {
+                assert!((0. ..=1.).contains(u) && (0. ..=1.).contains(v));
+                assert_abs_diff_eq!((u * 8.).fract(), 0.);
+            }
+        }
+    }
+
+    #[test]
+    fn creases_stay_sharp() {
+        // Two triangles folded 90° along the X axis.
+        let mut mesh = TriangleMesh {
+            positions: vec![
+                Vector3::new(0., 0., 0.),
+                Vector3::new(2., 0., 0.),
+                Vector3::new(1., 0., 1.),
+                Vector3::new(1., 1., 0.),
+            ],
+            faces: vec![[0, 2, 1], [0, 1, 3]],
+            ..Default::default()
+        };
+        mesh.mark_creases_by_angle(30.);
+        assert!(mesh.creases.contains(&edge_key(0, 1)));
+
+        let mesh = mesh.subdivided_times(2);
+        let on_fold = mesh
+            .positions
+            .iter()
+            .filter(|p| p.y.abs() < 1e-12 && p.z.abs() < 1e-12)
+            .count();
+        // The fold edge is split into four, giving five points that all stay on it.
+        assert_eq!(on_fold, 5);
+        assert_eq!(mesh.creases.len(), 4);
+    }
+
+    #[test]
+    fn refinement_splits_only_long_edges_without_t_junctions() {
+        // A tetrahedron with one vertex pulled far out: only the faces reaching it are long.
+        let mut mesh = tetrahedron();
+        mesh.positions[0] = Vector3::new(10., 10., 10.);
+        let refined = mesh.refined(3.);
+        assert!(refined.longest_edge() <= 3.);
+
+        // Every edge of the closed surface still has a face on each side.
+        let mut uses: HashMap<(usize, usize), usize> = HashMap::new();
+        for face in &refined.faces {
+            for k in 0..3 {
+                *uses
+                    .entry(edge_key(face[k], face[(k + 1) % 3]))
+                    .or_default() += 1;
+            }
+        }
+        assert!(uses.values().all(|&count| count == 2));
+
+        // The short base is untouched, and far fewer faces are made than by splitting all of
+        // them in four each round.
+        assert!(refined.faces.contains(&[1, 3, 2]));
+        let rounds = (mesh.longest_edge() / 3.).log2().ceil() as u32;
+        assert!(refined.faces.len() < mesh.faces.len() * 4usize.pow(rounds) / 2);
+    }
+
+    fn gray() -> Material {
+        Material::Lambertian(Lambertian {
+            albedo: Texture::solid(Color64::gray(0.5)),
+        })
+    }
+
+    #[test]
+    fn quad_mesh_matches_quad_uvs() {
+        let quad = Quad::new(
+            Point64::new(-1., 0., 2.),
+            Point64::new(4., 0., 0.),
+            Point64::new(0., 0., -2.),
+            gray(),
+        );
+        let mesh = TriangleMesh::from_quad(&quad).refined(0.7);
+        assert!(mesh.longest_edge() <= 0.7);
+        let triangles = Hittable::HittableVec(crate::hittables::hittable_vec::HittableVec {
+            hittables: mesh.triangles(gray()),
+        });
+
+        for (x, z) in [(-0.9, 1.9), (0.3, 0.7), (2.5, 0.1), (1.234, 1.5)] {
+            let ray = Ray {
+                origin: Point64::new(x, 3., z),
+                direction: Point64::new(0., -1., 0.),
+                exposure_time: 0.,
+            };
+            let expected = quad.is_hit_by(&ray, 0.001, f64::INFINITY).unwrap();
+            let hr = triangles.is_hit_by(&ray, 0.001, f64::INFINITY).unwrap();
+            assert_abs_diff_eq!(hr.value, expected.value, epsilon = 1e-9);
+            assert_abs_diff_eq!(hr.u, expected.u, epsilon = 1e-9);
+            assert_abs_diff_eq!(hr.v, expected.v, epsilon = 1e-9);
+        }
+    }
+
+    #[test]
+    fn displacement_moves_along_normals() {
+        let mesh = flat_grid().refined(0.2);
+        let vertex_count = mesh.positions.len();
+
+        let lifted = mesh.displaced(&Texture::solid(Color64::gray(0.25)), 2.);
+        assert_eq!(lifted.positions.len(), vertex_count);
+        for (before, after) in mesh.positions.iter().zip(&lifted.positions) {
+            assert_abs_diff_eq!(after.y, before.y + 0.5, epsilon = 1e-12);
+            assert_abs_diff_eq!(after.x, before.x, epsilon = 1e-12);
+        }
+        for normal in lifted.normals.as_ref().unwrap() {
+            assert_abs_diff_eq!(normal.y, 1., epsilon = 1e-12);
+        }
+
+        // A varying texture makes a bumpy surface with tilted normals.
+        let bumps = Texture::Noise(Box::new(Noise {
+            noise_gen: PerlinGenerator::new(),
+            scale: 8.,
+            noise_type: NoiseType::Turbulence,
+        }));
+        let bumpy = flat_grid().refined(0.1).displaced(&bumps, 0.1);
+        let (lowest, highest) = bumpy
+            .positions
+            .iter()
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), p| {
+                (lo.min(p.y), hi.max(p.y))
+            });
+        assert!(highest - lowest > 0.01);
+        assert!(bumpy.normals.unwrap().iter().any(|n| n.y < 0.999));
+    }
+}
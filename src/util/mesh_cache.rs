@@ -0,0 +1,634 @@
+//! Binary cache of OBJ meshes together with their built [`LinearBvh`], so large models skip
+//! parsing, subdivision and the hierarchy build on later runs.
+//!
+//! Cache files live in [`cache_dir`] and are named after the source file plus a key that
+//! hashes its contents and every load parameter. A changed model or parameter yields a new
+//! key and a new file; the old one stays, so switching back is still fast. Once the cache
+//! outgrows [`MAX_CACHE_BYTES`], the files read least recently are removed.
+//!
+//! Materials are stored only by their MTL name. They are mapped from the model's MTL libraries
+//! again on every load, so edits to those apply without a rebuild; faces without one take the
+//! material the caller supplies.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::SystemTime;
+
+use anyhow::Context;
+use nalgebra::Vector3;
+
+use crate::data::point64::Point64;
+use crate::data::storage::{Real, StoredBounds};
+use crate::hittables::Hittable;
+use crate::hittables::axis_aligned_bounding_box::AxisAlignedBoundingBox;
+use crate::hittables::bounded_volume_hierarchy::{
+    BoundedVolumeHierarchy, BvhBuildOptions, BvhLayout, BvhStrategy,
+};
+use crate::hittables::linear_bvh::{LinearBvh, LinearBvhNode};
+use crate::hittables::triangle::Triangle;
+use crate::materials::Material;
+use crate::util::obj::{Subdivision, load_mtl_materials, load_obj_triangles_from_library};
+
+const MAGIC: &[u8; 4] = b"RTBC";
+/// Bump whenever the layout below or the meaning of the cached data changes.
+const FORMAT_VERSION: u32 = 2;
+const EXTENSION: &str = "bvhcache";
+const PARTIAL_EXTENSION: &str = "partial";
+/// Total size of cache files kept in a cache directory.
+const MAX_CACHE_BYTES: u64 = 2 << 30;
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+const HAS_TEXCOORDS: u8 = 1;
+const HAS_VERTEX_NORMALS: u8 = 2;
+/// Material slot of triangles that take the caller's material.
+const FALLBACK_MATERIAL: u32 = u32::MAX;
+
+/// Environment variable naming the directory cache files go in, overriding [`cache_dir`]'s
+/// defaults.
+pub const CACHE_DIR_VAR: &str = "RAY_TRACER_CACHE_DIR";
+
+/// Where cache files go, resolved at run time: `$RAY_TRACER_CACHE_DIR` if set, else `cache/` in
+/// the source tree the binary was built from if it is still there, else `ray-tracer/` in the
+/// user cache directory (`$XDG_CACHE_HOME`, then `~/.cache`, then the temporary directory).
+pub fn cache_dir() -> PathBuf {
+    if let Some(dir) = std::env::var_os(CACHE_DIR_VAR) {
+        return PathBuf::from(dir);
+    }
+    let source_tree = Path::new(env!("CARGO_MANIFEST_DIR"));
+    if source_tree.is_dir() {
+        return source_tree.join("cache");
+    }
+    std::env::var_os("XDG_CACHE_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".cache")))
+        .unwrap_or_else(std::env::temp_dir)
+        .join("ray-tracer")
+}
+
+/// Like [`load_obj_triangles_with_materials`](crate::util::obj::load_obj_triangles_with_materials)
+/// followed by [`BoundedVolumeHierarchy::build`] with a linear layout, but reuses a cached
+/// result for the same file contents and parameters. Faces without an MTL material get
+/// `material`.
+pub fn load_obj_bvh(
+    path: &Path,
+    material: Material,
+    scale: f64,
+    offset: Vector3<f64>,
+    subdivision: Subdivision,
+    options: &BvhBuildOptions,
+) -> anyhow::Result<Hittable> {
+    load_obj_bvh_in(
+        &cache_dir(),
+        path,
+        material,
+        scale,
+        offset,
+        subdivision,
+        options,
+    )
+}
+
+fn load_obj_bvh_in(
+    cache_dir: &Path,
+    path: &Path,
+    material: Material,
+    scale: f64,
+    offset: Vector3<f64>,
+    subdivision: Subdivision,
+    options: &BvhBuildOptions,
+) -> anyhow::Result<Hittable> {
+    let source = fs::read(path).with_context(|| format!("reading {}", path.display()))?;
+    let key = cache_key(&source, scale, offset, subdivision, options);
+    let stem = path
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or("mesh");
+    let cache_path = cache_dir.join(format!("{stem}-{key:016x}.{EXTENSION}"));
+    let library = load_mtl_materials(path, &String::from_utf8_lossy(&source));
+    let fallback = Arc::new(material);
+
+    if let Ok(bytes) = fs::read(&cache_path) {
+        match read_cache(&bytes, key, &library, &fallback) {
+            Ok(bvh) => {
+                println!("{}: loaded from {}", path.display(), cache_path.display());
+                // Mark the file as recently used, so pruning keeps it.
+                if let Ok(file) = fs::File::options().write(true).open(&cache_path) {
+                    let _ = file.set_modified(SystemTime::now());
+                }
+                return Ok(Hittable::LinearBvh(bvh));
+            }
+            Err(e) => eprintln!("ignoring cache {}: {e}", cache_path.display()),
+        }
+    }
+
+    let triangles =
+        load_obj_triangles_from_library(path, &library, fallback, scale, offset, subdivision)?;
+    let options = BvhBuildOptions {
+        layout: BvhLayout::Linear,
+        ..*options
+    };
+    let (bvh, stats) = BoundedVolumeHierarchy::build(&triangles, 0., 1., &options);
+    println!("{}: {stats}", path.display());
+
+    if let Hittable::LinearBvh(linear) = &bvh
+        && let Err(e) = write_cache(cache_dir, &cache_path, key, linear, &library)
+    {
+        eprintln!("could not write cache {}: {e}", cache_path.display());
+    }
+    if let Err(e) = prune_cache(cache_dir, MAX_CACHE_BYTES, &cache_path) {
+        eprintln!("could not prune cache {}: {e}", cache_dir.display());
+    }
+
+    Ok(bvh)
+}
+
+fn fnv1a(hash: u64, bytes: &[u8]) -> u64 {
+    bytes.iter().fold(hash, |hash, &byte| {
+        (hash ^ byte as u64).wrapping_mul(FNV_PRIME)
+    })
+}
+
+fn cache_key(
+    source: &[u8],
+    scale: f64,
+    offset: Vector3<f64>,
+    subdivision: Subdivision,
+    options: &BvhBuildOptions,
+) -> u64 {
+    let strategy: u8 = match options.strategy {
+        BvhStrategy::RandomMedian => 0,
+        BvhStrategy::Sah => 1,
+    };
+    let mut parameters = vec![];
+    parameters.extend(FORMAT_VERSION.to_le_bytes());
+    parameters.extend(scale.to_le_bytes());
+    for component in offset.iter() {
+        parameters.extend(component.to_le_bytes());
+    }
+    parameters.extend(subdivision.levels.to_le_bytes());
+    parameters.extend(subdivision.crease_angle.unwrap_or(f64::NAN).to_le_bytes());
+    parameters.push(strategy);
+    parameters.extend((options.max_leaf_size as u64).to_le_bytes());
+    parameters.extend((options.bins as u64).to_le_bytes());
+    parameters.extend(options.traversal_cost.to_le_bytes());
+    parameters.extend(options.seed.to_le_bytes());
+    // Single- and double-precision builds round the same model differently.
+    parameters.extend((size_of::<Real>() as u64).to_le_bytes());
+
+    fnv1a(fnv1a(FNV_OFFSET_BASIS, source), &parameters)
+}
+
+fn write_cache(
+    cache_dir: &Path,
+    cache_path: &Path,
+    key: u64,
+    bvh: &LinearBvh,
+    library: &HashMap<String, Arc<Material>>,
+) -> anyhow::Result<()> {
+    let mut bytes = vec![];
+    bytes.extend(MAGIC);
+    bytes.extend(FORMAT_VERSION.to_le_bytes());
+    bytes.extend(key.to_le_bytes());
+
+    // Name the library materials the triangles use, in order of first use.
+    let names: HashMap<*const Material, &str> = library
+        .iter()
+        .map(|(name, material)| (Arc::as_ptr(material), name.as_str()))
+        .collect();
+    let mut used: Vec<&str> = vec![];
+    let slots: Vec<u32> = bvh
+        .primitives()
+        .iter()
+        .map(|primitive| match primitive {
+            Hittable::Triangle(triangle) => match names.get(&Arc::as_ptr(&triangle.material)) {
+                Some(name) => match used.iter().position(|used| used == name) {
+                    Some(slot) => slot as u32,
+                    None => {
+                        used.push(name);
+                        used.len() as u32 - 1
+                    }
+                },
+                None => FALLBACK_MATERIAL,
+            },
+            _ => FALLBACK_MATERIAL,
+        })
+        .collect();
+    bytes.extend((used.len() as u64).to_le_bytes());
+    for name in used {
+        bytes.extend((name.len() as u64).to_le_bytes());
+        bytes.extend(name.as_bytes());
+    }
+
+    let put_point = |bytes: &mut Vec<u8>, point: &Vector3<f64>| {
+        for component in point.iter() {
+            bytes.extend(component.to_le_bytes());
+        }
+    };
+
+    bytes.extend((bvh.primitives().len() as u64).to_le_bytes());
+    for (primitive, slot) in bvh.primitives().iter().zip(slots) {
+        let Hittable::Triangle(triangle) = primitive else {
+            anyhow::bail!("only triangle meshes can be cached");
+        };
+        for vertex in triangle.vertices() {
+            put_point(&mut bytes, &vertex.0);
+        }
+        bytes.extend(slot.to_le_bytes());
+        let texcoords = triangle.texcoords();
+        let vertex_normals = triangle.vertex_normals();
+        let mut flags = 0;
+        if texcoords.is_some() {
+            flags |= HAS_TEXCOORDS;
+        }
+        if vertex_normals.is_some() {
+            flags |= HAS_VERTEX_NORMALS;
+        }
+        bytes.push(flags);
+        for (u, v) in texcoords.into_iter().flatten() {
+            bytes.extend(u.to_le_bytes());
+            bytes.extend(v.to_le_bytes());
+        }
+        for normal in vertex_normals.into_iter().flatten() {
+            put_point(&mut bytes, &normal);
+        }
+    }
+
+    bytes.extend((bvh.nodes().len() as u64).to_le_bytes());
+    for node in bvh.nodes() {
+        let bounds = node.bounds.load();
+        put_point(&mut bytes, &bounds.minimum.0);
+        put_point(&mut bytes, &bounds.maximum.0);
+        bytes.extend(node.offset.to_le_bytes());
+        bytes.extend(node.primitive_count.to_le_bytes());
+        bytes.push(node.axis);
+    }
+
+    fs::create_dir_all(cache_dir)?;
+    // Write under a temporary name first so an interrupted run never leaves a torn cache.
+    // The name is unique so that concurrent runs building the same model do not collide.
+    static WRITES: AtomicUsize = AtomicUsize::new(0);
+    let partial = cache_path.with_extension(format!(
+        "{}-{}.{PARTIAL_EXTENSION}",
+        std::process::id(),
+        WRITES.fetch_add(1, Ordering::Relaxed)
+    ));
+    fs::write(&partial, bytes)?;
+    if let Err(e) = fs::rename(&partial, cache_path) {
+        let _ = fs::remove_file(&partial);
+        return Err(e.into());
+    }
+    Ok(())
+}
+
+/// Removes the least recently used cache files in `cache_dir` until the rest fit in
+/// `max_bytes`, never removing `keep`. Temporary files left by interrupted writes are
+/// removed once they are a day old.
+fn prune_cache(cache_dir: &Path, max_bytes: u64, keep: &Path) -> anyhow::Result<()> {
+    let Ok(entries) = fs::read_dir(cache_dir) else {
+        return Ok(());
+    };
+
+    let mut files = vec![];
+    for entry in entries {
+        let path = entry?.path();
+        let Some(extension) = path.extension().and_then(|ext| ext.to_str()) else {
+            continue;
+        };
+        let metadata = fs::metadata(&path)?;
+        let modified = metadata.modified()?;
+        if extension == PARTIAL_EXTENSION {
+            let age = SystemTime::now()
+                .duration_since(modified)
+                .unwrap_or_default();
+            if age.as_secs() > 24 * 60 * 60 {
+                fs::remove_file(&path)?;
+            }
+        } else if extension == EXTENSION {
+            files.push((modified, metadata.len(), path));
+        }
+    }
+
+    let mut total: u64 = files.iter().map(|(_, size, _)| size).sum();
+    files.sort();
+    for (_, size, path) in files {
+        if total <= max_bytes {
+            break;
+        }
+        if path != keep {
+            fs::remove_file(&path)?;
+            total -= size;
+        }
+    }
+    Ok(())
+}
+
+/// Reads little-endian values from a byte slice, failing cleanly on truncation.
+struct ByteReader<'a> {
+    bytes: &'a [u8],
+}
+
+impl ByteReader<'_> {
+    fn take<const N: usize>(&mut self) -> anyhow::Result<[u8; N]> {
+        anyhow::ensure!(self.bytes.len() >= N, "cache is truncated");
+        let (head, rest) = self.bytes.split_at(N);
+        self.bytes = rest;
+        Ok(head.try_into().unwrap())
+    }
+
+    fn u8(&mut self) -> anyhow::Result<u8> {
+        Ok(self.take::<1>()?[0])
+    }
+
+    fn u32(&mut self) -> anyhow::Result<u32> {
+        Ok(u32::from_le_bytes(self.take()?))
+    }
+
+    fn u64(&mut self) -> anyhow::Result<u64> {
+        Ok(u64::from_le_bytes(self.take()?))
+    }
+
+    fn f64(&mut self) -> anyhow::Result<f64> {
+        Ok(f64::from_le_bytes(self.take()?))
+    }
+
+    fn vector(&mut self) -> anyhow::Result<Vector3<f64>> {
+        Ok(Vector3::new(self.f64()?, self.f64()?, self.f64()?))
+    }
+
+    /// A count of items at least `item_size` bytes each, checked against the remaining bytes
+    /// so a corrupt count cannot trigger a huge allocation.
+    fn count(&mut self, item_size: usize) -> anyhow::Result<usize> {
+        let count = self.u64()? as usize;
+        anyhow::ensure!(
+            count.saturating_mul(item_size) <= self.bytes.len(),
+            "cache is truncated"
+        );
+        Ok(count)
+    }
+}
+
+fn read_cache(
+    bytes: &[u8],
+    key: u64,
+    library: &HashMap<String, Arc<Material>>,
+    fallback: &Arc<Material>,
+) -> anyhow::Result<LinearBvh> {
+    let mut reader = ByteReader { bytes };
+    anyhow::ensure!(&reader.take::<4>()? == MAGIC, "not a BVH cache");
+    anyhow::ensure!(
+        reader.u32()? == FORMAT_VERSION,
+        "cache format version differs"
+    );
+    anyhow::ensure!(reader.u64()? == key, "cache key differs");
+
+    let material_count = reader.count(8)?;
+    let mut materials = Vec::with_capacity(material_count);
+    for _ in 0..material_count {
+        let length = reader.count(1)?;
+        let name = std::str::from_utf8(&reader.bytes[..length])?;
+        reader.bytes = &reader.bytes[length..];
+        materials.push(library.get(name).cloned().unwrap_or_else(|| {
+            eprintln!("material {name} is no longer in the MTL libraries");
+            fallback.clone()
+        }));
+    }
+
+    let triangle_count = reader.count(9 * 8 + 4 + 1)?;
+    let mut primitives = Vec::with_capacity(triangle_count);
+    for _ in 0..triangle_count {
+        let [p1, p2, p3] = [reader.vector()?, reader.vector()?, reader.vector()?].map(Point64);
+        let material = match reader.u32()? {
+            FALLBACK_MATERIAL => fallback.clone(),
+            slot => materials
+                .get(slot as usize)
+                .cloned()
+                .ok_or_else(|| anyhow::anyhow!("cache has a bad material slot"))?,
+        };
+        let mut triangle = Triangle::new(p1, p2, p3, material);
+        let flags = reader.u8()?;
+        if flags & HAS_TEXCOORDS != 0 {
+            let mut texcoords = [(0., 0.); 3];
+            for uv in &mut texcoords {
+                *uv = (reader.f64()?, reader.f64()?);
+            }
+            triangle = triangle.with_texcoords(texcoords);
+        }
+        if flags & HAS_VERTEX_NORMALS != 0 {
+            triangle = triangle.with_vertex_normals([
+                reader.vector()?,
+                reader.vector()?,
+                reader.vector()?,
+            ]);
+        }
+        primitives.push(Hittable::Triangle(triangle));
+    }
+
+    let node_count = reader.count(6 * 8 + 9)?;
+    let mut nodes = Vec::with_capacity(node_count);
+    for _ in 0..node_count {
+        nodes.push(LinearBvhNode {
+            bounds: StoredBounds::new(&AxisAlignedBoundingBox {
+                minimum: Point64(reader.vector()?),
+                maximum: Point64(reader.vector()?),
+            }),
+            offset: reader.u32()?,
+            primitive_count: reader.u32()?,
+            axis: reader.u8()?,
+        });
+    }
+    anyhow::ensure!(reader.bytes.is_empty(), "cache has trailing bytes");
+
+    LinearBvh::from_parts(nodes, primitives)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{load_obj_bvh_in, prune_cache};
+    use crate::data::color64::Color64;
+    use crate::data::point64::Point64;
+    use crate::data::ray::Ray;
+    use crate::hittables::Hittable;
+    use crate::hittables::bounded_volume_hierarchy::BvhBuildOptions;
+    use crate::materials::Material;
+    use crate::materials::lambertian::Lambertian;
+    use crate::textures::Texture;
+    use crate::util::obj::Subdivision;
+    use nalgebra::Vector3;
+    use std::fs;
+    use std::path::Path;
+    use std::time::{Duration, SystemTime};
+
+    fn cache_files(dir: &Path) -> Vec<String> {
+        let mut names: Vec<String> = fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn cached_teapot_matches_fresh_build() {
+        let dir = std::env::temp_dir().join(format!("bvh-cache-test-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let teapot = Path::new(env!("CARGO_MANIFEST_DIR")).join("resources/teapot.obj");
+        let material = Material::Lambertian(Lambertian {
+            albedo: Texture::solid(Color64::gray(0.5)),
+        });
+        let load = |scale: f64| {
+            load_obj_bvh_in(
+                &dir,
+                &teapot,
+                material.clone(),
+                scale,
+                Vector3::new(0., 1., 0.),
+                Subdivision {
+                    levels: 1,
+                    crease_angle: Some(60.),
+                },
+                &BvhBuildOptions::default(),
+            )
+            .unwrap()
+        };
+
+        let fresh = load(0.5);
+        let files = cache_files(&dir);
+        assert_eq!(files.len(), 1);
+        let cached = load(0.5);
+        assert!(matches!(cached, Hittable::LinearBvh(_)));
+
+        for x in -10..=10 {
+            let ray = Ray {
+                origin: Point64::new(x as f64 * 0.2, 2., 10.),
+                direction: Point64::new(0., -0.1, -1.),
+                exposure_time: 0.,
+            };
+            let expected = fresh.is_hit_by(&ray, 0.001, f64::INFINITY);
+            let actual = cached.is_hit_by(&ray, 0.001, f64::INFINITY);
+            assert_eq!(
+                actual
+                    .as_ref()
+                    .map(|hr| (hr.value, hr.u, hr.v, hr.normal.0)),
+                expected
+                    .as_ref()
+                    .map(|hr| (hr.value, hr.u, hr.v, hr.normal.0)),
+            );
+        }
+
+        // A corrupt cache is rebuilt in place.
+        let path = dir.join(&files[0]);
+        let mut bytes = fs::read(&path).unwrap();
+        bytes.truncate(bytes.len() / 2);
+        fs::write(&path, bytes).unwrap();
+        load(0.5);
+        assert_eq!(cache_files(&dir), files);
+
+        // New parameters get a cache of their own, next to the old one.
+        load(0.25);
+        let new_files = cache_files(&dir);
+        assert_eq!(new_files.len(), 2);
+        assert!(new_files.contains(&files[0]));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn cached_meshes_keep_their_mtl_materials() {
+        let dir = std::env::temp_dir().join(format!("bvh-cache-mtl-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("cache")).unwrap();
+        fs::write(
+            dir.join("tiles.obj"),
+            "mtllib tiles.mtl\n\
+             v 0 0 0\nv 1 0 0\nv 1 0.5 1\nv 2 0 0\nv 3 0 0\nv 3 0.5 1\n\
+             usemtl red\nf 1 3 2\n\
+             usemtl missing\nf 4 6 5\n",
+        )
+        .unwrap();
+        let red_mtl = |red: f64| format!("newmtl red\nKd {red} 0 0\n");
+        fs::write(dir.join("tiles.mtl"), red_mtl(0.75)).unwrap();
+
+        let fallback = Material::Lambertian(Lambertian {
+            albedo: Texture::solid(Color64::gray(0.5)),
+        });
+        let load = || {
+            load_obj_bvh_in(
+                &dir.join("cache"),
+                &dir.join("tiles.obj"),
+                fallback.clone(),
+                1.,
+                Vector3::zeros(),
+                Subdivision::default(),
+                &BvhBuildOptions::default(),
+            )
+            .unwrap()
+        };
+        let red_at = |bvh: &Hittable, x: f64| {
+            let ray = Ray {
+                origin: Point64::new(x, 1., 0.25),
+                direction: Point64::new(0., -1., 0.),
+                exposure_time: 0.,
+            };
+            match bvh.is_hit_by(&ray, 0.001, f64::INFINITY).unwrap().material {
+                Material::Principled(p) => {
+                    Some(p.base_color.value(0., 0., &Point64::default()).r())
+                }
+                _ => None,
+            }
+        };
+
+        let fresh = load();
+        assert_eq!(red_at(&fresh, 0.75), Some(0.75));
+        assert_eq!(red_at(&fresh, 2.75), None);
+
+        // Materials are read from the MTL again, so an edit shows without a rebuild.
+        fs::write(dir.join("tiles.mtl"), red_mtl(0.25)).unwrap();
+        let cached = load();
+        assert_eq!(cache_files(&dir.join("cache")).len(), 1);
+        assert_eq!(red_at(&cached, 0.75), Some(0.25));
+        assert_eq!(red_at(&cached, 2.75), None);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn pruning_drops_least_recently_used_files() {
+        let dir = std::env::temp_dir().join(format!("bvh-cache-prune-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+
+        let now = SystemTime::now();
+        let file = |name: &str, age_hours: u64| {
+            let path = dir.join(name);
+            fs::write(&path, [0u8; 100]).unwrap();
+            let file = fs::File::options().write(true).open(&path).unwrap();
+            file.set_modified(now - Duration::from_secs(age_hours * 60 * 60))
+                .unwrap();
+            path
+        };
+        file("old-0000000000000001.bvhcache", 3);
+        let kept = file("oldest-0000000000000002.bvhcache", 4);
+        file("new-0000000000000003.bvhcache", 1);
+        file("torn-0000000000000004.1-0.partial", 30);
+        file("fresh-0000000000000005.1-1.partial", 1);
+        file("notes.txt", 5);
+
+        prune_cache(&dir, 250, &kept).unwrap();
+        assert_eq!(
+            cache_files(&dir),
+            [
+                "fresh-0000000000000005.1-1.partial",
+                "new-0000000000000003.bvhcache",
+                "notes.txt",
+                "oldest-0000000000000002.bvhcache",
+            ]
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
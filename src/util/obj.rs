@@ -1,13 +1,20 @@
-//! Load Wavefront OBJ meshes as [`Hittable::Triangle`] lists (via `tobj`).
+//! Load Wavefront OBJ meshes as [`Hittable::Triangle`] lists (via `tobj`), optionally
+//! smoothed with Loop subdivision and with their MTL materials mapped onto [`Principled`].
 
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
 use std::path::Path;
+use std::sync::Arc;
 
 use nalgebra::Vector3;
 
+use crate::data::color64::Color64;
 use crate::data::point64::Point64;
 use crate::hittables::Hittable;
-use crate::hittables::triangle::Triangle;
-use crate::materials::Material;
+use crate::materials::{Material, Principled};
+use crate::textures::Texture;
+use crate::textures::image::ImageTexture;
+use crate::util::mesh::TriangleMesh;
 
 /// Axis-aligned bounds of all vertex positions in the OBJ (model space).
 #[derive(Clone, Copy, Debug)]
@@ -29,13 +36,7 @@ impl ObjAxisBounds {
 }
 
 pub fn obj_mesh_axis_bounds(path: &Path) -> anyhow::Result<ObjAxisBounds> {
-    let (models, _materials) = tobj::load_obj(
-        path,
-        &tobj::LoadOptions {
-            triangulate: true,
-            ..Default::default()
-        },
-    )?;
+    let (models, _materials) = tobj::load_obj(path, &load_options())?;
 
     let mut x_min = f64::INFINITY;
     let mut x_max = f64::NEG_INFINITY;
@@ -81,59 +82,279 @@ pub fn obj_mesh_y_bounds(path: &Path) -> anyhow::Result<(f64, f64)> {
     Ok((b.y_min, b.y_max))
 }
 
-/// Triangulate faces and build one [`Triangle`] per face, with uniform scale and translation.
-pub fn load_obj_triangles(
+/// How [`load_obj_triangles`] refines a mesh before building triangles.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Subdivision {
+    /// Rounds of Loop subdivision; each one splits every triangle in four.
+    pub levels: u32,
+    /// Edges whose faces meet at more than this many degrees stay sharp.
+    pub crease_angle: Option<f64>,
+}
+
+/// Triangulate faces into an indexed mesh, with uniform scale and translation. Vertices at
+/// identical positions are merged so faces that the file lists separately still share edges.
+pub fn load_obj_mesh(
     path: &Path,
-    material: Material,
     scale: f64,
     offset: Vector3<f64>,
-) -> anyhow::Result<Vec<Hittable>> {
-    let (models, _materials) = tobj::load_obj(
-        path,
-        &tobj::LoadOptions {
-            triangulate: true,
-            ..Default::default()
-        },
-    )?;
+) -> anyhow::Result<TriangleMesh> {
+    let (models, _materials) = tobj::load_obj(path, &load_options())?;
 
-    let mut out = Vec::new();
+    let mesh = mesh_from_models(&models, scale, offset);
+    if mesh.faces.is_empty() {
+        anyhow::bail!("no triangles in OBJ: {}", path.display());
+    }
+    Ok(mesh)
+}
+
+fn load_options() -> tobj::LoadOptions {
+    tobj::LoadOptions {
+        triangulate: true,
+        ..Default::default()
+    }
+}
+
+fn mesh_from_models<'a>(
+    models: impl IntoIterator<Item = &'a tobj::Model>,
+    scale: f64,
+    offset: Vector3<f64>,
+) -> TriangleMesh {
+    let mut mesh = TriangleMesh::default();
+    let mut texcoords = vec![];
+    let mut has_texcoords = true;
+    let mut vertex_ids: HashMap<[u64; 3], usize> = HashMap::new();
 
     for model in models {
-        let mesh = &model.mesh;
-        let pos = &mesh.positions;
+        let m = &model.mesh;
+        let pos = &m.positions;
         if pos.len() < 3 {
             continue;
         }
+        has_texcoords &= !m.texcoord_indices.is_empty();
 
-        for tri in mesh.indices.chunks(3) {
-            if tri.len() != 3 {
+        for (face, tri) in m.indices.chunks(3).enumerate() {
+            if tri.len() != 3 || tri.iter().any(|&i| i as usize * 3 + 2 >= pos.len()) {
                 continue;
             }
-            let i0 = tri[0] as usize * 3;
-            let i1 = tri[1] as usize * 3;
-            let i2 = tri[2] as usize * 3;
-            if i2 + 2 >= pos.len() {
-                continue;
+
+            let corners = [0, 1, 2].map(|k| {
+                let p = transform_vertex(pos, tri[k] as usize * 3, scale, offset).0;
+                let key = [p.x.to_bits(), p.y.to_bits(), p.z.to_bits()];
+                *vertex_ids.entry(key).or_insert_with(|| {
+                    mesh.positions.push(p);
+                    mesh.positions.len() - 1
+                })
+            });
+            mesh.faces.push(corners);
+
+            if has_texcoords {
+                texcoords.push([0, 1, 2].map(|k| {
+                    let t = m.texcoord_indices[face * 3 + k] as usize * 2;
+                    (m.texcoords[t] as f64, m.texcoords[t + 1] as f64)
+                }));
             }
+        }
+    }
+
+    if has_texcoords && !mesh.faces.is_empty() {
+        mesh.texcoords = Some(texcoords);
+    }
+    mesh
+}
+
+/// Triangulate faces and build one [`Triangle`] per face, with uniform scale and translation,
+/// after optionally subdividing the mesh.
+pub fn load_obj_triangles(
+    path: &Path,
+    material: Material,
+    scale: f64,
+    offset: Vector3<f64>,
+    subdivision: Subdivision,
+) -> anyhow::Result<Vec<Hittable>> {
+    let mut mesh = load_obj_mesh(path, scale, offset)?;
+    if let Some(degrees) = subdivision.crease_angle {
+        mesh.mark_creases_by_angle(degrees);
+    }
 
-            let p1 = transform_vertex(pos, i0, scale, offset);
-            let p2 = transform_vertex(pos, i1, scale, offset);
-            let p3 = transform_vertex(pos, i2, scale, offset);
+    Ok(mesh
+        .subdivided_times(subdivision.levels)
+        .triangles(material))
+}
 
-            out.push(Hittable::Triangle(Triangle::new(
-                p1,
-                p2,
-                p3,
-                material.clone(),
-            )));
+/// Like [`load_obj_triangles`], but each group of faces gets its MTL material mapped onto
+/// [`Principled`] by [`principled_from_mtl`]. Faces without a material get `fallback`.
+pub fn load_obj_triangles_with_materials(
+    path: &Path,
+    fallback: Material,
+    scale: f64,
+    offset: Vector3<f64>,
+    subdivision: Subdivision,
+) -> anyhow::Result<Vec<Hittable>> {
+    let source = fs::read_to_string(path)?;
+    let library = load_mtl_materials(path, &source);
+    load_obj_triangles_from_library(
+        path,
+        &library,
+        Arc::new(fallback),
+        scale,
+        offset,
+        subdivision,
+    )
+}
+
+/// [`load_obj_triangles_with_materials`] with the OBJ's materials already loaded by
+/// [`load_mtl_materials`].
+pub(crate) fn load_obj_triangles_from_library(
+    path: &Path,
+    library: &HashMap<String, Arc<Material>>,
+    fallback: Arc<Material>,
+    scale: f64,
+    offset: Vector3<f64>,
+    subdivision: Subdivision,
+) -> anyhow::Result<Vec<Hittable>> {
+    let (models, materials) = tobj::load_obj(path, &load_options())?;
+    // Only the names are needed here; a library that fails to load was reported already.
+    let materials = materials.unwrap_or_default();
+
+    let mut groups: BTreeMap<Option<&str>, Vec<&tobj::Model>> = BTreeMap::new();
+    for model in &models {
+        let name = model
+            .mesh
+            .material_id
+            .and_then(|id| materials.get(id))
+            .map(|mtl| mtl.name.as_str());
+        groups.entry(name).or_default().push(model);
+    }
+
+    let mut triangles = vec![];
+    for (name, models) in groups {
+        let mut mesh = mesh_from_models(models, scale, offset);
+        if let Some(degrees) = subdivision.crease_angle {
+            mesh.mark_creases_by_angle(degrees);
         }
+        let material = name.and_then(|name| library.get(name)).unwrap_or(&fallback);
+        triangles.extend(
+            mesh.subdivided_times(subdivision.levels)
+                .triangles(material.clone()),
+        );
     }
 
-    if out.is_empty() {
+    if triangles.is_empty() {
         anyhow::bail!("no triangles in OBJ: {}", path.display());
     }
+    Ok(triangles)
+}
+
+/// The materials of every MTL library that the OBJ `source` at `path` names, mapped onto
+/// [`Principled`] by [`principled_from_mtl`] and keyed by name. Libraries that fail to load
+/// are reported and skipped.
+pub fn load_mtl_materials(path: &Path, source: &str) -> HashMap<String, Arc<Material>> {
+    let directory = path.parent().unwrap_or(Path::new("."));
+    let mut library = HashMap::new();
+    for line in source.lines() {
+        let Some(file) = line.trim().strip_prefix("mtllib") else {
+            continue;
+        };
+        let file = directory.join(file.trim());
+        match tobj::load_mtl(&file) {
+            Ok((materials, _)) => {
+                for mtl in materials {
+                    library.entry(mtl.name.clone()).or_insert_with(|| {
+                        Arc::new(Material::Principled(Box::new(principled_from_mtl(
+                            &mtl, directory,
+                        ))))
+                    });
+                }
+            }
+            Err(e) => eprintln!("could not load materials from {}: {e}", file.display()),
+        }
+    }
+    library
+}
 
-    Ok(out)
+/// Maps an MTL material onto [`Principled`]. The PBR extension keys (`Pr`, `Pm`, `Ps`, `Pc`,
+/// `Pcr`, `Ke` and their `map_` textures) are used where present; otherwise roughness comes
+/// from the Phong exponent `Ns`. Materials whose illumination model refracts (`illum` 4, 6,
+/// 7 or 9) transmit as much light as their filter `Tf` lets through. Texture paths are
+/// relative to `directory`.
+pub fn principled_from_mtl(mtl: &tobj::Material, directory: &Path) -> Principled {
+    let defaults = Principled::default();
+    let param = |key: &str| mtl.unknown_param.get(key).map(String::as_str);
+    let number = |key: &str| param(key).and_then(|value| value.trim().parse::<f64>().ok());
+    let image = |file: &str| {
+        Texture::Image(ImageTexture::new(
+            directory.join(file).to_string_lossy().into_owned(),
+        ))
+    };
+    let color = |rgb: [f32; 3]| Color64::new(rgb[0] as f64, rgb[1] as f64, rgb[2] as f64);
+    let scalar = |key: &str, default: Texture| match (param(&format!("map_{key}")), number(key)) {
+        (Some(file), _) => image(file),
+        (None, Some(value)) => Texture::solid(Color64::gray(value)),
+        (None, None) => default,
+    };
+
+    let base_color = match (&mtl.diffuse_texture, mtl.diffuse) {
+        (Some(file), _) => image(file),
+        (None, Some(diffuse)) => Texture::solid(color(diffuse)),
+        (None, None) => defaults.base_color,
+    };
+    // Blinn–Phong exponent to Beckmann alpha, then alpha to perceptual roughness.
+    let phong_roughness = mtl.shininess.map_or(defaults.roughness, |shininess| {
+        let alpha = (2. / (shininess as f64 + 2.)).sqrt();
+        Texture::solid(Color64::gray(alpha.sqrt()))
+    });
+    let emission = match (param("map_Ke"), param("Ke")) {
+        (Some(file), _) => image(file),
+        (None, Some(value)) => {
+            let rgb: Vec<f64> = value
+                .split_whitespace()
+                .filter_map(|c| c.parse().ok())
+                .collect();
+            match rgb[..] {
+                [r, g, b] => Texture::solid(Color64::new(r, g, b)),
+                [gray] => Texture::solid(Color64::gray(gray)),
+                _ => defaults.emission,
+            }
+        }
+        (None, None) => defaults.emission,
+    };
+    // Dissolve (`d`, or `Tr` as its complement) is coverage, like an alpha channel, rather
+    // than glass, so it is left alone.
+    let transmission = match mtl.illumination_model {
+        Some(4 | 6 | 7 | 9) => {
+            let filter: Vec<f64> = param("Tf")
+                .unwrap_or("1")
+                .split_whitespace()
+                .filter_map(|c| c.parse().ok())
+                .collect();
+            match filter[..] {
+                [] => Texture::solid(Color64::gray(1.)),
+                _ => Texture::solid(Color64::gray(
+                    filter.iter().sum::<f64>() / filter.len() as f64,
+                )),
+            }
+        }
+        _ => defaults.transmission,
+    };
+    let sheen = match number("Ps") {
+        Some(value) => Texture::solid(Color64::gray(value)),
+        None => defaults.sheen,
+    };
+
+    Principled {
+        base_color,
+        metallic: scalar("Pm", defaults.metallic),
+        roughness: scalar("Pr", phong_roughness),
+        specular: defaults.specular,
+        sheen,
+        clearcoat: scalar("Pc", defaults.clearcoat),
+        clearcoat_roughness: scalar("Pcr", defaults.clearcoat_roughness),
+        transmission,
+        index_of_refraction: mtl
+            .optical_density
+            .map_or(defaults.index_of_refraction, f64::from),
+        emission,
+    }
 }
 
 fn transform_vertex(positions: &[f32], i: usize, scale: f64, offset: Vector3<f64>) -> Point64 {
@@ -143,3 +364,114 @@ fn transform_vertex(positions: &[f32], i: usize, scale: f64, offset: Vector3<f64
         positions[i + 2] as f64 * scale + offset.z,
     )
 }
+
+#[cfg(test)]
+mod test {
+    use super::load_obj_triangles_with_materials;
+    use crate::data::color64::Color64;
+    use crate::data::point64::Point64;
+    use crate::hittables::Hittable;
+    use crate::materials::Material;
+    use crate::materials::lambertian::Lambertian;
+    use crate::textures::Texture;
+    use crate::util::obj::Subdivision;
+    use approx::assert_abs_diff_eq;
+    use nalgebra::Vector3;
+    use std::fs;
+
+    #[test]
+    fn mtl_materials_map_onto_principled() {
+        let dir = std::env::temp_dir().join(format!("obj-mtl-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("quads.mtl"),
+            "newmtl brass\nKd 0.8 0.6 0.2\nNs 250\nPm 1\n\n\
+             newmtl lamp\nKd 0.1 0.1 0.1\nKe 5 4 3\nd 0.25\nNi 1.33\n\n\
+             newmtl glass\nillum 7\nTf 0.9 0.8 0.7\nNi 1.45\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.join("quads.obj"),
+            "mtllib quads.mtl\n\
+             v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\n\
+             v 0 0 1\nv 1 0 1\nv 1 1 1\nv 0 1 1\n\
+             usemtl brass\nf 1 2 3 4\n\
+             usemtl lamp\nf 5 6 7\n\
+             usemtl missing\nf 5 7 8\n\
+             usemtl glass\nf 1 5 8\n",
+        )
+        .unwrap();
+
+        let fallback = Material::Lambertian(Lambertian {
+            albedo: Texture::solid(Color64::gray(0.5)),
+        });
+        let triangles = load_obj_triangles_with_materials(
+            &dir.join("quads.obj"),
+            fallback,
+            1.,
+            Vector3::zeros(),
+            Subdivision::default(),
+        )
+        .unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        let origin = Point64::default();
+        let materials: Vec<&Material> = triangles
+            .iter()
+            .map(|triangle| match triangle {
+                Hittable::Triangle(triangle) => triangle.material.as_ref(),
+                _ => panic!("expected triangles"),
+            })
+            .collect();
+        assert_eq!(materials.len(), 5);
+
+        let brass = materials.iter().filter_map(|m| match m {
+            Material::Principled(p) if p.metallic.value(0., 0., &origin).r() == 1. => Some(p),
+            _ => None,
+        });
+        assert_eq!(brass.clone().count(), 2);
+        for brass in brass {
+            assert_abs_diff_eq!(
+                brass.base_color.value(0., 0., &origin).0,
+                Color64::new(0.8, 0.6, 0.2).0,
+                epsilon = 1e-6
+            );
+            // A sharp Phong highlight maps to a low roughness.
+            assert!(brass.roughness.value(0., 0., &origin).r() < 0.4);
+        }
+
+        let ior = |ior: f64| {
+            materials
+                .iter()
+                .find_map(|m| match m {
+                    Material::Principled(p) if (p.index_of_refraction - ior).abs() < 1e-6 => {
+                        Some(p)
+                    }
+                    _ => None,
+                })
+                .unwrap()
+        };
+        let lamp = ior(1.33);
+        assert_eq!(
+            lamp.emission.value(0., 0., &origin),
+            Color64::new(5., 4., 3.)
+        );
+        // Dissolve is coverage, not glass.
+        assert_eq!(lamp.transmission.value(0., 0., &origin).r(), 0.);
+
+        let glass = ior(1.45);
+        assert_abs_diff_eq!(
+            glass.transmission.value(0., 0., &origin).r(),
+            0.8,
+            epsilon = 1e-12
+        );
+
+        assert_eq!(
+            materials
+                .iter()
+                .filter(|m| matches!(m, Material::Lambertian(_)))
+                .count(),
+            1
+        );
+    }
+}
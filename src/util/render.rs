@@ -1,3 +1,9 @@
+#![allow(clippy::too_many_arguments)]
+
+use std::array;
+use std::fmt;
+use std::ops::Range;
+use std::str::FromStr;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::mpsc::channel;
@@ -7,9 +13,42 @@ use threadpool::ThreadPool;
 
 use crate::camera::Camera;
 use crate::data::color64::Color64;
-use crate::hittables::Hittable;
+use crate::data::ray::{RR_MIN_BOUNCES, Ray};
+use crate::data::ray_packet::{PACKET_WIDTH, RayPacket};
+use crate::hittables::{HitRecord, Hittable};
 use crate::util::worlds::World;
 
+/// How [`render_frame`] follows the paths of a group of rows.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RenderMode {
+    /// Each sample's path is traced to its end before the next sample starts.
+    #[default]
+    Recursive,
+    /// Every path of the group advances one bounce at a time; see [`render_rows_wavefront`].
+    Wavefront,
+}
+
+impl FromStr for RenderMode {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "recursive" => Ok(RenderMode::Recursive),
+            "wavefront" => Ok(RenderMode::Wavefront),
+            _ => anyhow::bail!("unknown render mode: {s}"),
+        }
+    }
+}
+
+impl fmt::Display for RenderMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RenderMode::Recursive => write!(f, "recursive"),
+            RenderMode::Wavefront => write!(f, "wavefront"),
+        }
+    }
+}
+
 /// Renders one sample per pixel across `row_y`, returning `(flipped_y, samples)`.
 ///
 /// `flipped_y` is `render_h - row_y - 1` so the caller can write directly into a
@@ -27,18 +66,177 @@ pub fn render_row(
     let du = render_w.saturating_sub(1).max(1) as f64;
     let dv = render_h.saturating_sub(1).max(1) as f64;
     let flipped_y = render_h - row_y - 1;
-    let row = (0..render_w)
-        .map(|x| {
+    let mut row = Vec::with_capacity(render_w as usize);
+    // Neighbouring primary rays are traced through the scene as a packet; the final packet
+    // of a row repeats its last pixel to fill the unused lanes.
+    for first_x in (0..render_w).step_by(PACKET_WIDTH) {
+        let rays: [Ray; PACKET_WIDTH] = array::from_fn(|lane| {
+            let x = (first_x + lane as u32).min(render_w - 1);
             let u = (x as f64 + rng.random::<f64>()) / du;
             let v = (row_y as f64 + rng.random::<f64>()) / dv;
-            camera
-                .get_ray(u, v)
-                .color_in_world(hittable, background, max_depth, rng)
-        })
-        .collect();
+            camera.get_ray(u, v)
+        });
+        let packet = RayPacket::new(rays);
+        let hits = hittable.packet_hits(&packet, 0.001, [f64::INFINITY; PACKET_WIDTH]);
+        let lanes = (render_w - first_x).min(PACKET_WIDTH as u32) as usize;
+        for (ray, hit) in packet.rays.iter().zip(hits).take(lanes) {
+            row.push(ray.color_in_world_from_hit(hit, hittable, background, max_depth, rng));
+        }
+    }
     (flipped_y, row)
 }
 
+/// Renders `num_samples` samples per pixel for each row in `rows`, returning the rows in
+/// order with each pixel holding the *sum* of its samples.
+pub fn render_rows(
+    mode: RenderMode,
+    camera: &Camera,
+    hittable: &Hittable,
+    background: &Color64,
+    rows: Range<u32>,
+    render_w: u32,
+    render_h: u32,
+    max_depth: i32,
+    num_samples: u32,
+    rng: &mut impl Rng,
+) -> Vec<Vec<Color64>> {
+    match mode {
+        RenderMode::Recursive => rows
+            .map(|row_y| {
+                let mut accumulated = vec![Color64::new(0., 0., 0.); render_w as usize];
+                for _ in 0..num_samples {
+                    let (_, row) = render_row(
+                        camera, hittable, background, row_y, render_w, render_h, max_depth, rng,
+                    );
+                    for (i, c) in row.into_iter().enumerate() {
+                        accumulated[i] += c;
+                    }
+                }
+                accumulated
+            })
+            .collect(),
+        RenderMode::Wavefront => render_rows_wavefront(
+            camera,
+            hittable,
+            background,
+            rows,
+            render_w,
+            render_h,
+            max_depth,
+            num_samples,
+            rng,
+        ),
+    }
+}
+
+/// A path still being traced by [`render_rows_wavefront`].
+struct Path {
+    /// Index of the pixel within the group of rows.
+    pixel: usize,
+    ray: Ray,
+    /// Product of the attenuations along the path so far.
+    throughput: Color64,
+}
+
+/// Orders rays by the octant of their direction, then by the octant of the origin around
+/// the world origin, so rays likely to visit the same parts of the scene are traced
+/// together.
+fn ray_sort_key(ray: &Ray) -> u8 {
+    let octant = |v: &nalgebra::Vector3<f64>| {
+        (v.x < 0.) as u8 | ((v.y < 0.) as u8) << 1 | ((v.z < 0.) as u8) << 2
+    };
+    octant(&ray.direction.0) << 3 | octant(&ray.origin.0)
+}
+
+/// [`render_rows`] in wavefront order: every sample of every pixel in `rows` starts as a
+/// camera ray, and then each round sorts the live rays, finds all of their hits, and
+/// shades the hits grouped by material, which yields the rays of the next round. Paths end
+/// as [`Ray::color_in_world_from_hit`]'s do, so both modes converge to the same image.
+pub fn render_rows_wavefront(
+    camera: &Camera,
+    hittable: &Hittable,
+    background: &Color64,
+    rows: Range<u32>,
+    render_w: u32,
+    render_h: u32,
+    max_depth: i32,
+    num_samples: u32,
+    rng: &mut impl Rng,
+) -> Vec<Vec<Color64>> {
+    let width = render_w as usize;
+    let du = render_w.saturating_sub(1).max(1) as f64;
+    let dv = render_h.saturating_sub(1).max(1) as f64;
+    let mut sums = vec![Color64::new(0., 0., 0.); rows.len() * width];
+
+    let mut paths = Vec::with_capacity(sums.len() * num_samples as usize);
+    for (row, row_y) in rows.enumerate() {
+        for x in 0..render_w {
+            for _ in 0..num_samples {
+                let u = (x as f64 + rng.random::<f64>()) / du;
+                let v = (row_y as f64 + rng.random::<f64>()) / dv;
+                paths.push(Path {
+                    pixel: row * width + x as usize,
+                    ray: camera.get_ray(u, v),
+                    throughput: Color64::new(1., 1., 1.),
+                });
+            }
+        }
+    }
+
+    for bounce in 0..max_depth {
+        if paths.is_empty() {
+            break;
+        }
+        paths.sort_unstable_by_key(|path| ray_sort_key(&path.ray));
+
+        let hits: Vec<Option<HitRecord>> = paths
+            .iter()
+            .map(|path| hittable.is_hit_by(&path.ray, 0.001, f64::INFINITY))
+            .collect();
+        let mut order: Vec<usize> = (0..paths.len()).collect();
+        order.sort_by_key(|&i| {
+            hits[i]
+                .as_ref()
+                .map_or(0, |hr| 1 + hr.material.variant_index())
+        });
+
+        let mut next = Vec::with_capacity(paths.len());
+        for i in order {
+            let path = &paths[i];
+            let Some(hit_record) = &hits[i] else {
+                sums[path.pixel] += path.throughput.component_mul(background);
+                continue;
+            };
+
+            let emitted =
+                hit_record
+                    .material
+                    .emitted(hit_record.u, hit_record.v, &hit_record.location);
+            sums[path.pixel] += path.throughput.component_mul(&emitted);
+
+            let Some(scatter_record) = hit_record.material.scatter(&path.ray, hit_record) else {
+                continue;
+            };
+            let mut att = scatter_record.attenuation;
+            if bounce >= RR_MIN_BOUNCES {
+                let p = att.r().max(att.g()).max(att.b()).clamp(0.001, 1.0);
+                if rng.random::<f64>() > p {
+                    continue;
+                }
+                att = Color64(att.0 / p);
+            }
+            next.push(Path {
+                pixel: path.pixel,
+                ray: scatter_record.scattered,
+                throughput: path.throughput.component_mul(&att),
+            });
+        }
+        paths = next;
+    }
+
+    sums.chunks(width).map(<[Color64]>::to_vec).collect()
+}
+
 /// Renders `num_samples` samples per pixel across all rows, dispatching work in
 /// groups of `rows_per_task` rows to an internal thread pool.
 ///
@@ -48,6 +246,7 @@ pub fn render_row(
 /// longer equals `expected` mid-pass (interactive view-changed abort). Pass
 /// `None` for `cancel` to disable cancellation (batch rendering).
 pub fn render_frame(
+    mode: RenderMode,
     camera: Camera,
     world: Arc<World>,
     render_w: u32,
@@ -74,23 +273,19 @@ pub fn render_frame(
                 return;
             }
             let mut rng = rand::rng();
-            for row_y in y..y_end {
-                let mut accumulated = vec![Color64::new(0., 0., 0.); render_w as usize];
-                for _ in 0..num_samples {
-                    let (_, row) = render_row(
-                        &camera,
-                        &world.hittable,
-                        &world.background_color,
-                        row_y,
-                        render_w,
-                        render_h,
-                        max_depth,
-                        &mut rng,
-                    );
-                    for (i, c) in row.into_iter().enumerate() {
-                        accumulated[i] += c;
-                    }
-                }
+            let rows = render_rows(
+                mode,
+                &camera,
+                &world.hittable,
+                &world.background_color,
+                y..y_end,
+                render_w,
+                render_h,
+                max_depth,
+                num_samples,
+                &mut rng,
+            );
+            for (row_y, accumulated) in (y..y_end).zip(rows) {
                 let flipped_y = render_h - row_y - 1;
                 let _ = tx.send((flipped_y, accumulated));
             }
@@ -110,6 +305,9 @@ pub fn render_frame(
         rows.push(row);
         println!("{} / {} scanlines done", idx, world.image_height);
     }
+    // Every row has arrived; wait for the workers to drop their handles on the world too, so
+    // the caller holds the only one again and can update it in place.
+    pool.join();
 
     if cancel.is_none_or(|(generation, expected)| generation.load(Ordering::Acquire) == expected) {
         Some(rows)
@@ -117,3 +315,105 @@ pub fn render_frame(
         None
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::{RenderMode, render_rows};
+    use crate::camera::Camera;
+    use crate::data::color64::Color64;
+    use crate::data::point64::Point64;
+    use crate::hittables::Hittable;
+    use crate::hittables::hittable_vec::HittableVec;
+    use crate::hittables::plane::Plane;
+    use crate::hittables::quad::Quad;
+    use crate::hittables::sphere::Sphere;
+    use crate::materials::Material;
+    use crate::materials::dielectric::Dielectric;
+    use crate::materials::diffuse_light::DiffuseLight;
+    use crate::materials::lambertian::Lambertian;
+    use crate::materials::metal::Metal;
+    use crate::textures::Texture;
+    use nalgebra::Vector3;
+
+    #[test]
+    fn wavefront_converges_to_the_recursive_image() {
+        let sphere = |x: f64, material: Material| {
+            Hittable::Sphere(Sphere {
+                center: Point64::new(x, 1., 0.),
+                radius: 1.,
+                material,
+            })
+        };
+        let world = Hittable::HittableVec(HittableVec {
+            hittables: vec![
+                Hittable::Plane(Plane::horizontal(
+                    0.,
+                    Material::Lambertian(Lambertian {
+                        albedo: Texture::solid(Color64::gray(0.6)),
+                    }),
+                )),
+                sphere(
+                    -2.2,
+                    Material::Lambertian(Lambertian {
+                        albedo: Texture::solid(Color64::new(0.8, 0.3, 0.2)),
+                    }),
+                ),
+                sphere(0., Material::Dielectric(Dielectric::new(1.5))),
+                sphere(
+                    2.2,
+                    Material::Metal(Metal {
+                        albedo: Color64::gray(0.8),
+                        fuzz: 0.2,
+                    }),
+                ),
+                Hittable::Quad(Quad::new(
+                    Point64::new(-2., 4., -2.),
+                    Point64::new(4., 0., 0.),
+                    Point64::new(0., 0., 4.),
+                    Material::DiffuseLight(DiffuseLight::new(Color64::gray(4.))),
+                )),
+            ],
+        });
+        let camera = Camera::new(
+            Point64::new(0., 2., 9.),
+            Point64::new(0., 1., 0.),
+            Vector3::y(),
+            40.,
+            1.5,
+            0.,
+            1.,
+            0.0..1.0,
+        );
+        let background = Color64::new(0.3, 0.4, 0.6);
+
+        let mean = |mode| {
+            let rows = render_rows(
+                mode,
+                &camera,
+                &world,
+                &background,
+                0..16,
+                24,
+                16,
+                20,
+                64,
+                &mut rand::rng(),
+            );
+            let total = rows
+                .iter()
+                .flatten()
+                .fold(Vector3::zeros(), |sum, c| sum + c.0);
+            total / (24. * 16. * 64.)
+        };
+
+        let recursive = mean(RenderMode::Recursive);
+        let wavefront = mean(RenderMode::Wavefront);
+        for channel in 0..3 {
+            let (a, b) = (recursive[channel], wavefront[channel]);
+            assert!(
+                (a - b).abs() < 0.03 * a,
+                "channel {channel}: recursive {a}, wavefront {b}"
+            );
+        }
+    }
+}
@@ -0,0 +1,154 @@
+use crate::data::point64::Point64;
+use crate::hittables::Hittable;
+use crate::hittables::curve::{Curve, CurveShape};
+use crate::hittables::quad::Quad;
+use crate::materials::Material;
+use anyhow::Context;
+use rand::Rng;
+use std::path::Path;
+
+/// Reads strands from a text file with one cubic Bézier per line: the four control points'
+/// `x y z` coordinates followed by the root and tip widths, fourteen numbers in all. Blank
+/// lines and lines starting with `#` are ignored.
+pub fn load_strands(
+    path: &Path,
+    shape: CurveShape,
+    material: Material,
+) -> anyhow::Result<Vec<Hittable>> {
+    let text = std::fs::read_to_string(path)
+        .with_context(|| format!("could not read strands from {}", path.display()))?;
+
+    parse_strands(&text, shape, material)
+        .with_context(|| format!("could not parse strands in {}", path.display()))
+}
+
+fn parse_strands(
+    text: &str,
+    shape: CurveShape,
+    material: Material,
+) -> anyhow::Result<Vec<Hittable>> {
+    let mut curves = vec![];
+
+    for (index, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let values = line
+            .split_whitespace()
+            .map(str::parse::<f64>)
+            .collect::<Result<Vec<_>, _>>()
+            .with_context(|| format!("line {}: bad number", index + 1))?;
+        if values.len() != 14 {
+            anyhow::bail!(
+                "line {}: expected 14 numbers, found {}",
+                index + 1,
+                values.len()
+            );
+        }
+
+        let point = |i: usize| Point64::new(values[3 * i], values[3 * i + 1], values[3 * i + 2]);
+        curves.push(Hittable::Curve(Curve::new(
+            [point(0), point(1), point(2), point(3)],
+            (values[12], values[13]),
+            shape,
+            material.clone(),
+        )));
+    }
+
+    Ok(curves)
+}
+
+/// Plants `count` randomly placed, leaning ribbon blades on `ground`, growing along the side
+/// its `u × v` normal points to. Blade heights vary between half and all of `height`; blades
+/// taper from `width` at the root to a tenth of it at the tip.
+pub fn scatter_grass(
+    ground: &Quad,
+    count: usize,
+    height: f64,
+    width: f64,
+    material: Material,
+    rng: &mut impl Rng,
+) -> Vec<Hittable> {
+    let up = ground.u.0.cross(&ground.v.0).normalize();
+    let across = ground.u.0.normalize();
+    let along = up.cross(&across);
+
+    (0..count)
+        .map(|_| {
+            let root =
+                ground.q.0 + ground.u.0 * rng.random::<f64>() + ground.v.0 * rng.random::<f64>();
+            let blade_height = height * rng.random_range(0.5..1.0);
+            let angle = rng.random_range(0.0..std::f64::consts::TAU);
+            let lean = (across * angle.cos() + along * angle.sin())
+                * blade_height
+                * rng.random_range(0.1..0.6);
+
+            let at =
+                |rise: f64, bend: f64| Point64(root + up * (blade_height * rise) + lean * bend);
+            Hittable::Curve(Curve::new(
+                [Point64(root), at(0.4, 0.), at(0.75, 0.3), at(1., 1.)],
+                (width, 0.1 * width),
+                CurveShape::Ribbon,
+                material.clone(),
+            ))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::{parse_strands, scatter_grass};
+    use crate::data::color64::Color64;
+    use crate::data::point64::Point64;
+    use crate::hittables::Hittable;
+    use crate::hittables::curve::CurveShape;
+    use crate::hittables::quad::Quad;
+    use crate::materials::Material;
+    use crate::materials::lambertian::Lambertian;
+    use crate::textures::Texture;
+
+    fn material() -> Material {
+        Material::Lambertian(Lambertian {
+            albedo: Texture::solid(Color64::gray(0.5)),
+        })
+    }
+
+    #[test]
+    fn parses_strands_and_skips_comments() {
+        let text = "# two strands\n\n0 0 0  0 1 0  0 2 0  0 3 0  0.1 0.01\n1 0 0 1 1 0 1 2 0 1 3 0 0.1 0.01\n";
+        let curves = parse_strands(text, CurveShape::Tube, material()).unwrap();
+        assert_eq!(curves.len(), 2);
+
+        assert!(parse_strands("0 0 0 1", CurveShape::Tube, material()).is_err());
+        assert!(
+            parse_strands(
+                "0 0 0 0 1 0 0 2 0 0 3 0 0.1 x",
+                CurveShape::Tube,
+                material()
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn grass_grows_on_the_ground() {
+        let ground = Quad::new(
+            Point64::new(-1., 0., -1.),
+            Point64::new(2., 0., 0.),
+            Point64::new(0., 0., -2.),
+            material(),
+        );
+        let blades = scatter_grass(&ground, 50, 0.5, 0.02, material(), &mut rand::rng());
+        assert_eq!(blades.len(), 50);
+        for blade in &blades {
+            let Hittable::Curve(curve) = blade else {
+                panic!("expected a curve");
+            };
+            let bbox = curve.bounding_box(0., 1.).unwrap();
+            assert!(bbox.minimum.y() >= -0.02 && bbox.maximum.y() <= 0.52);
+            assert!(bbox.minimum.x() >= -1.4 && bbox.maximum.x() <= 1.4);
+        }
+    }
+}
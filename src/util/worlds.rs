@@ -2,32 +2,49 @@ use crate::camera::{Camera, CameraRecipe};
 use crate::data::color64::{BLACK, Color64, LIGHT_BLUE};
 use crate::data::point64::Point64;
 use crate::data::vector3::{rand_range, random_in_unit_cube};
-use crate::hittables::bounded_volume_hierarchy::BoundedVolumeHierarchy;
+use crate::hittables::axis_aligned_bounding_box::AxisAlignedBoundingBox;
+use crate::hittables::bounded_volume_hierarchy::{BoundedVolumeHierarchy, BvhBuildOptions};
+use crate::hittables::csg::{Csg, CsgOperation};
 use crate::hittables::cuboid::Cuboid;
+use crate::hittables::curve::CurveShape;
+use crate::hittables::heightfield::Heightfield;
 use crate::hittables::hittable_vec::HittableVec;
+use crate::hittables::metaballs::{Metaball, Metaballs};
 use crate::hittables::moving_sphere::MovingSphere;
+use crate::hittables::plane::Plane;
 use crate::hittables::quad::Quad;
 use crate::hittables::rotation::AxisAlignment::{self, X, Y, Z};
 use crate::hittables::rotation::Rotation;
+use crate::hittables::signed_distance_field::{Sdf, SignedDistanceField};
 use crate::hittables::sphere::Sphere;
+use crate::hittables::top_level_bvh::{Instance, TopLevelBvh};
+use crate::hittables::torus::Torus;
 use crate::hittables::translation::Translation;
 use crate::hittables::{ConstantMedium, Hittable};
+use crate::materials::conductor::Conductor;
 use crate::materials::dielectric::Dielectric;
 use crate::materials::diffuse_light::DiffuseLight;
 use crate::materials::lambertian::Lambertian;
 use crate::materials::metal::Metal;
-use crate::materials::{Isotropic, Material};
+use crate::materials::{Isotropic, Layered, Material, Principled, RoughDielectric};
 use crate::textures::Texture;
 use crate::textures::image::ImageTexture;
 use crate::textures::noise::NoiseType::Marble;
 use crate::textures::noise::{Noise, NoiseType};
 use crate::textures::perlin::PerlinGenerator;
-use crate::util::obj::{load_obj_triangles, obj_mesh_axis_bounds};
-use nalgebra::Vector3;
+use crate::util::mesh::TriangleMesh;
+use crate::util::mesh_cache::load_obj_bvh;
+use crate::util::obj::{
+    Subdivision, load_obj_mesh, load_obj_triangles_with_materials, obj_mesh_axis_bounds,
+};
+use crate::util::strands::{load_strands, scatter_grass};
+use nalgebra::{Isometry3, Translation3, UnitQuaternion, Vector3};
 use rand::Rng;
 use std::ops::Range;
 use std::path::Path;
+use std::sync::Arc;
 
+#[derive(Clone)]
 pub(crate) struct World {
     pub image_width: u32,
     pub image_height: u32,
@@ -41,8 +58,9 @@ pub(crate) struct World {
     pub camera_focus_distance: f64,
     pub camera_exposure_time: Range<f64>,
     pub hittable: Hittable,
-    /// Y coordinate of the ground plane, if any. The interactive camera will not
-    /// allow the viewpoint to drop below this level.
+    /// Y coordinate of the ground, if any. The interactive camera will not allow the
+    /// viewpoint to drop below this level. Worlds standing on a horizontal [`Plane`] can
+    /// leave this `None`; see [`World::ground_y`].
     pub ground_y: Option<f64>,
 }
 
@@ -57,28 +75,44 @@ const DEFAULT_APERTURE: f64 = 0.;
 const DEFAULT_FOCUS_DISTANCE: f64 = 10.;
 const DEFAULT_EXPOSURE_TIME: Range<f64> = 0.0..1.0;
 
+/// Builds a hierarchy over `objects` for the default exposure and prints its statistics.
+fn build_reported_bvh(name: &str, objects: &[Hittable], bvh_options: &BvhBuildOptions) -> Hittable {
+    let (bvh, stats) = BoundedVolumeHierarchy::build(objects, 0., 1., bvh_options);
+    println!("{name}: {stats}");
+    bvh
+}
+
 impl World {
+    /// The explicit `ground_y`, or else the height of a horizontal plane at the top level of
+    /// the scene.
+    pub fn ground_y(&self) -> Option<f64> {
+        self.ground_y.or_else(|| self.hittable.ground_height())
+    }
+
+    #[allow(dead_code)]
     pub const fn total_pixels(&self) -> u32 {
         self.image_height * self.image_width
     }
 
-    pub fn random_world(use_bvh: bool) -> World {
-        let checker_pattern = Texture::Checker {
+    pub fn random_world(use_bvh: bool, bvh_options: &BvhBuildOptions) -> World {
+        let checker_pattern = Texture::UvChecker {
             odd: Box::new(Texture::solid(Color64::new(0.2, 0.3, 0.1))),
             even: Box::new(Texture::solid(Color64::new(0.9, 0.9, 0.9))),
+            scale: 2.,
         };
 
-        let mut hittables: Vec<Hittable> = vec![Hittable::Sphere(Sphere {
-            center: Point64::new(0., -1000., 0.),
-            radius: 1000.,
-            material: Material::Lambertian(Lambertian {
-                albedo: checker_pattern,
-            }),
-        })];
+        let ground = Hittable::Plane(
+            Plane::horizontal(
+                0.,
+                Material::Lambertian(Lambertian {
+                    albedo: checker_pattern,
+                }),
+            )
+            .with_uv_scale(0.5),
+        );
+        let mut hittables: Vec<Hittable> = vec![];
 
-        let glass = Material::Dielectric(Dielectric {
-            index_of_refraction: 1.5,
-        });
+        let glass = Material::Dielectric(Dielectric::new(1.5));
 
         let mut rng = rand::rng();
 
@@ -164,9 +198,16 @@ impl World {
             }),
         }));
 
+        // The ground plane is unbounded, so it stays outside the hierarchy.
         let hittable = if use_bvh {
-            BoundedVolumeHierarchy::create_bvh(&mut hittables, 0.0, 1.0)
+            Hittable::HittableVec(HittableVec {
+                hittables: vec![
+                    ground,
+                    build_reported_bvh("spheres", &hittables, bvh_options),
+                ],
+            })
         } else {
+            hittables.insert(0, ground);
             Hittable::HittableVec(HittableVec { hittables })
         };
 
@@ -195,7 +236,7 @@ impl World {
             camera_focus_distance: recipe.focus_distance,
             camera_exposure_time: recipe.exposure_time,
             hittable,
-            ground_y: Some(0.0),
+            ground_y: None,
         }
     }
 
@@ -262,11 +303,7 @@ impl World {
 
         let hittable = Hittable::HittableVec(HittableVec {
             hittables: vec![
-                Hittable::Sphere(Sphere {
-                    center: Point64::new(0., -1000., 0.),
-                    radius: 1000.,
-                    material: material.clone(),
-                }),
+                Hittable::Plane(Plane::horizontal(0., material.clone())),
                 Hittable::Sphere(Sphere {
                     center: Point64::new(0., 2., 0.),
                     radius: 2.,
@@ -300,7 +337,7 @@ impl World {
             camera_focus_distance: recipe.focus_distance,
             camera_exposure_time: recipe.exposure_time,
             hittable,
-            ground_y: Some(0.0),
+            ground_y: None,
         }
     }
 
@@ -355,11 +392,7 @@ impl World {
 
         let hittable = Hittable::HittableVec(HittableVec {
             hittables: vec![
-                Hittable::Sphere(Sphere {
-                    center: Point64::new(0., -1000., 0.),
-                    radius: 1000.,
-                    material: material.clone(),
-                }),
+                Hittable::Plane(Plane::horizontal(0., material.clone())),
                 Hittable::Sphere(Sphere {
                     center: Point64::new(0., 2., 0.),
                     radius: 2.,
@@ -399,7 +432,7 @@ impl World {
             camera_focus_distance: recipe.focus_distance,
             camera_exposure_time: recipe.exposure_time,
             hittable,
-            ground_y: Some(0.0),
+            ground_y: None,
         }
     }
 
@@ -509,7 +542,7 @@ impl World {
         }
     }
 
-    pub fn final_scene() -> World {
+    pub fn final_scene(bvh_options: &BvhBuildOptions) -> World {
         let mut rng = rand::rng();
 
         let ground = Material::Lambertian(Lambertian {
@@ -581,9 +614,9 @@ impl World {
             camera_exposure_time: recipe.exposure_time,
             ground_y: Some(0.0),
             hittable: {
-                let mut scene: Vec<Hittable> = vec![
+                let scene: Vec<Hittable> = vec![
                     // floor
-                    BoundedVolumeHierarchy::create_bvh(&mut boxes, 0., 1.),
+                    build_reported_bvh("floor", &boxes, bvh_options),
                     // light
                     // Y+ light, at y=554: Q=(123,554,147), u=(300,0,0), v=(0,0,265)
                     Hittable::Quad(Quad::new(
@@ -607,9 +640,7 @@ impl World {
                     Hittable::Sphere(Sphere {
                         center: Point64::new(260., 150., 45.),
                         radius: 50.0,
-                        material: Material::Dielectric(Dielectric {
-                            index_of_refraction: 1.5,
-                        }),
+                        material: Material::Dielectric(Dielectric::new(1.5)),
                     }),
                     // metal sphere
                     Hittable::Sphere(Sphere {
@@ -625,9 +656,7 @@ impl World {
                         let boundary = Hittable::Sphere(Sphere {
                             center: Point64::new(360., 150., 145.),
                             radius: 70.0,
-                            material: Material::Dielectric(Dielectric {
-                                index_of_refraction: 1.5,
-                            }),
+                            material: Material::Dielectric(Dielectric::new(1.5)),
                         });
                         Hittable::HittableVec(HittableVec {
                             hittables: vec![
@@ -647,9 +676,7 @@ impl World {
                         Box::new(Hittable::Sphere(Sphere {
                             center: Point64::new(0., 0., 0.),
                             radius: 5000.0,
-                            material: Material::Dielectric(Dielectric {
-                                index_of_refraction: 1.5,
-                            }),
+                            material: Material::Dielectric(Dielectric::new(1.5)),
                         })),
                         0.0001,
                         Material::Isotropic(Isotropic {
@@ -681,15 +708,15 @@ impl World {
                     // rotated/translated box of spheres
                     // TODO rotation
                     Hittable::Translation(Translation {
-                        hittable: Box::new(BoundedVolumeHierarchy::create_bvh(
-                            &mut box_of_spheres,
-                            0.0,
-                            1.0,
+                        hittable: Box::new(build_reported_bvh(
+                            "box of spheres",
+                            &box_of_spheres,
+                            bvh_options,
                         )),
                         offset: Vector3::new(-100., 270., 395.),
                     }),
                 ];
-                BoundedVolumeHierarchy::create_bvh(&mut scene, 0., 1.)
+                build_reported_bvh("scene", &scene, bvh_options)
             },
         }
     }
@@ -710,7 +737,7 @@ impl World {
         let light_source = DiffuseLight::new(Color64::gray(7.));
 
         let tall_box = Hittable::Translation(Translation {
-            hittable: Box::new(Hittable::Rotation(Rotation::new(
+            hittable: Box::new(Hittable::Rotation(Box::new(Rotation::new(
                 Box::new(Hittable::Cuboid(Cuboid::new(
                     Point64::new(0., 0., 0.),
                     Point64::new(165., 330., 165.),
@@ -720,12 +747,12 @@ impl World {
                 15_f64.to_radians(),
                 0.,
                 1.,
-            ))),
+            )))),
             offset: Vector3::new(265., 0., 295.),
         });
 
         let short_box = Hittable::Translation(Translation {
-            hittable: Box::new(Hittable::Rotation(Rotation::new(
+            hittable: Box::new(Hittable::Rotation(Box::new(Rotation::new(
                 Box::new(Hittable::Cuboid(Cuboid::new(
                     Point64::new(0., 0., 0.),
                     Point64::new(165., 165., 165.),
@@ -735,7 +762,7 @@ impl World {
                 (-18_f64).to_radians(),
                 0.,
                 1.,
-            ))),
+            )))),
             offset: Vector3::new(130., 0., 65.),
         });
 
@@ -839,7 +866,7 @@ impl World {
         let teapot_bounds = obj_mesh_axis_bounds(&teapot_path)
             .unwrap_or_else(|e| panic!("failed to read bounds for {}: {e}", teapot_path.display()));
         // Place mesh so its lowest vertex lies on the ground (y ≈ 0), plus a small lift so the
-        // large ground sphere does not clip the base at off-center x positions.
+        // rotated teapots do not dip below the ground plane.
         const GROUND_LIFT: f64 = 0.04;
         let sit_teapot = -teapot_bounds.y_min * teapot_scale + GROUND_LIFT;
         let teapot_height_world = teapot_bounds.height() * teapot_scale;
@@ -870,21 +897,24 @@ impl World {
         let earth = Material::Lambertian(Lambertian {
             albedo: Texture::Image(ImageTexture::new("resources/earthmap.jpg".into())),
         });
-        let glass = Material::Dielectric(Dielectric {
-            index_of_refraction: 1.5,
-        });
-        let silver = Material::Metal(Metal {
-            albedo: Color64::new(0.92, 0.93, 0.96),
-            fuzz: 0.04,
-        });
+        // Bottle-green glass: thin spouts and handles stay pale, the thick body turns deep green.
+        let glass = Material::Dielectric(
+            Dielectric::new(1.5).with_transmittance(Color64::new(0.35, 0.8, 0.45), 0.5),
+        );
+        let car_paint = Material::Layered(Box::new(Layered::new(
+            Material::Lambertian(Lambertian {
+                albedo: Texture::solid(Color64::new(0.55, 0.03, 0.04)),
+            }),
+            1.5,
+            0.05,
+        )));
 
-        let mut hittables: Vec<Hittable> = vec![Hittable::Sphere(Sphere {
-            center: Point64::new(0., -1000., 0.),
-            radius: 1000.,
-            material: Material::Lambertian(Lambertian {
+        let ground = Hittable::Plane(Plane::horizontal(
+            0.,
+            Material::Lambertian(Lambertian {
                 albedo: Texture::solid(Color64::new(0.45, 0.45, 0.48)),
             }),
-        })];
+        ));
 
         // Five teapots and one Mini Cooper; spacing 3 on each side of x = 0 (car in the middle).
         // Each teapot is built at the origin (grounded), rotated, then translated along x.
@@ -896,40 +926,56 @@ impl World {
             (6.0, glass.clone(), Z, (-42_f64).to_radians()),
         ];
 
-        for (x, mat, axis, angle) in teapot_placements {
-            let mut tris = load_obj_triangles(
+        // Each mesh gets one bottom-level BVH, built (or loaded from the cache) once and placed
+        // by instance transforms, so animations only refit the top level. The teapots share
+        // theirs, each instance supplying its own material.
+        let teapot = Arc::new(
+            load_obj_bvh(
                 &teapot_path,
-                mat,
+                checker.clone(),
                 teapot_scale,
                 Vector3::new(0., sit_teapot, 0.0),
+                Subdivision {
+                    levels: 1,
+                    crease_angle: Some(60.),
+                },
+                &BvhBuildOptions::default(),
             )
-            .unwrap_or_else(|e| panic!("failed to load {}: {e}", teapot_path.display()));
-            let bvh = BoundedVolumeHierarchy::create_bvh(&mut tris, 0., 1.);
-            let rotated = Hittable::Rotation(Rotation::new(Box::new(bvh), axis, angle, 0., 1.));
-            hittables.push(Hittable::Translation(Translation {
-                hittable: Box::new(rotated),
-                offset: Vector3::new(x, 0., 0.),
-            }));
-        }
+            .unwrap_or_else(|e| panic!("failed to load {}: {e}", teapot_path.display())),
+        );
+        let mut instances: Vec<Instance> = teapot_placements
+            .into_iter()
+            .map(|(x, mat, axis, angle)| {
+                Instance::new(
+                    teapot.clone(),
+                    Isometry3::from_parts(
+                        Translation3::new(x, 0., 0.),
+                        UnitQuaternion::from_axis_angle(&axis.unit_vector(), angle),
+                    ),
+                )
+                .with_material(mat)
+            })
+            .collect();
 
         // Car: wheels on ground; rotate +90° about Z so the long axis faces the viewer's right (+X).
-        let mut car_tris = load_obj_triangles(
+        // Faces keep their MTL materials; the paint only covers faces without one.
+        let car_bvh = load_obj_bvh(
             &mini_path,
-            silver,
+            car_paint,
             car_scale,
             Vector3::new(0.0, sit_car, car_z),
+            Subdivision::default(),
+            &BvhBuildOptions::default(),
         )
         .unwrap_or_else(|e| panic!("failed to load {}: {e}", mini_path.display()));
-        let car_bvh = BoundedVolumeHierarchy::create_bvh(&mut car_tris, 0., 1.);
-        hittables.push(Hittable::Rotation(Rotation::new(
-            Box::new(car_bvh),
-            Z,
-            std::f64::consts::FRAC_PI_2,
-            0.,
-            1.,
-        )));
+        instances.push(Instance::new(
+            Arc::new(car_bvh),
+            Isometry3::rotation(Vector3::z() * std::f64::consts::FRAC_PI_2),
+        ));
 
-        let hittable = BoundedVolumeHierarchy::create_bvh(&mut hittables, 0., 1.);
+        let hittable = Hittable::HittableVec(HittableVec {
+            hittables: vec![ground, Hittable::TopLevelBvh(TopLevelBvh::new(instances))],
+        });
 
         let aspect = DEFAULT_IMAGE_WIDTH as f64 / DEFAULT_IMAGE_HEIGHT as f64;
         let recipe = CameraRecipe::new(
@@ -956,6 +1002,998 @@ impl World {
             camera_focus_distance: recipe.focus_distance,
             camera_exposure_time: recipe.exposure_time,
             hittable,
+            ground_y: None,
+        }
+    }
+
+    /// Tori on a checkered ground: solid, glass, and a smoke-filled ring standing on its edge.
+    pub fn tori() -> World {
+        let checker_pattern = Texture::UvChecker {
+            odd: Box::new(Texture::solid(Color64::new(0.2, 0.3, 0.1))),
+            even: Box::new(Texture::solid(Color64::new(0.9, 0.9, 0.9))),
+            scale: 2.,
+        };
+
+        let smoke_ring = Hittable::Translation(Translation {
+            hittable: Box::new(Hittable::Rotation(Box::new(Rotation::new(
+                Box::new(Hittable::Torus(Torus {
+                    center: Point64::new(0., 0., 0.),
+                    major_radius: 1.,
+                    minor_radius: 0.4,
+                    material: Material::Dielectric(Dielectric::new(1.5)),
+                })),
+                X,
+                std::f64::consts::FRAC_PI_2,
+                0.,
+                1.,
+            )))),
+            offset: Vector3::new(0., 1.4, -3.),
+        });
+
+        let hittables = vec![
+            Hittable::Plane(Plane::horizontal(
+                0.,
+                Material::Lambertian(Lambertian {
+                    albedo: checker_pattern,
+                }),
+            )),
+            Hittable::Torus(Torus {
+                center: Point64::new(-3., 0.4, 0.),
+                major_radius: 1.,
+                minor_radius: 0.4,
+                material: Material::Lambertian(Lambertian {
+                    albedo: Texture::solid(Color64::new(0.7, 0.2, 0.15)),
+                }),
+            }),
+            Hittable::Torus(Torus {
+                center: Point64::new(0., 0.4, 0.),
+                major_radius: 1.,
+                minor_radius: 0.4,
+                material: Material::Dielectric(Dielectric::new(1.5)),
+            }),
+            Hittable::Torus(Torus {
+                center: Point64::new(3., 0.4, 0.),
+                major_radius: 1.,
+                minor_radius: 0.4,
+                material: Material::Metal(Metal {
+                    albedo: Color64::new(0.95, 0.64, 0.54),
+                    fuzz: 0.1,
+                }),
+            }),
+            Hittable::ConstantMedium(ConstantMedium::new(
+                Box::new(smoke_ring),
+                2.,
+                Material::Isotropic(Isotropic {
+                    albedo: Texture::solid(Color64::new(0.2, 0.4, 0.9)),
+                }),
+            )),
+        ];
+
+        let aspect = DEFAULT_IMAGE_WIDTH as f64 / DEFAULT_IMAGE_HEIGHT as f64;
+        let recipe = CameraRecipe::new(
+            Point64::new(0., 5., 12.),
+            Point64::new(0., 0.8, -0.5),
+            DEFAULT_VUP,
+            35.,
+            aspect,
+            DEFAULT_APERTURE,
+            DEFAULT_FOCUS_DISTANCE,
+            DEFAULT_EXPOSURE_TIME,
+        );
+
+        World {
+            image_width: DEFAULT_IMAGE_WIDTH,
+            image_height: DEFAULT_IMAGE_HEIGHT,
+            samples_per_pixel: DEFAULT_SAMPLES_PER_PIXEL,
+            background_color: LIGHT_BLUE,
+            camera: recipe.camera,
+            camera_target: recipe.look_at,
+            camera_v_up: recipe.v_up,
+            camera_vfov_deg: recipe.vfov_deg,
+            camera_aperture: recipe.aperture,
+            camera_focus_distance: recipe.focus_distance,
+            camera_exposure_time: recipe.exposure_time,
+            hittable: Hittable::HittableVec(HittableVec { hittables }),
+            ground_y: None,
+        }
+    }
+
+    /// Constructive solid geometry: a cube with a spherical bite, a glass sphere–cube
+    /// intersection, and a metal ball drilled through along all three axes.
+    pub fn csg() -> World {
+        let checker_pattern = Texture::UvChecker {
+            odd: Box::new(Texture::solid(Color64::new(0.2, 0.3, 0.1))),
+            even: Box::new(Texture::solid(Color64::new(0.9, 0.9, 0.9))),
+            scale: 2.,
+        };
+        let red = Material::Lambertian(Lambertian {
+            albedo: Texture::solid(Color64::new(0.7, 0.15, 0.1)),
+        });
+        let yellow = Material::Lambertian(Lambertian {
+            albedo: Texture::solid(Color64::new(0.9, 0.75, 0.2)),
+        });
+        let glass = Material::Dielectric(Dielectric::new(1.5));
+        let steel = Material::Metal(Metal {
+            albedo: Color64::new(0.8, 0.8, 0.85),
+            fuzz: 0.05,
+        });
+
+        let bitten_cube = Csg::new(
+            CsgOperation::Difference,
+            Hittable::Cuboid(Cuboid::new(
+                Point64::new(-4., 0., -1.),
+                Point64::new(-2., 2., 1.),
+                red,
+            )),
+            Hittable::Sphere(Sphere {
+                center: Point64::new(-2., 2., 1.),
+                radius: 1.2,
+                material: yellow,
+            }),
+            0.,
+            1.,
+        );
+
+        let rounded_cube = Csg::new(
+            CsgOperation::Intersection,
+            Hittable::Cuboid(Cuboid::new(
+                Point64::new(-1., 0., -1.),
+                Point64::new(1., 2., 1.),
+                glass.clone(),
+            )),
+            Hittable::Sphere(Sphere {
+                center: Point64::new(0., 1., 0.),
+                radius: 1.35,
+                material: glass,
+            }),
+            0.,
+            1.,
+        );
+
+        let bar = |half_extent: Vector3<f64>| {
+            let center = Vector3::new(3., 1., 0.);
+            Hittable::Cuboid(Cuboid::new(
+                Point64(center - half_extent),
+                Point64(center + half_extent),
+                steel.clone(),
+            ))
+        };
+        let cross = Csg::new(
+            CsgOperation::Union,
+            Hittable::Csg(Csg::new(
+                CsgOperation::Union,
+                bar(Vector3::new(1.5, 0.35, 0.35)),
+                bar(Vector3::new(0.35, 1.5, 0.35)),
+                0.,
+                1.,
+            )),
+            bar(Vector3::new(0.35, 0.35, 1.5)),
+            0.,
+            1.,
+        );
+        let drilled_ball = Csg::new(
+            CsgOperation::Difference,
+            Hittable::Sphere(Sphere {
+                center: Point64::new(3., 1., 0.),
+                radius: 1.,
+                material: steel.clone(),
+            }),
+            Hittable::Csg(cross),
+            0.,
+            1.,
+        );
+
+        let hittables = vec![
+            Hittable::Plane(Plane::horizontal(
+                0.,
+                Material::Lambertian(Lambertian {
+                    albedo: checker_pattern,
+                }),
+            )),
+            Hittable::Csg(bitten_cube),
+            Hittable::Csg(rounded_cube),
+            Hittable::Csg(drilled_ball),
+        ];
+
+        let aspect = DEFAULT_IMAGE_WIDTH as f64 / DEFAULT_IMAGE_HEIGHT as f64;
+        let recipe = CameraRecipe::new(
+            Point64::new(2., 5., 10.),
+            Point64::new(0., 0.8, 0.),
+            DEFAULT_VUP,
+            40.,
+            aspect,
+            DEFAULT_APERTURE,
+            DEFAULT_FOCUS_DISTANCE,
+            DEFAULT_EXPOSURE_TIME,
+        );
+
+        World {
+            image_width: DEFAULT_IMAGE_WIDTH,
+            image_height: DEFAULT_IMAGE_HEIGHT,
+            samples_per_pixel: DEFAULT_SAMPLES_PER_PIXEL,
+            background_color: LIGHT_BLUE,
+            camera: recipe.camera,
+            camera_target: recipe.look_at,
+            camera_v_up: recipe.v_up,
+            camera_vfov_deg: recipe.vfov_deg,
+            camera_aperture: recipe.aperture,
+            camera_focus_distance: recipe.focus_distance,
+            camera_exposure_time: recipe.exposure_time,
+            hittable: Hittable::HittableVec(HittableVec { hittables }),
+            ground_y: None,
+        }
+    }
+
+    /// Sphere-traced distance fields: a Mandelbulb, smoothly blended spheres, a twisted rounded
+    /// box and a noise-displaced sphere.
+    pub fn distance_fields() -> World {
+        let checker_pattern = Texture::UvChecker {
+            odd: Box::new(Texture::solid(Color64::new(0.2, 0.3, 0.1))),
+            even: Box::new(Texture::solid(Color64::new(0.9, 0.9, 0.9))),
+            scale: 2.,
+        };
+        let bounds = |center: Vector3<f64>, half: f64| AxisAlignedBoundingBox {
+            minimum: Point64(center - Vector3::new(half, half, half)),
+            maximum: Point64(center + Vector3::new(half, half, half)),
+        };
+
+        let bulb_center = Vector3::new(-3.3, 1.2, 0.);
+        let mandelbulb = SignedDistanceField::new(
+            Sdf::Translate {
+                offset: bulb_center,
+                sdf: Box::new(Sdf::Scale {
+                    factor: 1.1,
+                    sdf: Box::new(Sdf::Mandelbulb {
+                        power: 8.,
+                        iterations: 12,
+                    }),
+                }),
+            },
+            bounds(bulb_center, 1.25),
+            Material::Metal(Metal {
+                albedo: Color64::new(0.9, 0.7, 0.4),
+                fuzz: 0.1,
+            }),
+        )
+        .with_max_steps(512);
+
+        let blob_center = Vector3::new(-1., 0.9, 0.);
+        let sphere_at = |offset: Vector3<f64>, radius: f64| Sdf::Translate {
+            offset: blob_center + offset,
+            sdf: Box::new(Sdf::Sphere { radius }),
+        };
+        let blob = SignedDistanceField::new(
+            Sdf::SmoothUnion {
+                a: Box::new(Sdf::SmoothUnion {
+                    a: Box::new(sphere_at(Vector3::new(-0.3, -0.2, 0.), 0.6)),
+                    b: Box::new(sphere_at(Vector3::new(0.4, 0.1, 0.2), 0.5)),
+                    k: 0.4,
+                }),
+                b: Box::new(sphere_at(Vector3::new(0., 0.6, -0.1), 0.4)),
+                k: 0.4,
+            },
+            bounds(blob_center, 1.3),
+            Material::Lambertian(Lambertian {
+                albedo: Texture::solid(Color64::new(0.2, 0.5, 0.8)),
+            }),
+        );
+
+        let twist_center = Vector3::new(1.2, 1.2, 0.);
+        let twisted = SignedDistanceField::new(
+            Sdf::Translate {
+                offset: twist_center,
+                sdf: Box::new(Sdf::Twist {
+                    rate: 1.2,
+                    sdf: Box::new(Sdf::Round {
+                        radius: 0.08,
+                        sdf: Box::new(Sdf::Box {
+                            half_extents: Vector3::new(0.45, 1.1, 0.45),
+                        }),
+                    }),
+                }),
+            },
+            bounds(twist_center, 1.25),
+            Material::Lambertian(Lambertian {
+                albedo: Texture::solid(Color64::new(0.8, 0.3, 0.2)),
+            }),
+        )
+        .with_step_scale(0.6);
+
+        let rock_center = Vector3::new(3.4, 1., 0.);
+        let rock = SignedDistanceField::new(
+            Sdf::Translate {
+                offset: rock_center,
+                sdf: Box::new(Sdf::Displace {
+                    noise: Box::new(PerlinGenerator::new()),
+                    scale: 3.,
+                    amplitude: 0.15,
+                    sdf: Box::new(Sdf::Sphere { radius: 0.85 }),
+                }),
+            },
+            bounds(rock_center, 1.1),
+            Material::Lambertian(Lambertian {
+                albedo: Texture::solid(Color64::gray(0.6)),
+            }),
+        )
+        .with_step_scale(0.5);
+
+        let objects = vec![
+            Hittable::SignedDistanceField(mandelbulb),
+            Hittable::SignedDistanceField(blob),
+            Hittable::SignedDistanceField(twisted),
+            Hittable::SignedDistanceField(rock),
+        ];
+
+        let hittables = vec![
+            Hittable::Plane(Plane::horizontal(
+                0.,
+                Material::Lambertian(Lambertian {
+                    albedo: checker_pattern,
+                }),
+            )),
+            BoundedVolumeHierarchy::create_bvh(&objects, 0., 1.),
+        ];
+
+        let aspect = DEFAULT_IMAGE_WIDTH as f64 / DEFAULT_IMAGE_HEIGHT as f64;
+        let recipe = CameraRecipe::new(
+            Point64::new(0., 3.5, 10.),
+            Point64::new(0., 1., 0.),
+            DEFAULT_VUP,
+            40.,
+            aspect,
+            DEFAULT_APERTURE,
+            DEFAULT_FOCUS_DISTANCE,
+            DEFAULT_EXPOSURE_TIME,
+        );
+
+        World {
+            image_width: DEFAULT_IMAGE_WIDTH,
+            image_height: DEFAULT_IMAGE_HEIGHT,
+            samples_per_pixel: DEFAULT_SAMPLES_PER_PIXEL,
+            background_color: LIGHT_BLUE,
+            camera: recipe.camera,
+            camera_target: recipe.look_at,
+            camera_v_up: recipe.v_up,
+            camera_vfov_deg: recipe.vfov_deg,
+            camera_aperture: recipe.aperture,
+            camera_focus_distance: recipe.focus_distance,
+            camera_exposure_time: recipe.exposure_time,
+            hittable: Hittable::HittableVec(HittableVec { hittables }),
+            ground_y: None,
+        }
+    }
+
+    /// The earth map used as its own height map (brighter is higher) with the image draped
+    /// over the terrain.
+    pub fn terrain() -> World {
+        let map_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("resources/earthmap.jpg");
+        let terrain = Heightfield::from_image(
+            &map_path,
+            Point64::new(-10., 0., -5.),
+            Vector3::new(20., 0.8, 10.),
+            Material::Lambertian(Lambertian {
+                albedo: Texture::Image(ImageTexture::new(map_path.display().to_string())),
+            }),
+        )
+        .unwrap_or_else(|e| panic!("failed to load height map {}: {e}", map_path.display()));
+
+        let aspect = DEFAULT_IMAGE_WIDTH as f64 / DEFAULT_IMAGE_HEIGHT as f64;
+        let recipe = CameraRecipe::new(
+            Point64::new(0., 9., 13.),
+            Point64::new(0., 0., 0.5),
+            DEFAULT_VUP,
+            45.,
+            aspect,
+            DEFAULT_APERTURE,
+            DEFAULT_FOCUS_DISTANCE,
+            DEFAULT_EXPOSURE_TIME,
+        );
+
+        World {
+            image_width: DEFAULT_IMAGE_WIDTH,
+            image_height: DEFAULT_IMAGE_HEIGHT,
+            samples_per_pixel: DEFAULT_SAMPLES_PER_PIXEL,
+            background_color: LIGHT_BLUE,
+            camera: recipe.camera,
+            camera_target: recipe.look_at,
+            camera_v_up: recipe.v_up,
+            camera_vfov_deg: recipe.vfov_deg,
+            camera_aperture: recipe.aperture,
+            camera_focus_distance: recipe.focus_distance,
+            camera_exposure_time: recipe.exposure_time,
+            hittable: Hittable::Heightfield(Box::new(terrain)),
+            ground_y: Some(0.0),
+        }
+    }
+
+    /// A furry ball loaded from a strand file resting on a lawn of scattered grass blades.
+    pub fn grass() -> World {
+        let mut rng = rand::rng();
+
+        let ground = Quad::new(
+            Point64::new(-5., 0., 5.),
+            Point64::new(10., 0., 0.),
+            Point64::new(0., 0., -10.),
+            Material::Lambertian(Lambertian {
+                albedo: Texture::solid(Color64::new(0.25, 0.2, 0.1)),
+            }),
+        );
+        let blades = scatter_grass(
+            &ground,
+            20_000,
+            0.5,
+            0.015,
+            Material::Lambertian(Lambertian {
+                albedo: Texture::solid(Color64::new(0.25, 0.55, 0.15)),
+            }),
+            &mut rng,
+        );
+
+        let strands_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("resources/fuzzball.strands");
+        let mut fur = load_strands(
+            &strands_path,
+            CurveShape::Tube,
+            Material::Lambertian(Lambertian {
+                albedo: Texture::solid(Color64::new(0.8, 0.45, 0.2)),
+            }),
+        )
+        .unwrap_or_else(|e| panic!("{e:#}"));
+        fur.push(Hittable::Sphere(Sphere {
+            center: Point64::new(0., 0., 0.),
+            radius: 1.,
+            material: Material::Lambertian(Lambertian {
+                albedo: Texture::solid(Color64::new(0.6, 0.3, 0.1)),
+            }),
+        }));
+
+        let hittables = vec![
+            Hittable::Quad(ground),
+            BoundedVolumeHierarchy::create_bvh(&blades, 0., 1.),
+            Hittable::Translation(Translation {
+                hittable: Box::new(BoundedVolumeHierarchy::create_bvh(&fur, 0., 1.)),
+                offset: Vector3::new(0., 1.3, 0.),
+            }),
+        ];
+
+        let aspect = DEFAULT_IMAGE_WIDTH as f64 / DEFAULT_IMAGE_HEIGHT as f64;
+        let recipe = CameraRecipe::new(
+            Point64::new(0., 2., 6.),
+            Point64::new(0., 0.9, 0.),
+            DEFAULT_VUP,
+            40.,
+            aspect,
+            DEFAULT_APERTURE,
+            DEFAULT_FOCUS_DISTANCE,
+            DEFAULT_EXPOSURE_TIME,
+        );
+
+        World {
+            image_width: DEFAULT_IMAGE_WIDTH,
+            image_height: DEFAULT_IMAGE_HEIGHT,
+            samples_per_pixel: DEFAULT_SAMPLES_PER_PIXEL,
+            background_color: LIGHT_BLUE,
+            camera: recipe.camera,
+            camera_target: recipe.look_at,
+            camera_v_up: recipe.v_up,
+            camera_vfov_deg: recipe.vfov_deg,
+            camera_aperture: recipe.aperture,
+            camera_focus_distance: recipe.focus_distance,
+            camera_exposure_time: recipe.exposure_time,
+            hittable: Hittable::HittableVec(HittableVec { hittables }),
+            ground_y: Some(0.0),
+        }
+    }
+
+    /// Metaball blobs: a merged cluster in red, a glass chain and a metal pair whose balls
+    /// drift apart during the exposure.
+    pub fn metaballs() -> World {
+        let mut rng = rand::rng();
+
+        let cluster = Metaballs {
+            balls: (0..10)
+                .map(|_| {
+                    Metaball::fixed(
+                        Point64::new(
+                            rng.random_range(-4.0..-2.0),
+                            rng.random_range(0.9..1.8),
+                            rng.random_range(-1.0..1.0),
+                        ),
+                        rng.random_range(0.8..1.2),
+                        1.,
+                    )
+                })
+                .collect(),
+            threshold: 0.5,
+            time0: 0.,
+            time1: 1.,
+            material: Material::Lambertian(Lambertian {
+                albedo: Texture::solid(Color64::new(0.8, 0.2, 0.15)),
+            }),
+        };
+
+        let chain = Metaballs {
+            balls: (0..5)
+                .map(|i| {
+                    let angle = i as f64 * 0.8;
+                    Metaball::fixed(
+                        Point64::new(0.6 * angle.sin(), 0.6 + 0.55 * i as f64, 0.6 * angle.cos()),
+                        1.,
+                        1.,
+                    )
+                })
+                .collect(),
+            threshold: 0.3,
+            time0: 0.,
+            time1: 1.,
+            material: Material::Dielectric(Dielectric::new(1.5)),
+        };
+
+        let splitting = Metaballs {
+            balls: vec![
+                Metaball {
+                    center0: Point64::new(3., 0.75, 0.),
+                    center1: Point64::new(2.4, 0.75, 0.),
+                    radius: 1.4,
+                    weight: 1.,
+                },
+                Metaball {
+                    center0: Point64::new(3., 0.75, 0.),
+                    center1: Point64::new(3.6, 1.05, 0.),
+                    radius: 1.4,
+                    weight: 1.,
+                },
+            ],
+            threshold: 0.4,
+            time0: 0.,
+            time1: 1.,
+            material: Material::Metal(Metal {
+                albedo: Color64::new(0.8, 0.8, 0.85),
+                fuzz: 0.05,
+            }),
+        };
+
+        let blobs = vec![
+            Hittable::Metaballs(cluster),
+            Hittable::Metaballs(chain),
+            Hittable::Metaballs(splitting),
+        ];
+
+        let hittables = vec![
+            Hittable::Quad(Quad::new(
+                Point64::new(-10., 0., 10.),
+                Point64::new(20., 0., 0.),
+                Point64::new(0., 0., -20.),
+                Material::Lambertian(Lambertian {
+                    albedo: Texture::Checker {
+                        odd: Box::new(Texture::solid(Color64::new(0.2, 0.3, 0.1))),
+                        even: Box::new(Texture::solid(Color64::new(0.9, 0.9, 0.9))),
+                    },
+                }),
+            )),
+            BoundedVolumeHierarchy::create_bvh(&blobs, 0., 1.),
+        ];
+
+        let aspect = DEFAULT_IMAGE_WIDTH as f64 / DEFAULT_IMAGE_HEIGHT as f64;
+        let recipe = CameraRecipe::new(
+            Point64::new(0., 4., 11.),
+            Point64::new(0., 1.2, 0.),
+            DEFAULT_VUP,
+            40.,
+            aspect,
+            DEFAULT_APERTURE,
+            DEFAULT_FOCUS_DISTANCE,
+            DEFAULT_EXPOSURE_TIME,
+        );
+
+        World {
+            image_width: DEFAULT_IMAGE_WIDTH,
+            image_height: DEFAULT_IMAGE_HEIGHT,
+            samples_per_pixel: DEFAULT_SAMPLES_PER_PIXEL,
+            background_color: LIGHT_BLUE,
+            camera: recipe.camera,
+            camera_target: recipe.look_at,
+            camera_v_up: recipe.v_up,
+            camera_vfov_deg: recipe.vfov_deg,
+            camera_aperture: recipe.aperture,
+            camera_focus_distance: recipe.focus_distance,
+            camera_exposure_time: recipe.exposure_time,
+            hittable: Hittable::HittableVec(HittableVec { hittables }),
+            ground_y: Some(0.0),
+        }
+    }
+
+    /// Displacement mapping: rocky ground pushed up from a quad by turbulence, and a teapot
+    /// embossed with marble veins.
+    pub fn displacement() -> World {
+        let ground_quad = Quad::new(
+            Point64::new(-6., 0., 6.),
+            Point64::new(12., 0., 0.),
+            Point64::new(0., 0., -12.),
+            Material::Lambertian(Lambertian {
+                albedo: Texture::solid(Color64::new(0.55, 0.5, 0.45)),
+            }),
+        );
+        let rocks = Texture::Noise(Box::new(Noise {
+            noise_gen: PerlinGenerator::new(),
+            scale: 1.5,
+            noise_type: NoiseType::Turbulence,
+        }));
+        let ground = TriangleMesh::from_quad(&ground_quad)
+            .refined(0.08)
+            .displaced(&rocks, 0.6)
+            .triangles(ground_quad.material.clone());
+
+        let teapot_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("resources/teapot.obj");
+        let teapot_scale = 0.5;
+        let teapot_bounds = obj_mesh_axis_bounds(&teapot_path)
+            .unwrap_or_else(|e| panic!("failed to read bounds for {}: {e}", teapot_path.display()));
+        let veins = Texture::Noise(Box::new(Noise {
+            noise_gen: PerlinGenerator::new(),
+            scale: 6.,
+            noise_type: Marble,
+        }));
+        let teapot = load_obj_mesh(
+            &teapot_path,
+            teapot_scale,
+            Vector3::new(0., -teapot_bounds.y_min * teapot_scale + 0.3, 0.),
+        )
+        .unwrap_or_else(|e| panic!("failed to load {}: {e}", teapot_path.display()))
+        .refined(0.05)
+        .displaced(&veins, 0.04)
+        .triangles(Material::Metal(Metal {
+            albedo: Color64::new(0.95, 0.64, 0.54),
+            fuzz: 0.2,
+        }));
+
+        let hittables = vec![
+            BoundedVolumeHierarchy::create_bvh(&ground, 0., 1.),
+            BoundedVolumeHierarchy::create_bvh(&teapot, 0., 1.),
+        ];
+
+        let aspect = DEFAULT_IMAGE_WIDTH as f64 / DEFAULT_IMAGE_HEIGHT as f64;
+        let recipe = CameraRecipe::new(
+            Point64::new(0., 3.5, 8.),
+            Point64::new(0., 0.8, 0.),
+            DEFAULT_VUP,
+            40.,
+            aspect,
+            DEFAULT_APERTURE,
+            DEFAULT_FOCUS_DISTANCE,
+            DEFAULT_EXPOSURE_TIME,
+        );
+
+        World {
+            image_width: DEFAULT_IMAGE_WIDTH,
+            image_height: DEFAULT_IMAGE_HEIGHT,
+            samples_per_pixel: DEFAULT_SAMPLES_PER_PIXEL,
+            background_color: LIGHT_BLUE,
+            camera: recipe.camera,
+            camera_target: recipe.look_at,
+            camera_v_up: recipe.v_up,
+            camera_vfov_deg: recipe.vfov_deg,
+            camera_aperture: recipe.aperture,
+            camera_focus_distance: recipe.focus_distance,
+            camera_exposure_time: recipe.exposure_time,
+            hittable: Hittable::HittableVec(HittableVec { hittables }),
+            ground_y: Some(0.0),
+        }
+    }
+
+    /// Gold, copper, aluminum and silver GGX conductors, rougher towards the back, behind a
+    /// large sphere of brushed aluminum with anisotropic roughness.
+    pub fn conductors() -> World {
+        let metals: [fn(f64) -> Conductor; 4] = [
+            Conductor::gold,
+            Conductor::copper,
+            Conductor::aluminum,
+            Conductor::silver,
+        ];
+
+        let mut hittables = vec![Hittable::Quad(Quad::new(
+            Point64::new(-10., 0., 10.),
+            Point64::new(20., 0., 0.),
+            Point64::new(0., 0., -20.),
+            Material::Lambertian(Lambertian {
+                albedo: Texture::solid(Color64::gray(0.4)),
+            }),
+        ))];
+        for (column, metal) in metals.iter().enumerate() {
+            for (row, roughness) in [0.05, 0.3, 0.6].into_iter().enumerate() {
+                hittables.push(Hittable::Sphere(Sphere {
+                    center: Point64::new(-3.3 + 2.2 * column as f64, 0.5, 2.4 - 1.2 * row as f64),
+                    radius: 0.5,
+                    material: Material::Conductor(metal(roughness)),
+                }));
+            }
+        }
+        let aluminum = Conductor::aluminum(0.);
+        hittables.push(Hittable::Sphere(Sphere {
+            center: Point64::new(0., 1.5, -2.),
+            radius: 1.5,
+            material: Material::Conductor(Conductor::anisotropic(
+                aluminum.eta,
+                aluminum.k,
+                0.6,
+                0.1,
+            )),
+        }));
+
+        let aspect = DEFAULT_IMAGE_WIDTH as f64 / DEFAULT_IMAGE_HEIGHT as f64;
+        let recipe = CameraRecipe::new(
+            Point64::new(0., 3., 9.),
+            Point64::new(0., 0.8, 0.),
+            DEFAULT_VUP,
+            40.,
+            aspect,
+            DEFAULT_APERTURE,
+            DEFAULT_FOCUS_DISTANCE,
+            DEFAULT_EXPOSURE_TIME,
+        );
+
+        World {
+            image_width: DEFAULT_IMAGE_WIDTH,
+            image_height: DEFAULT_IMAGE_HEIGHT,
+            samples_per_pixel: DEFAULT_SAMPLES_PER_PIXEL,
+            background_color: LIGHT_BLUE,
+            camera: recipe.camera,
+            camera_target: recipe.look_at,
+            camera_v_up: recipe.v_up,
+            camera_vfov_deg: recipe.vfov_deg,
+            camera_aperture: recipe.aperture,
+            camera_focus_distance: recipe.focus_distance,
+            camera_exposure_time: recipe.exposure_time,
+            hittable: Hittable::HittableVec(HittableVec { hittables }),
+            ground_y: Some(0.0),
+        }
+    }
+
+    /// Glass teapots from smooth to frosted, beside a frosted pane with a sphere behind it.
+    pub fn frosted_glass() -> World {
+        let mut hittables = vec![Hittable::Quad(Quad::new(
+            Point64::new(-10., 0., 10.),
+            Point64::new(20., 0., 0.),
+            Point64::new(0., 0., -20.),
+            Material::Lambertian(Lambertian {
+                albedo: Texture::solid(Color64::gray(0.5)),
+            }),
+        ))];
+
+        let teapot_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("resources/teapot.obj");
+        let teapot_scale = 0.35;
+        let teapot_bounds = obj_mesh_axis_bounds(&teapot_path)
+            .unwrap_or_else(|e| panic!("failed to read bounds for {}: {e}", teapot_path.display()));
+        for (i, roughness) in [0., 0.15, 0.4].into_iter().enumerate() {
+            let teapot = load_obj_mesh(
+                &teapot_path,
+                teapot_scale,
+                Vector3::new(
+                    -2.6 + 2.6 * i as f64,
+                    -teapot_bounds.y_min * teapot_scale,
+                    1.,
+                ),
+            )
+            .unwrap_or_else(|e| panic!("failed to load {}: {e}", teapot_path.display()))
+            .triangles(Material::RoughDielectric(RoughDielectric::new(
+                1.5, roughness,
+            )));
+            hittables.push(BoundedVolumeHierarchy::create_bvh(&teapot, 0., 1.));
+        }
+
+        hittables.push(Hittable::Cuboid(Cuboid::new(
+            Point64::new(-3.5, 0., -1.6),
+            Point64::new(3.5, 3., -1.5),
+            Material::RoughDielectric(RoughDielectric::new(1.5, 0.3)),
+        )));
+        hittables.push(Hittable::Sphere(Sphere {
+            center: Point64::new(0., 1., -3.),
+            radius: 1.,
+            material: Material::Lambertian(Lambertian {
+                albedo: Texture::solid(Color64::new(0.8, 0.2, 0.1)),
+            }),
+        }));
+
+        let aspect = DEFAULT_IMAGE_WIDTH as f64 / DEFAULT_IMAGE_HEIGHT as f64;
+        let recipe = CameraRecipe::new(
+            Point64::new(0., 3., 9.),
+            Point64::new(0., 0.8, 0.),
+            DEFAULT_VUP,
+            40.,
+            aspect,
+            DEFAULT_APERTURE,
+            DEFAULT_FOCUS_DISTANCE,
+            DEFAULT_EXPOSURE_TIME,
+        );
+
+        World {
+            image_width: DEFAULT_IMAGE_WIDTH,
+            image_height: DEFAULT_IMAGE_HEIGHT,
+            samples_per_pixel: DEFAULT_SAMPLES_PER_PIXEL,
+            background_color: LIGHT_BLUE,
+            camera: recipe.camera,
+            camera_target: recipe.look_at,
+            camera_v_up: recipe.v_up,
+            camera_vfov_deg: recipe.vfov_deg,
+            camera_aperture: recipe.aperture,
+            camera_focus_distance: recipe.focus_distance,
+            camera_exposure_time: recipe.exposure_time,
+            hittable: Hittable::HittableVec(HittableVec { hittables }),
+            ground_y: Some(0.0),
+        }
+    }
+
+    /// One sphere per lobe of the principled material: plastic, brushed metal, velvet, car
+    /// paint, tinted glass and an emitter. The teapot has no MTL file, so it is loaded with
+    /// the car paint as the fallback material.
+    pub fn principled() -> World {
+        let paint = Principled {
+            base_color: Texture::solid(Color64::new(0.6, 0.05, 0.05)),
+            roughness: Texture::solid(Color64::gray(0.4)),
+            clearcoat: Texture::solid(Color64::gray(1.)),
+            ..Default::default()
+        };
+        let lobes = [
+            Principled {
+                base_color: Texture::solid(Color64::new(0.1, 0.3, 0.7)),
+                roughness: Texture::solid(Color64::gray(0.2)),
+                ..Default::default()
+            },
+            Principled {
+                base_color: Texture::solid(Color64::new(0.95, 0.75, 0.4)),
+                metallic: Texture::solid(Color64::gray(1.)),
+                roughness: Texture::solid(Color64::gray(0.35)),
+                ..Default::default()
+            },
+            Principled {
+                base_color: Texture::solid(Color64::new(0.25, 0.05, 0.3)),
+                roughness: Texture::solid(Color64::gray(1.)),
+                specular: Texture::solid(Color64::gray(0.)),
+                sheen: Texture::solid(Color64::new(0.6, 0.4, 0.6)),
+                ..Default::default()
+            },
+            paint.clone(),
+            Principled {
+                base_color: Texture::solid(Color64::new(0.7, 1., 0.8)),
+                roughness: Texture::solid(Color64::gray(0.05)),
+                transmission: Texture::solid(Color64::gray(1.)),
+                ..Default::default()
+            },
+            Principled {
+                base_color: Texture::solid(Color64::gray(0.2)),
+                emission: Texture::solid(Color64::new(4., 3., 1.5)),
+                ..Default::default()
+            },
+        ];
+
+        let mut hittables = vec![Hittable::Quad(Quad::new(
+            Point64::new(-10., 0., 10.),
+            Point64::new(20., 0., 0.),
+            Point64::new(0., 0., -20.),
+            Material::Lambertian(Lambertian {
+                albedo: Texture::solid(Color64::gray(0.5)),
+            }),
+        ))];
+        for (i, lobe) in lobes.into_iter().enumerate() {
+            hittables.push(Hittable::Sphere(Sphere {
+                center: Point64::new(-4.5 + 1.8 * i as f64, 0.7, 1.5),
+                radius: 0.7,
+                material: Material::Principled(Box::new(lobe)),
+            }));
+        }
+
+        let teapot_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("resources/teapot.obj");
+        let teapot_scale = 0.6;
+        let teapot_bounds = obj_mesh_axis_bounds(&teapot_path)
+            .unwrap_or_else(|e| panic!("failed to read bounds for {}: {e}", teapot_path.display()));
+        let teapot = load_obj_triangles_with_materials(
+            &teapot_path,
+            Material::Principled(Box::new(paint)),
+            teapot_scale,
+            Vector3::new(0., -teapot_bounds.y_min * teapot_scale, -2.),
+            Subdivision {
+                levels: 1,
+                crease_angle: Some(60.),
+            },
+        )
+        .unwrap_or_else(|e| panic!("failed to load {}: {e}", teapot_path.display()));
+        hittables.push(BoundedVolumeHierarchy::create_bvh(&teapot, 0., 1.));
+
+        let aspect = DEFAULT_IMAGE_WIDTH as f64 / DEFAULT_IMAGE_HEIGHT as f64;
+        let recipe = CameraRecipe::new(
+            Point64::new(0., 3., 9.),
+            Point64::new(0., 0.8, 0.),
+            DEFAULT_VUP,
+            40.,
+            aspect,
+            DEFAULT_APERTURE,
+            DEFAULT_FOCUS_DISTANCE,
+            DEFAULT_EXPOSURE_TIME,
+        );
+
+        World {
+            image_width: DEFAULT_IMAGE_WIDTH,
+            image_height: DEFAULT_IMAGE_HEIGHT,
+            samples_per_pixel: DEFAULT_SAMPLES_PER_PIXEL,
+            background_color: LIGHT_BLUE,
+            camera: recipe.camera,
+            camera_target: recipe.look_at,
+            camera_v_up: recipe.v_up,
+            camera_vfov_deg: recipe.vfov_deg,
+            camera_aperture: recipe.aperture,
+            camera_focus_distance: recipe.focus_distance,
+            camera_exposure_time: recipe.exposure_time,
+            hittable: Hittable::HittableVec(HittableVec { hittables }),
+            ground_y: Some(0.0),
+        }
+    }
+
+    /// Coatings over different bases: lacquered paint, clear-coated copper, a rough satin
+    /// finish and amber varnish over wood, which absorbs blue light in the coating.
+    pub fn coatings() -> World {
+        let paint = Material::Lambertian(Lambertian {
+            albedo: Texture::solid(Color64::new(0.05, 0.2, 0.55)),
+        });
+        let copper = Material::Conductor(Conductor::copper(0.3));
+        let wood = Material::Lambertian(Lambertian {
+            albedo: Texture::Noise(Box::new(Noise {
+                noise_gen: PerlinGenerator::new(),
+                scale: 4.,
+                noise_type: Marble,
+            })),
+        });
+        let coated = [
+            Layered::new(paint.clone(), 1.5, 0.),
+            Layered::new(copper, 1.5, 0.),
+            Layered::new(paint, 1.5, 0.4),
+            Layered::new(wood, 1.5, 0.05).with_absorption(Color64::new(0.2, 0.8, 3.), 0.3),
+        ];
+
+        let mut hittables = vec![Hittable::Quad(Quad::new(
+            Point64::new(-10., 0., 10.),
+            Point64::new(20., 0., 0.),
+            Point64::new(0., 0., -20.),
+            Material::Lambertian(Lambertian {
+                albedo: Texture::solid(Color64::gray(0.5)),
+            }),
+        ))];
+        for (i, layered) in coated.into_iter().enumerate() {
+            hittables.push(Hittable::Sphere(Sphere {
+                center: Point64::new(-3.3 + 2.2 * i as f64, 0.9, 0.),
+                radius: 0.9,
+                material: Material::Layered(Box::new(layered)),
+            }));
+        }
+        hittables.push(Hittable::Quad(Quad::new(
+            Point64::new(-2., 6., -2.),
+            Point64::new(4., 0., 0.),
+            Point64::new(0., 0., 2.),
+            Material::DiffuseLight(DiffuseLight::new(Color64::gray(4.))),
+        )));
+
+        let aspect = DEFAULT_IMAGE_WIDTH as f64 / DEFAULT_IMAGE_HEIGHT as f64;
+        let recipe = CameraRecipe::new(
+            Point64::new(0., 2.5, 9.),
+            Point64::new(0., 0.8, 0.),
+            DEFAULT_VUP,
+            40.,
+            aspect,
+            DEFAULT_APERTURE,
+            DEFAULT_FOCUS_DISTANCE,
+            DEFAULT_EXPOSURE_TIME,
+        );
+
+        World {
+            image_width: DEFAULT_IMAGE_WIDTH,
+            image_height: DEFAULT_IMAGE_HEIGHT,
+            samples_per_pixel: DEFAULT_SAMPLES_PER_PIXEL,
+            background_color: LIGHT_BLUE,
+            camera: recipe.camera,
+            camera_target: recipe.look_at,
+            camera_v_up: recipe.v_up,
+            camera_vfov_deg: recipe.vfov_deg,
+            camera_aperture: recipe.aperture,
+            camera_focus_distance: recipe.focus_distance,
+            camera_exposure_time: recipe.exposure_time,
+            hittable: Hittable::HittableVec(HittableVec { hittables }),
             ground_y: Some(0.0),
         }
     }